@@ -0,0 +1,312 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # Benchmark Harness
+//!
+//! This module compares different [strategies](crate::strategies::Strategy) on randomly generated
+//! reconfiguration problems of increasing size. Each problem is generated with
+//! [`ZooTopology::random`], using the scenario [`Scenario::FullMesh2RouteReflector`]. The trial
+//! index is used as the seed, such that all strategies are compared on the same problems.
+//!
+//! ```rust,no_run
+//! use snowcap::bench::{run_scaling, to_csv};
+//! use snowcap::strategies::StrategyTRTA;
+//!
+//! let results = run_scaling::<StrategyTRTA>(&[10, 20, 40], 5);
+//! println!("{}", to_csv(&results));
+//! ```
+//!
+//! Similarly, [`run_candidate_evaluation`] compares both
+//! [`CandidateEvaluation`](crate::strategies::CandidateEvaluation) modes of
+//! [`StrategyTRTA`](crate::strategies::StrategyTRTA), and [`run_forwarding_state`] compares
+//! extracting the forwarding state from scratch with updating it incrementally.
+//!
+//! *This module is only available if the `"bench"` feature is enabled!*
+
+use crate::hard_policies::HardPolicy;
+use crate::netsim::config::Config;
+use crate::netsim::{ForwardingState, Network};
+use crate::strategies::{CandidateEvaluation, Strategy, StrategyTRTA};
+use crate::topology_zoo::{Scenario, ZooTopology};
+use crate::Stopper;
+
+use log::*;
+use std::time::{Duration, Instant};
+
+/// Number of external routers in each generated problem.
+const NUM_EXTERNAL: usize = 3;
+/// Maximum link weight of the generated problems.
+const MAX_WEIGHT: u32 = 100;
+/// Time budget of a single run.
+const TIME_BUDGET: Duration = Duration::from_secs(60);
+
+/// Result of a single run of a strategy on a generated problem.
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    /// Name of the strategy
+    pub strategy: String,
+    /// Number of internal routers in the network
+    pub size: usize,
+    /// Index of the trial, which is also the seed of the generated problem
+    pub trial: usize,
+    /// Number of modifiers of the reconfiguration
+    pub num_modifiers: usize,
+    /// Whether the strategy found a valid ordering
+    pub success: bool,
+    /// Time spent to setup the strategy and to find the ordering
+    pub time: Duration,
+    /// Number of states explored by the strategy
+    pub num_states: usize,
+}
+
+impl BenchResult {
+    /// Header of the CSV table, matching [`BenchResult::to_csv_row`].
+    pub const CSV_HEADER: &'static str =
+        "strategy,size,trial,num_modifiers,success,time_ms,num_states";
+
+    /// Format the result as a single row of a CSV table.
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "\"{}\",{},{},{},{},{:.3},{}",
+            self.strategy.replace('"', "\"\""),
+            self.size,
+            self.trial,
+            self.num_modifiers,
+            self.success,
+            self.time.as_secs_f64() * 1000.0,
+            self.num_states
+        )
+    }
+}
+
+/// Result of applying the modifiers of a generated problem, and computing the forwarding state
+/// after each of them (see [`run_forwarding_state`]).
+#[derive(Debug, Clone)]
+pub struct FwStateBenchResult {
+    /// Number of internal routers in the network
+    pub size: usize,
+    /// Index of the trial, which is also the seed of the generated problem
+    pub trial: usize,
+    /// Number of modifiers, after which the forwarding state was computed
+    pub num_modifiers: usize,
+    /// Time spent to extract the forwarding state from scratch
+    pub full: Duration,
+    /// Time spent to update the forwarding state incrementally
+    pub delta: Duration,
+}
+
+/// Format all results as a CSV table, including the header.
+pub fn to_csv(results: &[BenchResult]) -> String {
+    let mut csv = String::from(BenchResult::CSV_HEADER);
+    csv.push('\n');
+    for result in results.iter() {
+        csv.push_str(&result.to_csv_row());
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Run the strategy `S` on `trials` generated problems for each of the `sizes` (number of internal
+/// routers), and record the time and the number of explored states. A failure of the strategy is
+/// recorded as an unsuccessful run. Problems, which cannot be generated, are skipped.
+pub fn run_scaling<S: Strategy>(sizes: &[usize], trials: usize) -> Vec<BenchResult> {
+    run::<S, _>(std::any::type_name::<S>(), sizes, trials, |_| {})
+}
+
+/// Compare both [`CandidateEvaluation`] modes of [`StrategyTRTA`] on the same problems as
+/// [`run_scaling`]. Since every router in the generated problems is affected by the
+/// reconfiguration, undoing a group is expensive on large networks. The results of both modes are
+/// returned one after the other, and named `"StrategyTRTA (Undo)"` and
+/// `"StrategyTRTA (SpeculativeClone)"`.
+pub fn run_candidate_evaluation(sizes: &[usize], trials: usize) -> Vec<BenchResult> {
+    [CandidateEvaluation::Undo, CandidateEvaluation::SpeculativeClone]
+        .iter()
+        .flat_map(|&mode| {
+            let name = format!("StrategyTRTA ({:?})", mode);
+            run::<StrategyTRTA, _>(&name, sizes, trials, |s| s.set_candidate_evaluation(mode))
+        })
+        .collect()
+}
+
+/// Apply the modifiers of the same problems as [`run_scaling`] one after the other, and compare
+/// the time to extract the forwarding state from scratch ([`Network::get_forwarding_state`]) with
+/// the time to update the previous one ([`Network::get_forwarding_state_delta`]). Both times
+/// include computing the route of every router towards every prefix. A trial stops at the first
+/// modifier, which cannot be applied. Use a size of 50 to see the difference on larger networks.
+pub fn run_forwarding_state(sizes: &[usize], trials: usize) -> Vec<FwStateBenchResult> {
+    let mut results = Vec::with_capacity(sizes.len() * trials);
+    for &size in sizes.iter() {
+        for trial in 0..trials {
+            let (mut net, final_config, _) = match generate(size, trial) {
+                Some(problem) => problem,
+                None => continue,
+            };
+            let modifiers = net.current_config().get_diff(&final_config).modifiers;
+
+            let mut result = FwStateBenchResult {
+                size,
+                trial,
+                num_modifiers: 0,
+                full: Duration::default(),
+                delta: Duration::default(),
+            };
+            let mut fw_state = net.get_forwarding_state();
+            for modifier in modifiers.iter() {
+                let snapshot = net.snapshot();
+                if net.apply_modifier(modifier).is_err() {
+                    warn!("Cannot apply the modifier of size {} (trial {})", size, trial);
+                    break;
+                }
+
+                let start = Instant::now();
+                let mut full = net.get_forwarding_state();
+                compute_all_routes(&net, &mut full);
+                result.full += start.elapsed();
+
+                let start = Instant::now();
+                fw_state = net.get_forwarding_state_delta(fw_state, snapshot);
+                compute_all_routes(&net, &mut fw_state);
+                result.delta += start.elapsed();
+                debug_assert!(fw_state == full);
+
+                result.num_modifiers += 1;
+            }
+            info!(
+                "Forwarding state on size {} (trial {}): full: {:?}, delta: {:?}",
+                size, trial, result.full, result.delta
+            );
+            results.push(result);
+        }
+    }
+    results
+}
+
+/// Compute the route of every router towards every prefix, which fills the cache of `fw_state`.
+fn compute_all_routes(net: &Network, fw_state: &mut ForwardingState) {
+    for router in net.get_routers() {
+        for prefix in net.get_known_prefixes() {
+            let _ = fw_state.get_route(router, *prefix);
+        }
+    }
+}
+
+/// Generate the problem of the given size for the trial. `None` is returned if the problem cannot
+/// be generated.
+fn generate(size: usize, trial: usize) -> Option<(Network, Config, HardPolicy)> {
+    let mut topo = ZooTopology::random(size, NUM_EXTERNAL, trial as u64);
+    match topo.apply_scenario(Scenario::FullMesh2RouteReflector, true, MAX_WEIGHT, 1, 1.0) {
+        Ok(problem) => Some(problem),
+        Err(e) => {
+            warn!("Cannot generate the problem of size {} (trial {}): {}", size, trial, e);
+            None
+        }
+    }
+}
+
+/// Run the strategy `S`, configured with `setup`, on the generated problems. See [`run_scaling`].
+fn run<S: Strategy, F: Fn(&mut S)>(
+    strategy: &str,
+    sizes: &[usize],
+    trials: usize,
+    setup: F,
+) -> Vec<BenchResult> {
+    let mut results = Vec::with_capacity(sizes.len() * trials);
+    for &size in sizes.iter() {
+        for trial in 0..trials {
+            let (net, final_config, hard_policy) = match generate(size, trial) {
+                Some(problem) => problem,
+                None => continue,
+            };
+            let modifiers = net.current_config().get_diff(&final_config).modifiers;
+            let num_modifiers = modifiers.len();
+
+            let start = Instant::now();
+            let (success, num_states) = match S::new(net, modifiers, hard_policy, Some(TIME_BUDGET))
+            {
+                Ok(mut s) => {
+                    setup(&mut s);
+                    (s.work(Stopper::new()).is_ok(), s.num_states())
+                }
+                Err(_) => (false, 0),
+            };
+            let time = start.elapsed();
+            info!("{} on size {} (trial {}): success: {}", strategy, size, trial, success);
+
+            results.push(BenchResult {
+                strategy: strategy.to_string(),
+                size,
+                trial,
+                num_modifiers,
+                success,
+                time,
+                num_states,
+            });
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::modifier_ordering::SimpleOrdering;
+    use crate::strategies::PushBackTreeStrategy;
+
+    #[test]
+    fn scaling() {
+        let results = run_scaling::<PushBackTreeStrategy<SimpleOrdering>>(&[4, 6], 2);
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results.iter().map(|r| (r.size, r.trial)).collect::<Vec<_>>(),
+            vec![(4, 0), (4, 1), (6, 0), (6, 1)]
+        );
+
+        let csv = to_csv(&results);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], BenchResult::CSV_HEADER);
+        // the strategy name may contain commas, and is quoted
+        assert!(lines[1].starts_with("\"snowcap::strategies::"));
+    }
+
+    #[test]
+    fn candidate_evaluation() {
+        let results = run_candidate_evaluation(&[4], 2);
+        assert_eq!(
+            results.iter().map(|r| (r.strategy.as_str(), r.trial)).collect::<Vec<_>>(),
+            vec![
+                ("StrategyTRTA (Undo)", 0),
+                ("StrategyTRTA (Undo)", 1),
+                ("StrategyTRTA (SpeculativeClone)", 0),
+                ("StrategyTRTA (SpeculativeClone)", 1)
+            ]
+        );
+        // both modes run on the same problems
+        assert_eq!(
+            results[..2].iter().map(|r| r.num_modifiers).collect::<Vec<_>>(),
+            results[2..].iter().map(|r| r.num_modifiers).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn forwarding_state() {
+        let results = run_forwarding_state(&[50], 1);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].size, 50);
+        assert!(results[0].num_modifiers > 0);
+    }
+}
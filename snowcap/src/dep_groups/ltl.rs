@@ -0,0 +1,589 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # LTL Formulas
+//!
+//! This module contains the functions to build the LTL formulas which are passed to the
+//! [`LtlSolver`](super::solver::LtlSolver) by [`StrategyTRTA`](crate::strategies::StrategyTRTA).
+//! Each group `i` is represented by the variables `x{i}`, which is set in the state in which the
+//! group is executed, and `e{i}`, which holds in the single state in which only `x{i}` is set.
+
+use super::strategy_trta::routers_from_error;
+use crate::netsim::config::ConfigModifier;
+use crate::netsim::{NetworkError, RouterId};
+
+use std::collections::HashMap;
+
+/// Prefix of the variable `x{i}` of each group `i`.
+const VAR_PREFIX: &str = "x";
+
+/// Prefix of the variable `e{i}` of each group `i`.
+const EXEC_VAR_PREFIX: &str = "e";
+
+/// Returns the name of the variable, which is set in the state in which group `i` is executed.
+/// Together with [`parse_var_name`], this is the single source of truth for the names of the
+/// variables, which are read back from the model returned by the solver.
+pub(crate) fn var_name(i: usize) -> String {
+    format!("{}{}", VAR_PREFIX, i)
+}
+
+/// Parse the index of the group from the name of its variable (see [`var_name`]). If `name` is
+/// not the variable of any group, `None` is returned.
+pub(crate) fn parse_var_name(name: &str) -> Option<usize> {
+    parse_index(name.strip_prefix(VAR_PREFIX)?)
+}
+
+/// Returns the name of the variable, which holds in the single state in which group `i` is
+/// executed, and in which no other group is executed (see [`mutual_exclusion_formula`]). All
+/// learned constraints are expressed over these variables. Together with
+/// [`parse_exec_var_name`], this is the single source of truth for their names.
+pub(crate) fn exec_var_name(i: usize) -> String {
+    format!("{}{}", EXEC_VAR_PREFIX, i)
+}
+
+/// Parse the index of the group from the name of its execution variable (see [`exec_var_name`]).
+/// If `name` is not the execution variable of any group, `None` is returned.
+pub(crate) fn parse_exec_var_name(name: &str) -> Option<usize> {
+    parse_index(name.strip_prefix(EXEC_VAR_PREFIX)?)
+}
+
+/// Parse the index of a variable, after its prefix was stripped.
+fn parse_index(index: &str) -> Option<usize> {
+    // only accept the exact representation of the variable names, i.e., without a sign or leading
+    // zeros
+    if index.is_empty()
+        || !index.chars().all(|c| c.is_ascii_digit())
+        || (index.len() > 1 && index.starts_with('0'))
+    {
+        return None;
+    }
+    index.parse().ok()
+}
+
+/// Build the constraints that in every state, exactly one group is executed: `e{i}` holds if and
+/// only if `x{i}` is the only variable set, and in every state, some `e{i}` holds which never holds
+/// again.
+pub(crate) fn mutual_exclusion_formula(n: usize) -> String {
+    let mut formula_parts = Vec::new();
+    for i in 0..n {
+        let negations =
+            (0..n).filter(|j| *j != i).map(|j| format!("! {}", var_name(j))).collect::<Vec<_>>();
+        formula_parts.push(format!(
+            "G(({} & {}) <-> ({}))",
+            var_name(i),
+            negations.join(" & "),
+            exec_var_name(i)
+        ));
+    }
+    formula_parts.push(format!(
+        "G({})",
+        (0..n)
+            .map(|i| format!("(({}) & N(G(! {})))", exec_var_name(i), exec_var_name(i)))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    ));
+    formula_parts.join(" & ")
+}
+
+/// Build the constraint that there are at least `n` states. Together with the
+/// [`mutual_exclusion_formula`], this implies that every group is executed exactly once, without
+/// requiring `F x{i}` for every group.
+///
+/// The constraint always covers all `n` groups, even if the exploration is stuck after some prefix
+/// of groups was applied. The formula describes the complete ordering from the initial state (no
+/// prefix is fixed in the formula), and the solver must return a complete ordering, with which the
+/// exploration is reset. Its size does not depend on the groups already applied.
+pub(crate) fn liveness_formula(n: usize) -> String {
+    (0..n.saturating_sub(1)).fold("true".to_string(), |acc, _| format!("X({})", acc))
+}
+
+/// Build the constraints that every model of the formula is an ordering of all `n` groups. This
+/// is the part of the formula which is always passed to the solver.
+pub(crate) fn ordering_formula(n: usize) -> String {
+    format!("{} & {}\n", mutual_exclusion_formula(n), liveness_formula(n))
+}
+
+/// Formula passed to the solver, consisting of the [`ordering_formula`] as the invariant base, and
+/// all constraints learned so far. The base is stored only once, and the query is composed as
+/// `(learned_1) & ... & (learned_k) & base` whenever it is passed to the solver.
+#[derive(Debug, Clone)]
+pub(crate) struct LearnedFormula {
+    base: String,
+    learned: Vec<String>,
+    max_learned: usize,
+}
+
+impl LearnedFormula {
+    /// Create a new formula for `n` groups without any learned constraints. Each pair `(i, j)` of
+    /// `precedence` requires group `i` to be executed before group `j` (see
+    /// [`precedence_formula`]). These constraints are part of the base, and are not counted as
+    /// learned constraints.
+    pub(crate) fn with_precedence(n: usize, precedence: &[(usize, usize)]) -> Self {
+        let mut base = String::new();
+        for (before, after) in precedence.iter() {
+            base.push_str(&precedence_formula(*before, *after));
+            base.push_str(" & ");
+        }
+        base.push_str(&ordering_formula(n));
+        Self { base, learned: Vec::new(), max_learned: usize::MAX }
+    }
+
+    /// Keep only the `max_learned` most recent learned constraints (by default, all are kept).
+    /// Whenever a new constraint is learned, the oldest ones are dropped. The most recent
+    /// constraint is always kept, even if `max_learned` is zero.
+    pub(crate) fn set_max_learned(&mut self, max_learned: usize) {
+        self.max_learned = max_learned.max(1);
+        self.truncate_learned();
+    }
+
+    /// Add a learned constraint to the formula. If there are more than the maximum number of
+    /// learned constraints, the oldest ones are dropped (see [`LearnedFormula::set_max_learned`]).
+    pub(crate) fn learn(&mut self, constraint: String) {
+        self.learned.push(constraint);
+        self.truncate_learned();
+    }
+
+    /// Drop the oldest learned constraints, until at most `max_learned` remain.
+    fn truncate_learned(&mut self) {
+        if self.learned.len() > self.max_learned {
+            let num_dropped = self.learned.len() - self.max_learned;
+            self.learned.drain(..num_dropped);
+        }
+    }
+
+    /// Replace the last learned constraint (e.g., with a more general one).
+    pub(crate) fn replace_last(&mut self, constraint: String) {
+        match self.learned.last_mut() {
+            Some(last) => *last = constraint,
+            None => self.learned.push(constraint),
+        }
+    }
+
+    /// Returns the total length (in bytes) of all learned constraints, excluding the base.
+    pub(crate) fn learned_len(&self) -> usize {
+        self.learned.iter().map(|c| c.len()).sum()
+    }
+
+    /// Returns the length (in bytes) of the query, as composed by [`LearnedFormula::query`].
+    pub(crate) fn query_len(&self) -> usize {
+        self.learned_len() + 5 * self.learned.len() + self.base.len()
+    }
+
+    /// Forget the oldest learned constraint. The most recent constraint is always kept, as it
+    /// reflects the last conflict. Returns `false` if nothing was forgotten.
+    pub(crate) fn forget_oldest(&mut self) -> bool {
+        if self.learned.len() > 1 {
+            self.learned.remove(0);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Compose the query for the solver.
+    pub(crate) fn query(&self) -> String {
+        let mut query = String::with_capacity(self.learned_len() + 5 * self.learned.len());
+        for constraint in self.learned.iter() {
+            query.push('(');
+            query.push_str(constraint);
+            query.push_str(") & ");
+        }
+        query.push_str(&self.base);
+        query
+    }
+}
+
+/// Map each router to the indices of all groups which touch it, i.e., where any modifier of the
+/// group references the router (see [`ConfigModifier::routers`]). Problems at any of these
+/// routers are assumed to be resolvable by the group. The indices of each router are sorted.
+pub(crate) fn router_groups(groups: &[Vec<ConfigModifier>]) -> HashMap<RouterId, Vec<usize>> {
+    index_routers(
+        groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, group)| group.iter().map(move |modifier| (i, modifier.routers()))),
+    )
+}
+
+/// Map each router to the indices of all `session_pairs` which contain it.
+pub(crate) fn router_groups_from_pairs(
+    session_pairs: &[(RouterId, RouterId)],
+) -> HashMap<RouterId, Vec<usize>> {
+    index_routers(session_pairs.iter().map(|(source, target)| vec![*source, *target]).enumerate())
+}
+
+fn index_routers(
+    routers: impl Iterator<Item = (usize, Vec<RouterId>)>,
+) -> HashMap<RouterId, Vec<usize>> {
+    let mut result: HashMap<RouterId, Vec<usize>> = HashMap::new();
+    for (i, routers) in routers {
+        for router in routers {
+            let groups = result.entry(router).or_default();
+            if groups.last() != Some(&i) {
+                groups.push(i);
+            }
+        }
+    }
+    result
+}
+
+/// Returns the indices of all groups (see [`router_groups`]) which touch any of the `routers`.
+pub(crate) fn sessions_touching(
+    router_groups: &HashMap<RouterId, Vec<usize>>,
+    routers: &[RouterId],
+) -> Vec<usize> {
+    let mut result: Vec<usize> =
+        routers.iter().filter_map(|r| router_groups.get(r)).flatten().cloned().collect();
+    result.sort_unstable();
+    result.dedup();
+    result
+}
+
+/// Returns the groups in `matched`, which may fix the problem caused by `doing`, i.e., all of them
+/// except `doing` itself and the groups in the `prefix`, which were already applied (or tried).
+pub(crate) fn fixing_groups(matched: &[usize], doing: usize, prefix: &[usize]) -> Vec<usize> {
+    matched.iter().filter(|idx| **idx != doing && !prefix.contains(idx)).cloned().collect()
+}
+
+/// Build the constraint learned when executing group `doing` causes a black hole (or a forwarding
+/// loop) at a router, and the groups `matched` change a session of this router: after `doing`,
+/// one of the `matched` groups must be executed before. Groups which are part of `prefix` (or
+/// `doing` itself) cannot fix the problem, and are ignored. If no group remains, `None` is
+/// returned.
+pub(crate) fn blackhole_constraint(
+    matched: &[usize],
+    doing: usize,
+    prefix: &[usize],
+) -> Option<String> {
+    let fixes = fixing_groups(matched, doing, prefix)
+        .iter()
+        .map(|idx| format!("N(G(! {}))", exec_var_name(*idx)))
+        .collect::<Vec<_>>();
+    if fixes.is_empty() {
+        None
+    } else {
+        Some(format!("G({} -> ({}))", exec_var_name(doing), fixes.join(" | ")))
+    }
+}
+
+/// Returns the groups (see [`fixing_groups`]) which may fix each problem of the error, where
+/// `router_groups` maps each router to the groups touching it (see [`router_groups`]). Every
+/// problem is a set of routers, either a single router with a black hole, all routers of a
+/// forwarding loop, or all routers involved in another error. Problems without any fix are
+/// omitted.
+pub(crate) fn conflict_fixes(
+    error: &NetworkError,
+    router_groups: &HashMap<RouterId, Vec<usize>>,
+    doing: usize,
+    done: &[usize],
+) -> Vec<Vec<usize>> {
+    let problems: Vec<Vec<RouterId>> = match error {
+        NetworkError::ForwardingBlackHole(nodes) => nodes.iter().map(|node| vec![*node]).collect(),
+        NetworkError::ForwardingLoops(loops) => loops.clone(),
+        e => vec![routers_from_error(e)],
+    };
+    problems
+        .iter()
+        .map(|routers| fixing_groups(&sessions_touching(router_groups, routers), doing, done))
+        .filter(|fixes| !fixes.is_empty())
+        .collect()
+}
+
+/// Compile a violation of the hard policies, caused by applying group `doing` after the groups in
+/// `done`, into an LTL conflict clause. For every problem of the error, the clause requires that
+/// one of the groups, which affect the routers of the problem, is applied before `doing` (see
+/// [`conflict_fixes`]). The index of each group is its position in `session_pairs`, which contains
+/// the pair of routers affected by each group. If no group can fix any of the problems, `None` is
+/// returned.
+///
+/// The clause uses the variables `e{i}` of the formulas built by
+/// [`StrategyTRTA`](crate::strategies::StrategyTRTA), i.e., `e{i}` holds in the single state in
+/// which group `i` is applied.
+pub fn policy_error_to_ltl(
+    error: &NetworkError,
+    session_pairs: &[(RouterId, RouterId)],
+    doing: usize,
+    done: &[usize],
+) -> Option<String> {
+    let clauses = conflict_fixes(error, &router_groups_from_pairs(session_pairs), doing, done)
+        .iter()
+        .filter_map(|fixes| blackhole_constraint(fixes, doing, done))
+        .collect::<Vec<_>>();
+    if clauses.is_empty() {
+        None
+    } else {
+        Some(clauses.join(" & "))
+    }
+}
+
+/// Build a clause that forbids executing the groups in `prefix` as the first groups, immediately
+/// followed by group `doing`. This is used if no [`blackhole_constraint`] can be learned.
+pub(crate) fn block_prefix_formula(prefix: &[usize], doing: usize) -> String {
+    let mut formula = exec_var_name(doing);
+    for idx in prefix.iter().rev() {
+        formula = format!("{} & X({})", exec_var_name(*idx), formula);
+    }
+    format!("!({})", formula)
+}
+
+/// Build the constraint that group `before` is executed before group `after`: `x{after}` does not
+/// hold until `x{before}` holds.
+pub(crate) fn precedence_formula(before: usize, after: usize) -> String {
+    format!("(! {} U {})", var_name(after), var_name(before))
+}
+
+/// Build a clause that forbids the given sequence of groups to be applied in exactly this order,
+/// starting at the first step.
+pub(crate) fn block_sequence_formula(sequence: &[usize]) -> String {
+    let mut formula = String::from("true");
+    for idx in sequence.iter().rev() {
+        formula = format!("{} & X({})", exec_var_name(*idx), formula);
+    }
+    format!("!({})", formula)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::netsim::config::ConfigExpr;
+
+    #[test]
+    fn ordering_formula_groups() {
+        for n in [3, 6, 9].iter().cloned() {
+            let formula = ordering_formula(n);
+            for i in 0..n {
+                assert!(formula.contains(&format!("G((x{} & ", i)));
+                assert!(formula.contains(&format!("((e{}) & N(G(! e{})))", i, i)));
+            }
+            assert!(!formula.contains(&format!("x{}", n)));
+            assert!(!formula.contains(&format!("e{}", n)));
+            assert_eq!(formula.matches("X(").count(), n - 1);
+        }
+        assert_eq!(
+            mutual_exclusion_formula(2),
+            "G((x0 & ! x1) <-> (e0)) & G((x1 & ! x0) <-> (e1)) & \
+             G(((e0) & N(G(! e0))) | ((e1) & N(G(! e1))))"
+        );
+        assert_eq!(liveness_formula(1), "true");
+        assert_eq!(liveness_formula(3), "X(X(true))");
+    }
+
+    #[test]
+    fn sessions() {
+        let r = |i: usize| RouterId::new(i);
+        let sessions = router_groups_from_pairs(&[(r(0), r(1)), (r(1), r(2)), (r(2), r(3))]);
+        assert_eq!(sessions_touching(&sessions, &[r(1)]), vec![0, 1]);
+        assert_eq!(sessions_touching(&sessions, &[r(0), r(3)]), vec![0, 2]);
+        assert_eq!(sessions_touching(&sessions, &[r(4)]), Vec::<usize>::new());
+        assert_eq!(sessions_touching(&sessions, &[r(1), r(2)]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn router_touched_by_multiple_groups() {
+        use crate::netsim::config::ConfigModifier::*;
+        use crate::netsim::BgpSessionType::IBgpPeer;
+        let r = |i: usize| RouterId::new(i);
+        let session =
+            |a, b| ConfigExpr::BgpSession { source: r(a), target: r(b), session_type: IBgpPeer };
+        let groups = vec![
+            vec![Insert(session(0, 1))],
+            vec![Remove(session(2, 3)), Insert(session(1, 2))],
+            vec![Insert(session(1, 4)), Remove(session(4, 1))],
+        ];
+        let map = router_groups(&groups);
+        assert_eq!(map.get(&r(1)), Some(&vec![0, 1, 2]));
+        assert_eq!(map.get(&r(2)), Some(&vec![1]));
+        assert_eq!(map.get(&r(4)), Some(&vec![2]));
+        assert_eq!(map.get(&r(5)), None);
+        assert_eq!(sessions_touching(&map, &[r(1)]), vec![0, 1, 2]);
+        assert_eq!(sessions_touching(&map, &[r(3), r(4)]), vec![1, 2]);
+    }
+
+    #[test]
+    fn var_names() {
+        for i in [0, 1, 9, 10, 42, 1000, usize::MAX].iter().cloned() {
+            assert_eq!(parse_var_name(&var_name(i)), Some(i));
+        }
+        assert_eq!(var_name(3), "x3");
+        for name in ["x", "e1", "!x1", "x01", "x+1", "x1a", "y1", ""].iter() {
+            assert_eq!(parse_var_name(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn exec_var_names() {
+        for i in [0, 1, 9, 10, 42, 1000, usize::MAX].iter().cloned() {
+            assert_eq!(parse_exec_var_name(&exec_var_name(i)), Some(i));
+            // the two kinds of variables are never confused
+            assert_eq!(parse_var_name(&exec_var_name(i)), None);
+            assert_eq!(parse_exec_var_name(&var_name(i)), None);
+        }
+        assert_eq!(exec_var_name(3), "e3");
+        for name in ["e", "x1", "!e1", "e01", "e+1", "e1a", ""].iter() {
+            assert_eq!(parse_exec_var_name(name), None, "{}", name);
+        }
+    }
+
+    #[test]
+    fn conflict_fixes_per_variant() {
+        use crate::netsim::config::ConfigModifier::*;
+        use crate::netsim::route_map::RouteMapBuilder;
+        use crate::netsim::route_map::RouteMapDirection::Outgoing;
+        use crate::netsim::{BgpSessionType::EBgp, Prefix};
+        let r = |i: usize| RouterId::new(i);
+        let groups = vec![
+            vec![Update {
+                from: ConfigExpr::IgpLinkWeight { source: r(0), target: r(1), weight: 1.0 },
+                to: ConfigExpr::IgpLinkWeight { source: r(0), target: r(1), weight: 10.0 },
+            }],
+            vec![Insert(ConfigExpr::StaticRoute { router: r(2), prefix: Prefix(0), target: r(3) })],
+            vec![Remove(ConfigExpr::BgpRouteMap {
+                router: r(4),
+                direction: Outgoing,
+                map: RouteMapBuilder::new().order(10).deny().build(),
+            })],
+            vec![Insert(ConfigExpr::BgpSession { source: r(5), target: r(6), session_type: EBgp })],
+            vec![Remove(ConfigExpr::BgpSession { source: r(7), target: r(8), session_type: EBgp })],
+        ];
+        let map = router_groups(&groups);
+        let black_hole = |i| NetworkError::ForwardingBlackHole(vec![r(i)]);
+        // a problem at any router affected by the modifier of a group can be fixed by that group
+        assert_eq!(conflict_fixes(&black_hole(1), &map, 4, &[]), vec![vec![0]]);
+        assert_eq!(conflict_fixes(&black_hole(3), &map, 4, &[]), vec![vec![1]]);
+        assert_eq!(conflict_fixes(&black_hole(4), &map, 4, &[]), vec![vec![2]]);
+        assert_eq!(conflict_fixes(&black_hole(6), &map, 4, &[]), vec![vec![3]]);
+        let forwarding_loop = NetworkError::ForwardingLoops(vec![vec![r(0), r(2)]]);
+        assert_eq!(conflict_fixes(&forwarding_loop, &map, 4, &[]), vec![vec![0, 1]]);
+        // groups already applied cannot fix the problem
+        assert!(conflict_fixes(&black_hole(3), &map, 4, &[1]).is_empty());
+        assert_eq!(
+            policy_error_to_ltl(
+                &black_hole(2),
+                &[(r(0), r(1)), (r(2), r(3)), (r(4), r(4)), (r(5), r(6)), (r(7), r(8))],
+                4,
+                &[]
+            ),
+            Some("G(e4 -> (N(G(! e1))))".to_string())
+        );
+    }
+
+    #[test]
+    fn blackhole() {
+        assert_eq!(
+            blackhole_constraint(&[0, 1, 2, 3], 2, &[0]),
+            Some("G(e2 -> (N(G(! e1)) | N(G(! e3))))".to_string())
+        );
+        assert_eq!(blackhole_constraint(&[0, 2], 2, &[0]), None);
+    }
+
+    #[test]
+    fn block_prefix() {
+        // the group is blocked as the very first one
+        assert_eq!(block_prefix_formula(&[], 2), "!(e2)");
+        // every group of the prefix is followed immediately by the next one, and finally by `doing`
+        assert_eq!(block_prefix_formula(&[3], 2), "!(e3 & X(e2))");
+        assert_eq!(block_prefix_formula(&[1, 0], 2), "!(e1 & X(e0 & X(e2)))");
+        assert_eq!(block_prefix_formula(&[4, 1, 0], 3), "!(e4 & X(e1 & X(e0 & X(e3))))");
+    }
+
+    #[test]
+    fn block_sequence() {
+        assert_eq!(block_sequence_formula(&[]), "!(true)");
+        assert_eq!(block_sequence_formula(&[2]), "!(e2 & X(true))");
+        assert_eq!(block_sequence_formula(&[2, 0, 1]), "!(e2 & X(e0 & X(e1 & X(true))))");
+    }
+
+    #[test]
+    fn max_learned() {
+        let base = ordering_formula(2);
+        let mut formula = LearnedFormula::with_precedence(2, &[]);
+        for i in 0..4 {
+            formula.learn(format!("c{}", i));
+        }
+        assert_eq!(formula.query(), format!("(c0) & (c1) & (c2) & (c3) & {}", base));
+        // only the most recent constraints are kept
+        formula.set_max_learned(2);
+        assert_eq!(formula.query(), format!("(c2) & (c3) & {}", base));
+        formula.learn("c4".to_string());
+        assert_eq!(formula.query(), format!("(c3) & (c4) & {}", base));
+        // the most recent constraint is always kept
+        formula.set_max_learned(0);
+        assert_eq!(formula.query(), format!("(c4) & {}", base));
+    }
+
+    #[test]
+    fn learned_formula() {
+        let base = ordering_formula(3);
+        let mut formula = LearnedFormula::with_precedence(3, &[]);
+        assert_eq!(formula.query(), base);
+        assert_eq!(formula.learned_len(), 0);
+        let clauses = vec![block_prefix_formula(&[0], 1), block_sequence_formula(&[2, 1, 0])];
+        for clause in clauses.iter() {
+            formula.learn(clause.clone());
+        }
+        let query = formula.query();
+        // the base block appears exactly once, no matter how many constraints were learned
+        assert_eq!(query.matches(&base).count(), 1);
+        assert!(query.ends_with(&base));
+        assert_eq!(query.matches(&mutual_exclusion_formula(3)).count(), 1);
+        for clause in clauses.iter() {
+            assert!(query.contains(&format!("({})", clause)));
+        }
+        assert_eq!(formula.learned_len(), clauses.iter().map(|c| c.len()).sum::<usize>());
+        formula.replace_last("true".to_string());
+        assert!(formula.query().starts_with(&format!("({}) & (true) & ", clauses[0])));
+        assert_eq!(formula.query_len(), formula.query().len());
+
+        // the most recent constraint is never forgotten
+        assert!(formula.forget_oldest());
+        assert_eq!(formula.query(), format!("(true) & {}", base));
+        assert!(!formula.forget_oldest());
+        assert_eq!(formula.query_len(), formula.query().len());
+
+        // precedence constraints are part of the base
+        let formula = LearnedFormula::with_precedence(3, &[(2, 0)]);
+        assert_eq!(formula.learned_len(), 0);
+        assert_eq!(formula.query(), format!("(! x0 U x2) & {}", base));
+    }
+
+    #[test]
+    fn policy_error() {
+        let r = |i: usize| RouterId::new(i);
+        // groups 0 and 1 touch router 1, groups 1 and 2 touch router 2
+        let session_pairs = vec![(r(0), r(1)), (r(1), r(2)), (r(2), r(3))];
+        let black_hole = NetworkError::ForwardingBlackHole(vec![r(1)]);
+        assert_eq!(
+            policy_error_to_ltl(&black_hole, &session_pairs, 0, &[]),
+            Some("G(e0 -> (N(G(! e1))))".to_string())
+        );
+        // the only fix was already applied
+        assert_eq!(policy_error_to_ltl(&black_hole, &session_pairs, 0, &[1]), None);
+        // every black hole is a separate problem
+        let black_holes = NetworkError::ForwardingBlackHole(vec![r(1), r(3)]);
+        assert_eq!(
+            policy_error_to_ltl(&black_holes, &session_pairs, 1, &[]),
+            Some("G(e1 -> (N(G(! e0)))) & G(e1 -> (N(G(! e2))))".to_string())
+        );
+        // all routers of a loop are a single problem
+        let forwarding_loop = NetworkError::ForwardingLoop(vec![r(0), r(3)]);
+        assert_eq!(
+            policy_error_to_ltl(&forwarding_loop, &session_pairs, 1, &[]),
+            Some("G(e1 -> (N(G(! e0)) | N(G(! e2))))".to_string())
+        );
+        assert_eq!(policy_error_to_ltl(&NetworkError::NoConvergence, &session_pairs, 0, &[]), None);
+    }
+}
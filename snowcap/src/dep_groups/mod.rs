@@ -19,8 +19,12 @@
 //!
 //! This module contains the code required for the `DepGroupsStrategy` and the `DepGroupsOptimizer`.
 
+pub(crate) mod ltl;
 pub(crate) mod optimizer;
 pub(crate) mod optimizer_trta;
+pub(crate) mod ordering_cost;
+pub(crate) mod roles;
+pub(crate) mod solver;
 pub(crate) mod strategy;
 pub(crate) mod strategy_trta;
 mod utils;
@@ -17,7 +17,7 @@
 
 //! # One Optimizer To Rule Them All
 
-use super::utils;
+use super::utils::{self, Snapshot};
 use crate::hard_policies::{HardPolicy, PolicyError};
 use crate::modifier_ordering::RandomOrdering;
 use crate::netsim::config::ConfigModifier;
@@ -27,6 +27,7 @@ use crate::soft_policies::SoftPolicy;
 use crate::strategies::PushBackTreeStrategy;
 use crate::{Error, Stopper};
 
+use itertools::Itertools;
 use log::*;
 use rand::prelude::*;
 use std::time::{Duration, SystemTime};
@@ -64,6 +65,8 @@ where
     max_group_solve_time: Option<Duration>,
     #[cfg(feature = "count-states")]
     num_states: usize,
+    #[cfg(feature = "debug-stack")]
+    stack_dump: Vec<String>,
 }
 
 impl<P> Optimizer<P> for OptimizerTRTA<P>
@@ -108,6 +111,8 @@ where
             max_group_solve_time,
             #[cfg(feature = "count-states")]
             num_states: 0,
+            #[cfg(feature = "debug-stack")]
+            stack_dump: Vec::new(),
         }))
     }
 
@@ -127,11 +132,18 @@ where
         );
 
         // setup the stack with a randomized frame
+        let snapshot = Snapshot::take(&net, &hard_policy);
         let mut stack =
-            vec![StackFrame { num_undo: 0, valid_groups, invalid_groups, idx: 0, soft_policy }];
+            vec![StackFrame { snapshot, valid_groups, invalid_groups, idx: 0, soft_policy }];
         let mut current_sequence: Vec<usize> = vec![];
 
         loop {
+            // remember the stack, such that it can be inspected after `work` has returned
+            #[cfg(feature = "debug-stack")]
+            {
+                self.stack_dump = fmt_stack(&stack, &current_sequence);
+            }
+
             // check for iter overflow
             if self.stop_time.as_ref().map(|time| time.elapsed().is_ok()).unwrap_or(false) {
                 // time budget is used up!
@@ -153,6 +165,7 @@ where
                     return Err(Error::ProbablyNoSafeOrdering);
                 }
             };
+            trace!("Current frame: {}", frame);
 
             // Check if there are valid options to try
             let action: StackAction<P> = if frame.idx < frame.valid_groups.len() {
@@ -177,6 +190,7 @@ where
                 }
 
                 let mut soft_policy = frame.soft_policy.clone();
+                let snapshot = Snapshot::take(&net, &hard_policy);
 
                 // perform the step
                 for modifier in self.groups[next_group_idx].iter() {
@@ -203,7 +217,7 @@ where
 
                 // Prepare the stack action with the new stack frame
                 StackAction::Push(StackFrame {
-                    num_undo: self.groups[next_group_idx].len(),
+                    snapshot,
                     valid_groups,
                     invalid_groups,
                     idx: 0,
@@ -258,10 +272,20 @@ where
                         } else {
                             // undo the net, the hard policy and pop the current sequence
                             current_sequence.pop();
-                            (0..frame.num_undo).for_each(|_| {
-                                net.undo_action().expect("Cannot undo the action on the network");
-                                hard_policy.undo();
-                            });
+                            frame
+                                .snapshot
+                                .restore(&mut net, &mut hard_policy)
+                                .expect("Cannot undo the action on the network");
+                            // the hard policy must hold exactly one state for every modifier,
+                            // which is still applied
+                            debug_assert_eq!(
+                                hard_policy.num_steps(),
+                                self.hard_policy.num_steps()
+                                    + current_sequence
+                                        .iter()
+                                        .map(|g| self.groups[*g].len())
+                                        .sum::<usize>()
+                            );
                             // pop the stack
                             stack.pop();
                         }
@@ -289,7 +313,7 @@ where
 
                     // setup the stack with a randomized frame
                     stack = vec![StackFrame {
-                        num_undo: 0,
+                        snapshot: Snapshot::take(&net, &hard_policy),
                         valid_groups,
                         invalid_groups,
                         idx: 0,
@@ -311,6 +335,16 @@ impl<P> OptimizerTRTA<P>
 where
     P: SoftPolicy + Clone,
 {
+    /// Returns an iterator over all frames of the exploration stack, as it was at the beginning of
+    /// the last iteration of [`work`](Optimizer::work), starting with the bottom frame. Each frame
+    /// is formatted as `[g0, g1, ...] frame[idx/len rem=[...]]`, where `[g0, g1, ...]` is the
+    /// sequence of groups applied before the frame. This is only available with the feature
+    /// `debug-stack`.
+    #[cfg(feature = "debug-stack")]
+    pub fn stack_frames(&self) -> impl Iterator<Item = &str> {
+        self.stack_dump.iter().map(|s| s.as_str())
+    }
+
     /// Check all remaining possible choices at the current position in the stack. For all options,
     /// we check if it is possible and what the cost is. Once finished, this function will return a
     /// tuple, where the first vector contains all the valid options, including the cost, already
@@ -332,8 +366,7 @@ where
         for group_idx in options {
             // perform the modification group
             let mut mod_ok: bool = true;
-            let mut num_undo: usize = 0;
-            let mut num_undo_policy: usize = 0;
+            let snapshot = Snapshot::take(net, hard_policy);
             let mut cost: f64 = current_cost;
             let mut sp = soft_policy.clone();
             'apply_group: for modifier in self.groups[group_idx].iter() {
@@ -341,9 +374,7 @@ where
                 {
                     self.num_states += 1;
                 }
-                num_undo += 1;
                 if net.apply_modifier(modifier).is_ok() {
-                    num_undo_policy += 1;
                     let mut fw_state = net.get_forwarding_state();
                     hard_policy.step(net, &mut fw_state).expect("cannot check policies!");
                     if !hard_policy.check() {
@@ -359,10 +390,7 @@ where
             }
 
             // undo the hard policy and the network
-            (0..num_undo_policy).for_each(|_| hard_policy.undo());
-            (0..num_undo).for_each(|_| {
-                net.undo_action().expect("Cannot perform undo!");
-            });
+            snapshot.restore(net, hard_policy).expect("Cannot perform undo!");
 
             // check if the modifier is ok
             if mod_ok {
@@ -403,13 +431,10 @@ where
         abort: Stopper,
     ) -> Option<(Vec<ConfigModifier>, Vec<usize>)> {
         // apply the modifier to the network to get the errors
-        let mut num_undo = 0;
-        let mut num_undo_policy = 0;
+        let snapshot = Snapshot::take(net, hard_policy);
         let mut errors = None;
         'apply_group: for modifier in self.groups[bad_group].iter() {
-            num_undo += 1;
             if net.apply_modifier(modifier).is_ok() {
-                num_undo_policy += 1;
                 let mut fw_state = net.get_forwarding_state();
                 hard_policy.step(net, &mut fw_state).expect("cannot check policies!");
                 if !hard_policy.check() {
@@ -423,10 +448,7 @@ where
         }
 
         // undo the hard policy and the network
-        (0..num_undo_policy).for_each(|_| hard_policy.undo());
-        (0..num_undo).for_each(|_| {
-            net.undo_action().expect("Cannot perform undo!");
-        });
+        snapshot.restore(net, hard_policy).expect("Cannot perform undo!");
 
         match errors {
             Some(errors) => {
@@ -469,8 +491,9 @@ struct StackFrame<P>
 where
     P: SoftPolicy,
 {
-    /// Number of calls to undo, in order to undo this step
-    num_undo: usize,
+    /// State of the network and the hard policy before this step, to which they are restored in
+    /// order to undo it
+    snapshot: Snapshot,
     /// valid groups
     valid_groups: Vec<(usize, f64)>,
     /// invalid groups
@@ -487,7 +510,7 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("StackFrame")
-            .field("num_undo", &self.num_undo)
+            .field("snapshot", &self.snapshot)
             .field("valid_groups", &self.valid_groups)
             .field("invalid_groups", &self.invalid_groups)
             .field("idx", &self.idx)
@@ -495,6 +518,37 @@ where
     }
 }
 
+/// Compact representation of the frame: `frame[idx/len rem=[...]]`, where `len` is the number of
+/// valid groups, and `rem` are the valid groups which are not yet explored.
+impl<P> std::fmt::Display for StackFrame<P>
+where
+    P: SoftPolicy,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "frame[{}/{} rem=[{}]]",
+            self.idx,
+            self.valid_groups.len(),
+            self.valid_groups.iter().skip(self.idx).map(|(group, _)| group).join(", ")
+        )
+    }
+}
+
+/// Format every frame of the stack, together with the sequence of groups that was applied before
+/// reaching it. The frame at position `i` corresponds to the first `i` groups of `sequence`.
+#[cfg(feature = "debug-stack")]
+fn fmt_stack<P>(stack: &[StackFrame<P>], sequence: &[usize]) -> Vec<String>
+where
+    P: SoftPolicy,
+{
+    stack
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| format!("{:?} {}", &sequence[..i.min(sequence.len())], frame))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -505,6 +559,41 @@ mod tests {
     use assert_approx_eq::assert_approx_eq;
     use std::time::Duration;
 
+    #[test]
+    fn stack_frame_display() {
+        let net = ChainGadget::<Repetition3>::net(0);
+        let soft_policy = MinimizeTrafficShift::new(&mut net.get_forwarding_state(), &net);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let frame = StackFrame {
+            snapshot: Snapshot::take(&net, &hard_policy),
+            valid_groups: vec![(4, 1.0), (1, 2.0), (3, 3.0)],
+            invalid_groups: vec![0, 2],
+            idx: 1,
+            soft_policy,
+        };
+        assert_eq!(format!("{}", frame), "frame[1/3 rem=[1, 3]]");
+    }
+
+    #[cfg(feature = "debug-stack")]
+    #[test]
+    fn stack_dump() {
+        let net = ChainGadget::<Repetition3>::net(0);
+        let soft_policy = MinimizeTrafficShift::new(&mut net.get_forwarding_state(), &net);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let frame = |valid: Vec<usize>, idx: usize| StackFrame {
+            snapshot: Snapshot::take(&net, &hard_policy),
+            valid_groups: valid.into_iter().map(|g| (g, 0.0)).collect(),
+            invalid_groups: vec![],
+            idx,
+            soft_policy: soft_policy.clone(),
+        };
+        let stack = vec![frame(vec![0, 1, 2], 1), frame(vec![2, 1], 0)];
+        assert_eq!(
+            fmt_stack(&stack, &[0, 2]),
+            vec!["[] frame[1/3 rem=[1, 2]]", "[0] frame[0/2 rem=[2, 1]]"]
+        );
+    }
+
     #[test]
     fn chain_gadget() {
         type R = Repetition5;
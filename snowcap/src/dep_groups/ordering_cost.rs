@@ -0,0 +1,96 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+//! # Ordering Cost
+//!
+//! Many valid orderings may exist for the same reconfiguration. Beyond satisfying the hard
+//! policies, operators often prefer orderings which cause less disruption while the network moves
+//! from the initial to the final configuration. An [`OrderingCost`] assigns a cost to every step of
+//! an ordering, and the total cost of the ordering is the sum over all steps (see [`total_cost`]).
+//! [`StrategyTRTA::set_ordering_cost`](crate::strategies::StrategyTRTA::set_ordering_cost) uses
+//! it to pick the cheapest among multiple valid orderings.
+
+use crate::netsim::config::ConfigModifier;
+use crate::netsim::{Network, NetworkError};
+
+/// Cost of a single step of an ordering.
+pub trait OrderingCost {
+    /// Returns the cost of the state reached by applying `step`. `net` is the network after `step`
+    /// was applied.
+    fn cost(&self, net: &Network, step: &ConfigModifier) -> u64;
+}
+
+/// Cost counting the forwarding loops during the transition. The cost of every step is the number
+/// of pairs of routers and prefixes, for which the packets are forwarded in a loop after the step
+/// was applied.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransientLoopCost;
+
+impl OrderingCost for TransientLoopCost {
+    fn cost(&self, net: &Network, _step: &ConfigModifier) -> u64 {
+        let mut fw_state = net.get_forwarding_state();
+        let mut loops = 0;
+        for router in net.get_routers() {
+            for prefix in net.get_known_prefixes() {
+                if let Err(NetworkError::ForwardingLoop(_)) = fw_state.get_route(router, *prefix) {
+                    loops += 1;
+                }
+            }
+        }
+        loops
+    }
+}
+
+/// Apply the `ordering` on a copy of the network, and return the sum of the cost of every step. If
+/// any modifier cannot be applied, the error is returned.
+pub fn total_cost(
+    net: &Network,
+    ordering: &[ConfigModifier],
+    cost: &dyn OrderingCost,
+) -> Result<u64, NetworkError> {
+    let mut net = net.clone();
+    let mut total: u64 = 0;
+    for step in ordering {
+        net.apply_modifier(step)?;
+        total = total.saturating_add(cost.cost(&net, step));
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::example_networks::{ExampleNetwork, SimpleNet};
+
+    /// Cost of one for every step
+    struct StepCost;
+
+    impl OrderingCost for StepCost {
+        fn cost(&self, _net: &Network, _step: &ConfigModifier) -> u64 {
+            1
+        }
+    }
+
+    #[test]
+    fn total() {
+        let net = SimpleNet::net(0);
+        let modifiers = net.current_config().get_diff(&SimpleNet::final_config(&net, 0)).modifiers;
+        assert_eq!(total_cost(&net, &modifiers, &StepCost).unwrap(), modifiers.len() as u64);
+        assert_eq!(total_cost(&net, &[], &StepCost).unwrap(), 0);
+        // the initial and the final state of the simple net are free of loops
+        assert_eq!(TransientLoopCost.cost(&net, &modifiers[0]), 0);
+    }
+}
@@ -0,0 +1,168 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # Role-Based Dependencies
+//!
+//! Operators often perform structurally similar changes, like migrating the sessions of one router
+//! after the other. The dependencies learned during one change can be reused for the next one, if
+//! they are expressed in terms of the *role* of each router, rather than the specific router ids.
+//!
+//! A [`RoleDependency`] is created from a dependency (an ordered list of modifiers) of a previous
+//! change, together with a mapping from every router to its role (an arbitrary name, like
+//! `"client"` or `"old_reflector"`). For a new change, the dependency is instantiated with a
+//! mapping from every role to the router which plays this role in the new change. Two routers play
+//! the same role if and only if they are mapped to the same role name. Matching is purely by name;
+//! no check is performed that the topology around the routers is similar.
+//!
+//! Only modifiers on link weights, BGP sessions and static routes can be expressed by role. Route
+//! maps may reference routers in their match and set clauses, and are therefore not supported.
+
+use crate::netsim::config::{ConfigExpr, ConfigModifier};
+use crate::netsim::RouterId;
+
+use std::collections::HashMap;
+
+/// Dependency learned during a previous change, where each router is replaced by its role. See the
+/// [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleDependency {
+    /// Names of all roles. The router ids in `template` are indices into this vector.
+    roles: Vec<String>,
+    /// Ordered modifiers of the dependency, where each router id is the index of its role.
+    template: Vec<ConfigModifier>,
+}
+
+impl RoleDependency {
+    /// Create a role-based dependency from the ordered modifiers of a dependency, and the role of
+    /// every router. Returns `None` if any router referenced by the modifiers has no role, or if any
+    /// modifier configures a route map.
+    pub fn new(dependency: &[ConfigModifier], roles: &HashMap<RouterId, String>) -> Option<Self> {
+        let mut role_names: Vec<String> = Vec::new();
+        let template = dependency
+            .iter()
+            .map(|m| {
+                map_modifier(m, &mut |r| {
+                    let role = roles.get(&r)?;
+                    let idx = match role_names.iter().position(|x| x == role) {
+                        Some(idx) => idx,
+                        None => {
+                            role_names.push(role.clone());
+                            role_names.len() - 1
+                        }
+                    };
+                    Some(RouterId::new(idx))
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { roles: role_names, template })
+    }
+
+    /// Returns the names of all roles used in this dependency.
+    pub fn roles(&self) -> &[String] {
+        &self.roles
+    }
+
+    /// Instantiate the dependency for a new change, with the router that plays each role. Returns
+    /// `None` if any role used in the dependency has no router assigned.
+    pub fn instantiate(&self, routers: &HashMap<String, RouterId>) -> Option<Vec<ConfigModifier>> {
+        self.template
+            .iter()
+            .map(|m| map_modifier(m, &mut |r| routers.get(self.roles.get(r.index())?).copied()))
+            .collect()
+    }
+}
+
+/// Replace every router id in the modifier, using the function `f`. Returns `None` if `f` returns
+/// `None` for any router, or if the modifier configures a route map.
+fn map_modifier(
+    modifier: &ConfigModifier,
+    f: &mut impl FnMut(RouterId) -> Option<RouterId>,
+) -> Option<ConfigModifier> {
+    Some(match modifier {
+        ConfigModifier::Insert(e) => ConfigModifier::Insert(map_expr(e, f)?),
+        ConfigModifier::Remove(e) => ConfigModifier::Remove(map_expr(e, f)?),
+        ConfigModifier::Update { from, to } => {
+            ConfigModifier::Update { from: map_expr(from, f)?, to: map_expr(to, f)? }
+        }
+    })
+}
+
+/// Replace every router id in the expression, using the function `f`. Returns `None` if `f`
+/// returns `None` for any router, or if the expression configures a route map.
+fn map_expr(
+    expr: &ConfigExpr,
+    f: &mut impl FnMut(RouterId) -> Option<RouterId>,
+) -> Option<ConfigExpr> {
+    Some(match expr {
+        ConfigExpr::IgpLinkWeight { source, target, weight } => {
+            ConfigExpr::IgpLinkWeight { source: f(*source)?, target: f(*target)?, weight: *weight }
+        }
+        ConfigExpr::BgpSession { source, target, session_type } => ConfigExpr::BgpSession {
+            source: f(*source)?,
+            target: f(*target)?,
+            session_type: *session_type,
+        },
+        ConfigExpr::StaticRoute { router, prefix, target } => {
+            ConfigExpr::StaticRoute { router: f(*router)?, prefix: *prefix, target: f(*target)? }
+        }
+        ConfigExpr::BgpRouteMap { .. } => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::netsim::config::ConfigExpr::*;
+    use crate::netsim::config::ConfigModifier::*;
+    use crate::netsim::BgpSessionType::*;
+    use maplit::hashmap;
+
+    #[test]
+    fn instantiate_by_role() {
+        let (r1, r2, r3, r4) = (0.into(), 1.into(), 2.into(), 3.into());
+        // previous change: migrate r4 from reflector r1 to reflector r2
+        let dependency = vec![
+            Insert(BgpSession { source: r2, target: r4, session_type: IBgpClient }),
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpClient }),
+        ];
+        let roles = hashmap! {
+            r1 => "old".to_string(),
+            r2 => "new".to_string(),
+            r4 => "client".to_string(),
+        };
+        let dep = RoleDependency::new(&dependency, &roles).unwrap();
+        assert_eq!(dep.roles(), &["new", "client", "old"]);
+
+        // new change: migrate r3 from reflector r1 to reflector r2
+        let routers = hashmap! {
+            "old".to_string() => r1,
+            "new".to_string() => r2,
+            "client".to_string() => r3,
+        };
+        assert_eq!(
+            dep.instantiate(&routers),
+            Some(vec![
+                Insert(BgpSession { source: r2, target: r3, session_type: IBgpClient }),
+                Remove(BgpSession { source: r1, target: r3, session_type: IBgpClient }),
+            ])
+        );
+
+        // missing role
+        assert_eq!(dep.instantiate(&hashmap! {"old".to_string() => r1}), None);
+        assert_eq!(RoleDependency::new(&dependency, &hashmap! {r1 => "old".to_string()}), None);
+    }
+}
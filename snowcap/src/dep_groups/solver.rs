@@ -0,0 +1,809 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # LTL Solvers
+//!
+//! This module contains the interface to the LTL satisfiability solvers used by
+//! [`StrategyTRTA`](crate::strategies::StrategyTRTA) when the exploration is stuck. The solver
+//! receives the accumulated LTL formula, and returns an ordering of the groups (the indices of the
+//! `x` variables, in the order in which they are set in the model), or `None` if the formula is
+//! unsatisfiable.
+
+use super::ltl;
+use crate::{Error, Stopper};
+
+use log::*;
+use std::collections::VecDeque;
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Environment variable to set the path of the `aaltaf` binary.
+pub const AALTAF_PATH_ENV: &str = "SNOWCAP_AALTAF_PATH";
+
+/// Interface of an LTL satisfiability solver.
+pub trait LtlSolver {
+    /// Check if the LTL formula is satisfiable. If it is, then the ordering of the groups, as
+    /// extracted from the model, is returned. If the formula is unsatisfiable, `Ok(None)` is
+    /// returned.
+    fn check(&mut self, formula: &str) -> Result<Option<Vec<usize>>, Error>;
+
+    /// Check if the LTL formula is satisfiable, like [`LtlSolver::check`], but give up once the
+    /// `deadline` has passed (returning `Error::Timeout`), or once `abort` is stopped (returning
+    /// `Error::Abort`). The default implementation ignores both limits.
+    fn check_with_limits(
+        &mut self,
+        formula: &str,
+        deadline: Option<SystemTime>,
+        abort: Option<&Stopper>,
+    ) -> Result<Option<Vec<usize>>, Error> {
+        let _ = (deadline, abort);
+        self.check(formula)
+    }
+
+    /// Create an independent copy of the solver, which does not share any state (e.g., a running
+    /// process) with `self`. This is used to fork a strategy (see
+    /// [`StrategyTRTA::fork`](crate::strategies::StrategyTRTA::fork)). The default implementation
+    /// returns `None`, meaning that the solver cannot be forked.
+    fn fork(&self) -> Option<Box<dyn LtlSolver>> {
+        None
+    }
+}
+
+/// Boxed solvers (e.g., the one held by [`StrategyTRTA`](crate::strategies::StrategyTRTA)) forward
+/// both methods to the inner solver, such that overridden limits are preserved.
+impl<S: LtlSolver + ?Sized> LtlSolver for Box<S> {
+    fn check(&mut self, formula: &str) -> Result<Option<Vec<usize>>, Error> {
+        (**self).check(formula)
+    }
+
+    fn check_with_limits(
+        &mut self,
+        formula: &str,
+        deadline: Option<SystemTime>,
+        abort: Option<&Stopper>,
+    ) -> Result<Option<Vec<usize>>, Error> {
+        (**self).check_with_limits(formula, deadline, abort)
+    }
+
+    fn fork(&self) -> Option<Box<dyn LtlSolver>> {
+        (**self).fork()
+    }
+}
+
+/// Interval at which the limits are checked while waiting for the solver.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Returns the error to return if the deadline has passed, or if the stopper was stopped.
+fn limit_exceeded(deadline: Option<SystemTime>, abort: Option<&Stopper>) -> Option<Error> {
+    if deadline.map(|time| time.elapsed().is_ok()).unwrap_or(false) {
+        Some(Error::Timeout)
+    } else if abort.map(|abort| abort.is_stop()).unwrap_or(false) {
+        Some(Error::Abort)
+    } else {
+        None
+    }
+}
+
+/// Solver calling the external `aaltaf` binary. A new process is spawned for every query, and the
+/// formula is passed on its standard input. Hence, queries of different solvers (e.g., in
+/// different threads) do not share any state, and run concurrently.
+#[derive(Debug, Clone)]
+pub struct AaltafSolver {
+    path: PathBuf,
+}
+
+impl Default for AaltafSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AaltafSolver {
+    /// Create a new solver. The path of the binary is read from the environment variable
+    /// `SNOWCAP_AALTAF_PATH`. If it is not set, `aaltaf` is searched in `$PATH`.
+    pub fn new() -> Self {
+        Self::with_path(env::var_os(AALTAF_PATH_ENV).unwrap_or_else(|| "aaltaf".into()))
+    }
+
+    /// Create a new solver, using the binary at the given path.
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the path of the binary
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+impl LtlSolver for AaltafSolver {
+    fn check(&mut self, formula: &str) -> Result<Option<Vec<usize>>, Error> {
+        self.check_with_limits(formula, None, None)
+    }
+
+    fn check_with_limits(
+        &mut self,
+        formula: &str,
+        deadline: Option<SystemTime>,
+        abort: Option<&Stopper>,
+    ) -> Result<Option<Vec<usize>>, Error> {
+        trace!("aalta_input: {}", formula);
+        let unavailable = |e: std::io::Error| Error::SolverUnavailable(e.to_string());
+        // start the solver process
+        let mut child = Command::new(&self.path)
+            .arg("-e")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                Error::SolverUnavailable(format!("cannot start {}: {}", self.path.display(), e))
+            })?;
+
+        let mut input = child
+            .stdin
+            .take()
+            .ok_or_else(|| Error::SolverUnavailable("cannot capture the input".to_string()))?;
+        let output = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::SolverUnavailable("cannot capture the output".to_string()))?;
+        // write the input and read the output in a separate thread, to check the deadline and the
+        // abort while waiting
+        let (tx, rx) = mpsc::channel();
+        let formula = format!("{}\n", formula);
+        thread::spawn(move || {
+            // the input is closed after writing, such that the solver starts
+            let written = input.write_all(formula.as_bytes()).and_then(|_| input.flush());
+            drop(input);
+            // parse the output line by line, instead of reading it all into memory
+            let _ = tx.send(written.and_then(|_| read_aaltaf_output(BufReader::new(output))));
+        });
+        let result = loop {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(result) => break result.map_err(unavailable)?,
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Some(e) = limit_exceeded(deadline, abort) {
+                        warn!("Stop the LTL solver: {}", e);
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Err(e);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(Error::SolverUnavailable("cannot read the output".to_string()))
+                }
+            }
+        };
+
+        // wait for the process to exit
+        let exit_status = child.wait().map_err(unavailable)?;
+        debug!("Child exited with status: {}", exit_status);
+
+        result.map_err(Error::SolverParse)
+    }
+
+    fn fork(&self) -> Option<Box<dyn LtlSolver>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+/// Result of a single query to an [`AaltaSession`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverResult {
+    /// The formula is satisfiable, and the model contains the given ordering of the groups.
+    Sat(Vec<usize>),
+    /// The formula is unsatisfiable.
+    Unsat,
+}
+
+impl From<Option<Vec<usize>>> for SolverResult {
+    fn from(result: Option<Vec<usize>>) -> Self {
+        match result {
+            Some(ordering) => Self::Sat(ordering),
+            None => Self::Unsat,
+        }
+    }
+}
+
+impl From<SolverResult> for Option<Vec<usize>> {
+    fn from(result: SolverResult) -> Self {
+        match result {
+            SolverResult::Sat(ordering) => Some(ordering),
+            SolverResult::Unsat => None,
+        }
+    }
+}
+
+/// Solver keeping a single `aaltaf` process alive for all queries, instead of spawning a new
+/// process for every query like [`AaltafSolver`]. Every formula is written as a single line to the
+/// standard input of the process, and the answer is read from its standard output, until an empty
+/// line. The process must therefore answer every line without waiting for the end of the input.
+///
+/// If the process terminates after the first answer (i.e., the solver does not support the
+/// interactive mode), the session falls back to spawning a new process for every query.
+#[derive(Debug)]
+pub struct AaltaSession {
+    path: PathBuf,
+    process: Option<SessionProcess>,
+    fallback: Option<AaltafSolver>,
+}
+
+impl AaltaSession {
+    /// Create a new session, using the binary at the given path. The process is started on the
+    /// first query.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), process: None, fallback: None }
+    }
+
+    /// Returns `true` if the session has fallen back to spawning a new process for every query.
+    pub fn is_fallback(&self) -> bool {
+        self.fallback.is_some()
+    }
+
+    /// Check if the LTL formula is satisfiable, reusing the running process if possible.
+    pub fn query(&mut self, formula: &str) -> Result<SolverResult, Error> {
+        self.query_with_limits(formula, None, None)
+    }
+
+    /// Check if the LTL formula is satisfiable, like [`AaltaSession::query`], but give up once the
+    /// `deadline` has passed, or once `abort` is stopped. In this case, the process is killed, and
+    /// a new one is started for the next query.
+    pub fn query_with_limits(
+        &mut self,
+        formula: &str,
+        deadline: Option<SystemTime>,
+        abort: Option<&Stopper>,
+    ) -> Result<SolverResult, Error> {
+        if self.fallback.is_none() {
+            if self.process.is_none() {
+                let process = SessionProcess::spawn(&self.path).map_err(|e| {
+                    Error::SolverUnavailable(format!("cannot start {}: {}", self.path.display(), e))
+                })?;
+                self.process = Some(process);
+            }
+            let process = self.process.as_mut().expect("process was started above");
+            match process.exchange(formula, deadline, abort) {
+                Exchange::Answer(output) => {
+                    return parse_aaltaf_output(&output)
+                        .map(SolverResult::from)
+                        .map_err(Error::SolverParse)
+                }
+                Exchange::Exceeded(e) => {
+                    warn!("Stop the LTL solver: {}", e);
+                    self.process = None;
+                    return Err(e);
+                }
+                Exchange::Terminated => {}
+            }
+            // the process has terminated, and cannot answer any further queries.
+            warn!("aaltaf does not support the interactive mode! Spawning a process per query.");
+            self.process = None;
+            self.fallback = Some(AaltafSolver::with_path(self.path.clone()));
+        }
+        let solver = self.fallback.as_mut().expect("fallback is set above");
+        solver.check_with_limits(formula, deadline, abort).map(SolverResult::from)
+    }
+}
+
+impl LtlSolver for AaltaSession {
+    fn check(&mut self, formula: &str) -> Result<Option<Vec<usize>>, Error> {
+        self.query(formula).map(Option::from)
+    }
+
+    fn check_with_limits(
+        &mut self,
+        formula: &str,
+        deadline: Option<SystemTime>,
+        abort: Option<&Stopper>,
+    ) -> Result<Option<Vec<usize>>, Error> {
+        self.query_with_limits(formula, deadline, abort).map(Option::from)
+    }
+
+    /// The fork starts its own process on the first query.
+    fn fork(&self) -> Option<Box<dyn LtlSolver>> {
+        Some(Box::new(Self::new(self.path.clone())))
+    }
+}
+
+/// Outcome of a single exchange with the process of an [`AaltaSession`].
+#[derive(Debug)]
+enum Exchange {
+    /// The process has answered
+    Answer(String),
+    /// The process has terminated before answering
+    Terminated,
+    /// The deadline has passed, or the operation was aborted
+    Exceeded(Error),
+}
+
+/// Running solver process of an [`AaltaSession`]. The process is killed when dropped.
+#[derive(Debug)]
+struct SessionProcess {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines of the output, read by a separate thread
+    lines: Receiver<String>,
+}
+
+impl SessionProcess {
+    fn spawn(path: &Path) -> std::io::Result<Self> {
+        let mut child =
+            Command::new(path).arg("-e").stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let missing = || std::io::Error::other("cannot capture stdio");
+        let stdin = child.stdin.take().ok_or_else(missing)?;
+        let stdout = BufReader::new(child.stdout.take().ok_or_else(missing)?);
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if line.map(|line| tx.send(line).is_err()).unwrap_or(true) {
+                    break;
+                }
+            }
+        });
+        Ok(Self { child, stdin, lines })
+    }
+
+    /// Write the formula to the process, and read the answer.
+    fn exchange(
+        &mut self,
+        formula: &str,
+        deadline: Option<SystemTime>,
+        abort: Option<&Stopper>,
+    ) -> Exchange {
+        let line = formula.lines().collect::<Vec<_>>().join(" ");
+        if writeln!(self.stdin, "{}", line).and_then(|_| self.stdin.flush()).is_err() {
+            return Exchange::Terminated;
+        }
+
+        let mut output = String::new();
+        loop {
+            let line = match self.lines.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => match limit_exceeded(deadline, abort) {
+                    Some(e) => return Exchange::Exceeded(e),
+                    None => continue,
+                },
+                // end of file
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            if line.trim().is_empty() {
+                // an empty line terminates the answer, but only after the header.
+                if output.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            output.push_str(&line);
+            output.push('\n');
+            if line.trim() == "unsat" {
+                break;
+            }
+        }
+        if output.is_empty() {
+            Exchange::Terminated
+        } else {
+            Exchange::Answer(output)
+        }
+    }
+}
+
+impl Drop for SessionProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Parse the output of `aaltaf -e`. The first line must be either `sat` or `unsat`. If it is
+/// `unsat`, then `None` is returned. Otherwise, the following lines contain the model, one state per
+/// line, as a comma-separated list of (possibly negated) variables. The indices of all positive `x`
+/// variables are returned, in the order in which they appear. If the output does not follow this
+/// format, a description of the problem is returned as error. See [`read_aaltaf_output`].
+pub(crate) fn parse_aaltaf_output(output: &str) -> Result<Option<Vec<usize>>, String> {
+    read_aaltaf_output(output.as_bytes()).unwrap_or_else(|e| Err(e.to_string()))
+}
+
+/// Parse the output of `aaltaf -e` like [`parse_aaltaf_output`], but read it line by line from
+/// `reader`, without buffering the entire output. Reading stops as soon as the result is known,
+/// i.e., right after the `unsat` header, or at the first line which does not follow the format.
+/// If the output cannot be read, the outer error is returned.
+pub(crate) fn read_aaltaf_output<R: BufRead>(
+    reader: R,
+) -> std::io::Result<Result<Option<Vec<usize>>, String>> {
+    let mut sat = false;
+    let mut indices = Vec::new();
+    let mut num_states = 0;
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        trace!("Output: {}", line);
+        // the first line tells whether the formula is satisfiable
+        if !sat {
+            match line {
+                "sat" => sat = true,
+                "unsat" => return Ok(Ok(None)),
+                header => return Ok(Err(format!("unexpected header: {:?}", header))),
+            }
+            continue;
+        }
+        // every following line is one state of the model
+        num_states += 1;
+        if let Err(e) = parse_aaltaf_state(line, &mut indices) {
+            return Ok(Err(e));
+        }
+    }
+    if !sat {
+        return Ok(Err("empty output".to_string()));
+    }
+    if num_states == 0 {
+        return Ok(Err("result is sat, but no model is given".to_string()));
+    }
+    debug!("Extracted indices: {:?}", indices);
+    Ok(Ok(Some(indices)))
+}
+
+/// Parse a single state of the model returned by `aaltaf -e`, and push the indices of all positive
+/// `x` variables to `indices`. A positive `e` variable must belong to a group whose `x` variable is
+/// set in the same state, otherwise the model is inconsistent.
+fn parse_aaltaf_state(line: &str, indices: &mut Vec<usize>) -> Result<(), String> {
+    let state = line.trim_start_matches('(').trim_end_matches(')');
+    let first = indices.len();
+    let mut executed = Vec::new();
+    // the literals of the state are separated by commas
+    for part in state.split(',') {
+        let literal = part.trim();
+        let var = literal.strip_prefix('!').unwrap_or(literal).trim();
+        if var.is_empty() || !var.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("malformed assignment {:?} in line {:?}", literal, line));
+        }
+        // collect all group variables, negated variables are not set in this state
+        if let Some(index) = ltl::parse_var_name(literal) {
+            indices.push(index);
+        } else if let Some(index) = ltl::parse_exec_var_name(literal) {
+            executed.push(index);
+        }
+    }
+    match executed.iter().find(|index| !indices[first..].contains(index)) {
+        Some(index) => Err(format!(
+            "{} is set without {} in line {:?}",
+            ltl::exec_var_name(*index),
+            ltl::var_name(*index),
+            line
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Solver returning pre-programmed answers for successive queries, used to test the strategy
+/// deterministically without the external solver. All queries are recorded, and can be inspected
+/// with [`RecordedSolver::queries`]. If the solver is queried more often than there are answers,
+/// `Error::Internal` is returned.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedSolver {
+    answers: VecDeque<Option<Vec<usize>>>,
+    queries: Vec<String>,
+}
+
+impl RecordedSolver {
+    /// Create a new recorded solver, with the answers for successive queries. `None` means that the
+    /// formula is unsatisfiable.
+    pub fn new(answers: impl IntoIterator<Item = Option<Vec<usize>>>) -> Self {
+        Self { answers: answers.into_iter().collect(), queries: Vec::new() }
+    }
+
+    /// Returns all formulas which were passed to the solver, in the order of the queries.
+    pub fn queries(&self) -> &[String] {
+        &self.queries
+    }
+}
+
+impl LtlSolver for RecordedSolver {
+    fn check(&mut self, formula: &str) -> Result<Option<Vec<usize>>, Error> {
+        self.queries.push(formula.to_string());
+        self.answers.pop_front().ok_or_else(|| {
+            Error::Internal(format!("No recorded answer for query {}", self.queries.len()))
+        })
+    }
+
+    fn fork(&self) -> Option<Box<dyn LtlSolver>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+/// Solver replaying a recorded sequence of queries, used to test the strategy deterministically
+/// without the external solver. Each entry consists of a pattern and a response. The `n`-th query
+/// must contain the pattern of the `n`-th entry, and is answered with its response (`None` meaning
+/// unsatisfiable). An empty pattern matches any query. If a query does not match the next entry,
+/// or if there are no entries left, `Error::Internal` is returned.
+///
+/// A transcript recorded by [`StrategyTRTA`](crate::strategies::StrategyTRTA) can be replayed with
+/// [`MockLtlSolver::from_transcript`].
+#[derive(Debug, Clone, Default)]
+pub struct MockLtlSolver {
+    entries: VecDeque<(String, Option<Vec<usize>>)>,
+    num_queries: usize,
+}
+
+impl MockLtlSolver {
+    /// Create a new mock solver from a sequence of `(formula_pattern, response)` pairs.
+    pub fn new<S: Into<String>>(
+        entries: impl IntoIterator<Item = (S, Option<Vec<usize>>)>,
+    ) -> Self {
+        Self { entries: entries.into_iter().map(|(p, r)| (p.into(), r)).collect(), num_queries: 0 }
+    }
+
+    /// Create a new mock solver, which replays a recorded transcript. The (possibly truncated)
+    /// query of each exchange is used as the pattern.
+    pub fn from_transcript(transcript: &[super::strategy_trta::SolverExchange]) -> Self {
+        Self::new(transcript.iter().map(|e| (e.query.clone(), e.result.clone())))
+    }
+
+    /// Returns the number of entries which were not yet replayed.
+    pub fn remaining(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl LtlSolver for MockLtlSolver {
+    fn check(&mut self, formula: &str) -> Result<Option<Vec<usize>>, Error> {
+        self.num_queries += 1;
+        let (pattern, response) = self.entries.pop_front().ok_or_else(|| {
+            Error::Internal(format!("No recorded entry for query {}", self.num_queries))
+        })?;
+        if !formula.contains(pattern.as_str()) {
+            return Err(Error::Internal(format!(
+                "Query {} does not match the recorded pattern {:?}: {}",
+                self.num_queries, pattern, formula
+            )));
+        }
+        Ok(response)
+    }
+
+    fn fork(&self) -> Option<Box<dyn LtlSolver>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_output() {
+        assert_eq!(parse_aaltaf_output("sat\n(x2, !x0)\nx0, !x2\n"), Ok(Some(vec![2, 0])));
+        assert_eq!(
+            parse_aaltaf_output("sat\n(x1, !x0, e1)\n\n(x0, !x1, e0)\n"),
+            Ok(Some(vec![1, 0]))
+        );
+        assert_eq!(parse_aaltaf_output("unsat\n"), Ok(None));
+        assert!(parse_aaltaf_output("sat").is_err());
+        assert!(parse_aaltaf_output("").is_err());
+        assert!(parse_aaltaf_output("please wait...\nsat\n(x0)\n").is_err());
+        assert!(parse_aaltaf_output("sat\n(x0, , !x1)\n").is_err());
+        assert!(parse_aaltaf_output("sat\n(x0 -> x1)\n").is_err());
+        // the execution variable must match the group variable of the same state
+        assert!(parse_aaltaf_output("sat\n(x1, !x0, e0)\n").is_err());
+    }
+
+    #[test]
+    fn read_output() {
+        use std::io::{Error as IoError, ErrorKind, Read};
+        /// Reader failing on every read.
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(IoError::new(ErrorKind::Other, "broken pipe"))
+            }
+        }
+        let read = |output: &'static str| {
+            read_aaltaf_output(BufReader::new(output.as_bytes().chain(Broken)))
+        };
+        // the reader is not touched once the result is known
+        assert_eq!(read("unsat\n").unwrap(), Ok(None));
+        assert!(read("please wait...\n").unwrap().is_err());
+        assert!(read("sat\n(x0 -> x1)\n").unwrap().is_err());
+        // the model is only complete at the end of the output
+        assert!(read("sat\n(x1, !x0)\n").is_err());
+        assert_eq!(
+            read_aaltaf_output("sat\n(x1, !x0)\n\n(x0, !x1)\n".as_bytes()).unwrap(),
+            Ok(Some(vec![1, 0]))
+        );
+    }
+
+    #[test]
+    fn aaltaf_not_found() {
+        let mut solver = AaltafSolver::with_path("/nonexistent/aaltaf");
+        assert_eq!(solver.path(), &PathBuf::from("/nonexistent/aaltaf"));
+        assert!(matches!(solver.check("G(x0)"), Err(Error::SolverUnavailable(_))));
+    }
+
+    /// Write a shell script acting as a fake solver, and return its path.
+    #[cfg(unix)]
+    fn fake_solver(name: &str, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = env::temp_dir().join(format!("{}_{}.sh", name, std::process::id()));
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn session_reuses_process() {
+        let script = fake_solver(
+            "aaltaf_session",
+            "#!/bin/sh\nn=0\nwhile read formula; do\n  n=$((n+1))\n  \
+             if [ $n -eq 1 ]; then printf 'sat\\n(x1, !x0)\\n(x0, !x1)\\n\\n'; \
+             else printf 'unsat\\n'; fi\ndone\n",
+        );
+
+        let mut session = AaltaSession::new(&script);
+        assert_eq!(session.query("G(x0)\n& G(x1)\n").unwrap(), SolverResult::Sat(vec![1, 0]));
+        // the second answer can only be unsat if the same process is still running
+        assert_eq!(session.query("G(x0)").unwrap(), SolverResult::Unsat);
+        assert_eq!(session.check("G(x0)").unwrap(), None);
+        assert!(!session.is_fallback());
+        drop(session);
+        let _ = std::fs::remove_file(&script);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn solver_timeout() {
+        let script = fake_solver("aaltaf_timeout", "#!/bin/sh\nexec sleep 10\n");
+        let start = SystemTime::now();
+        let deadline = Some(start + Duration::from_millis(200));
+        let mut solver = AaltafSolver::with_path(&script);
+        assert!(matches!(solver.check_with_limits("G(x0)", deadline, None), Err(Error::Timeout)));
+        let abort = Stopper::new();
+        abort.send_stop();
+        assert!(matches!(solver.check_with_limits("G(x0)", None, Some(&abort)), Err(Error::Abort)));
+        assert!(start.elapsed().unwrap() < Duration::from_secs(5));
+        let _ = std::fs::remove_file(&script);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn session_timeout() {
+        let script = fake_solver("aaltaf_session_timeout", "#!/bin/sh\nexec sleep 10\n");
+        let start = SystemTime::now();
+        let deadline = Some(start + Duration::from_millis(200));
+        let mut session = AaltaSession::new(&script);
+        assert!(matches!(session.query_with_limits("G(x0)", deadline, None), Err(Error::Timeout)));
+        assert!(start.elapsed().unwrap() < Duration::from_secs(5));
+        let _ = std::fs::remove_file(&script);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn abort_kills_solver() {
+        let pid_file = env::temp_dir().join(format!("aaltaf_abort_{}.pid", std::process::id()));
+        let script = fake_solver(
+            "aaltaf_abort",
+            &format!("#!/bin/sh\necho $$ > {}\nexec sleep 10\n", pid_file.display()),
+        );
+        let solvers: Vec<Box<dyn LtlSolver>> =
+            vec![Box::new(AaltafSolver::with_path(&script)), Box::new(AaltaSession::new(&script))];
+        for mut solver in solvers {
+            let _ = std::fs::remove_file(&pid_file);
+            let start = SystemTime::now();
+            // the abort arrives while the solver is running
+            let abort = Stopper::new();
+            let sender = abort.clone();
+            let handle = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(200));
+                sender.send_stop();
+            });
+            assert!(matches!(
+                solver.check_with_limits("G(x0)", None, Some(&abort)),
+                Err(Error::Abort)
+            ));
+            assert!(start.elapsed().unwrap() < Duration::from_secs(5));
+            handle.join().unwrap();
+            drop(solver);
+            // the process was killed and reaped
+            let pid = std::fs::read_to_string(&pid_file).unwrap();
+            let alive = Command::new("kill")
+                .arg("-0")
+                .arg(pid.trim())
+                .stderr(Stdio::null())
+                .status()
+                .unwrap();
+            assert!(!alive.success());
+        }
+        let _ = std::fs::remove_file(&pid_file);
+        let _ = std::fs::remove_file(&script);
+    }
+
+    #[test]
+    fn session_not_found() {
+        let mut session = AaltaSession::new("/nonexistent/aaltaf");
+        assert!(matches!(session.query("G(x0)"), Err(Error::SolverUnavailable(_))));
+    }
+
+    #[test]
+    fn recorded_answers() {
+        let mut solver = RecordedSolver::new(vec![Some(vec![1, 0]), None]);
+        assert_eq!(solver.check("a").unwrap(), Some(vec![1, 0]));
+        assert_eq!(solver.check("b").unwrap(), None);
+        assert!(matches!(solver.check("c"), Err(Error::Internal(_))));
+        assert_eq!(solver.queries(), &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn mock_solver() {
+        let mut solver = MockLtlSolver::new(vec![("x1", Some(vec![1, 0])), ("", None)]);
+        assert_eq!(solver.check("G(x1 -> x0)").unwrap(), Some(vec![1, 0]));
+        assert_eq!(solver.remaining(), 1);
+        assert_eq!(solver.check("anything").unwrap(), None);
+        assert!(matches!(solver.check("c"), Err(Error::Internal(_))));
+        let mut solver = MockLtlSolver::new(vec![("x1", None)]);
+        assert!(matches!(solver.check("G(x0)"), Err(Error::Internal(_))));
+    }
+
+    #[test]
+    fn fork_solver() {
+        let mut solver = RecordedSolver::new(vec![Some(vec![0]), None]);
+        assert_eq!(solver.check("a").unwrap(), Some(vec![0]));
+        // the fork continues with the remaining answers, independently of the original solver
+        let mut fork = solver.fork().unwrap();
+        assert_eq!(fork.check("b").unwrap(), None);
+        assert_eq!(solver.check("b").unwrap(), None);
+        assert!(fork.check("c").is_err());
+        let boxed: Box<dyn LtlSolver> = Box::new(AaltafSolver::with_path("/bin/aaltaf"));
+        assert!(boxed.fork().is_some());
+    }
+
+    #[test]
+    fn boxed_solver() {
+        /// Solver which only answers if there is no deadline.
+        struct Impatient;
+        impl LtlSolver for Impatient {
+            fn check(&mut self, _formula: &str) -> Result<Option<Vec<usize>>, Error> {
+                Ok(None)
+            }
+            fn check_with_limits(
+                &mut self,
+                formula: &str,
+                deadline: Option<SystemTime>,
+                _abort: Option<&Stopper>,
+            ) -> Result<Option<Vec<usize>>, Error> {
+                match deadline {
+                    Some(_) => Err(Error::Timeout),
+                    None => self.check(formula),
+                }
+            }
+        }
+
+        let mut solver: Box<dyn LtlSolver> = Box::new(Impatient);
+        assert_eq!(solver.check("a").unwrap(), None);
+        assert!(matches!(
+            solver.check_with_limits("a", Some(SystemTime::now()), None),
+            Err(Error::Timeout)
+        ));
+        let mut solver: Box<dyn LtlSolver> = Box::new(RecordedSolver::new(vec![Some(vec![0])]));
+        assert_eq!(solver.check_with_limits("a", None, None).unwrap(), Some(vec![0]));
+    }
+}
@@ -17,23 +17,44 @@
 
 //! # One Strategy To Rule Them All
 
+use super::ltl;
+use super::ordering_cost::{total_cost, OrderingCost};
+use super::roles::RoleDependency;
+use super::solver::{AaltafSolver, LtlSolver};
 use super::utils;
-use crate::hard_policies::{HardPolicy, PolicyError};
-use crate::modifier_ordering::RandomOrdering;
-use crate::netsim::config::{ConfigExpr::BgpSession, ConfigModifier};
-use crate::netsim::{Network, NetworkError, RouterId};
-use crate::strategies::{PushBackTreeStrategy, Strategy};
+use crate::hard_policies::{Condition, HardPolicy, PolicyError};
+use crate::modifier_ordering::{NoOrdering, RandomOrdering};
+use crate::netsim::config::{ConfigExprKey, ConfigModifier};
+use crate::netsim::{ForwardingState, Network, NetworkDevice, NetworkError, RouterId};
+use crate::permutators::{Permutator, PermutatorItem, TreePermutator};
+use crate::strategies::{GroupStrategy, PushBackTreeStrategy, Strategy};
 use crate::{Error, Stopper};
-use std::fmt::format;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
-use std::process::{Command, Stdio};
-use std::string;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::PathBuf;
 
+use itertools::Itertools;
 use log::*;
+use petgraph::algo::is_cyclic_directed;
+use petgraph::graphmap::DiGraphMap;
+use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+#[cfg(feature = "ltl-solver")]
+use std::time::Instant;
 use std::time::{Duration, SystemTime};
-use utils::fmt_err;
+use utils::{fmt_err, Snapshot};
+
+/// Validator for orderings returned by the solver. See [`StrategyTRTA::set_solver_validator`].
+type SolverValidator = Box<dyn Fn(&[usize]) -> bool>;
+
+/// Callback observing the current sequence of groups. See [`StrategyTRTA::set_on_progress`].
+type ProgressCallback = Box<dyn FnMut(&[usize])>;
+
+/// New dependency found by `find_dependency`: the ordering of the modifiers, and the groups which
+/// are part of it.
+type Dependency = (Vec<ConfigModifier>, Vec<usize>);
 
 /// # One Strategy To Rule Them All
 ///
@@ -177,882 +198,1684 @@ use utils::fmt_err;
 ///        return Err
 ///    }
 ///    ```
-pub struct StrategyTRTA {
+///
+/// ## Type Arguments
+///
+/// The type argument `S` is the [`GroupStrategy`] used to solve the smaller problem when learning
+/// a new dependency. By default, this is a
+/// [`PushBackTreeStrategy`](crate::strategies::PushBackTreeStrategy) with a
+/// [`RandomOrdering`](crate::modifier_ordering::RandomOrdering).
+///
+/// The type argument `P` is the [`Permutator<usize>`](crate::permutators::Permutator), which
+/// generates the orderings of the groups (as indices) explored in the tree. Whenever a group cannot
+/// be applied, all orderings starting the same way are skipped with `fail_pos`. Whenever the
+/// exploration is reset, a new permutator is created, starting with the ordering returned by the
+/// solver (if any). By default, this is a [`TreePermutator`], which explores the groups in the
+/// order it is created with.
+pub struct StrategyTRTA<
+    S = PushBackTreeStrategy<RandomOrdering>,
+    P = TreePermutator<NoOrdering, usize>,
+> where
+    S: Strategy + GroupStrategy,
+    P: Permutator<usize> + Iterator,
+    P::Item: PermutatorItem<usize>,
+{
     net: Network,
     groups: Vec<Vec<ConfigModifier>>,
     hard_policy: HardPolicy,
-    rng: ThreadRng,
+    rng: StdRng,
+    shuffle: bool,
+    conflict_weights: bool,
+    time_budget: Option<Duration>,
     stop_time: Option<SystemTime>,
     max_group_solve_time: Option<Duration>,
+    time_fraction: u32,
+    solver: Box<dyn LtlSolver>,
+    solver_timeout: Option<Duration>,
+    solver_validator: Option<SolverValidator>,
+    cross_validate: bool,
+    on_progress: Option<ProgressCallback>,
+    transcript: Option<(Vec<SolverExchange>, Option<usize>)>,
+    formula_dump: Option<PathBuf>,
+    formula_growth_limit: Option<(usize, usize)>,
+    stall_limit: Option<usize>,
+    max_solver_calls: Option<usize>,
+    memory_budget: Option<(usize, bool)>,
+    max_learned_clauses: usize,
+    solver_errors: HashSet<SolverErrorKind>,
+    max_depth: Option<usize>,
+    merge_threshold: Option<usize>,
+    max_expansions: usize,
+    best_first: bool,
+    candidate_evaluation: CandidateEvaluation,
+    conflict_selection: ConflictSelection,
+    try_given_order: bool,
+    return_best_on_timeout: bool,
+    precedence: Vec<(ConfigModifier, ConfigModifier)>,
+    ordering_cost: Option<(Box<dyn OrderingCost>, usize)>,
+    fewest_steps: Option<usize>,
+    fw_state_cache: Option<ForwardingState>,
+    stats: TrtaStats,
+    strategy_phantom: PhantomData<S>,
+    permutator_phantom: PhantomData<P>,
     #[cfg(feature = "count-states")]
-    num_states: usize,
+    num_tree_states: usize,
+    #[cfg(feature = "count-states")]
+    num_solver_states: usize,
     #[cfg(feature = "count-states")]
     seen_difficult_dependency: bool,
 }
 
-impl Strategy for StrategyTRTA {
+impl<S, P> Strategy for StrategyTRTA<S, P>
+where
+    S: Strategy + GroupStrategy,
+    P: Permutator<usize> + Iterator,
+    P::Item: PermutatorItem<usize>,
+{
     fn new(
-        mut net: Network,
+        net: Network,
         modifiers: Vec<ConfigModifier>,
+        hard_policy: HardPolicy,
+        time_budget: Option<Duration>,
+    ) -> Result<Box<Self>, Error> {
+        // prepare the groups, every modifier in its own group
+        let groups = modifiers.into_iter().map(|modifier| vec![modifier]).collect();
+        Self::new_with_groups(net, groups, hard_policy, time_budget)
+    }
+
+    fn work(&mut self, abort: Stopper) -> Result<Vec<ConfigModifier>, Error> {
+        if let Some((cost, max_orderings)) = self.ordering_cost.take() {
+            let result = self.work_all(abort, max_orderings).map(|orderings| {
+                orderings
+                    .into_iter()
+                    .min_by_key(|o| total_cost(&self.net, o, cost.as_ref()).unwrap_or(u64::MAX))
+            });
+            self.ordering_cost = Some((cost, max_orderings));
+            return result?.ok_or_else(|| no_ordering_found("work_all"));
+        }
+        if let Some(max_orderings) = self.fewest_steps {
+            let mut orderings = self.work_all(abort, max_orderings)?;
+            let best = (0..orderings.len())
+                .min_by_key(|i| self.stats.found_steps[*i])
+                .ok_or_else(|| no_ordering_found("work_all"))?;
+            return Ok(orderings.swap_remove(best));
+        }
+        let mut found = Vec::with_capacity(1);
+        self.explore(abort, 1, &mut found)?;
+        found.pop().ok_or_else(|| no_ordering_found("explore"))
+    }
+
+    fn initial_network(&self) -> Option<&Network> {
+        Some(&self.net)
+    }
+
+    fn num_solver_calls(&self) -> usize {
+        self.stats.solver_calls
+    }
+
+    fn set_max_solver_calls(&mut self, max_calls: Option<usize>) {
+        self.max_solver_calls = max_calls;
+    }
+
+    /// Returns the number of states explored by the tree exploration and while searching for
+    /// dependencies (see [`StrategyTRTA::num_tree_states`] and
+    /// [`StrategyTRTA::num_solver_states`]).
+    #[cfg(feature = "count-states")]
+    fn num_states(&self) -> usize {
+        self.num_tree_states + self.num_solver_states
+    }
+}
+
+impl<S, P> StrategyTRTA<S, P>
+where
+    S: Strategy + GroupStrategy,
+    P: Permutator<usize> + Iterator,
+    P::Item: PermutatorItem<usize>,
+{
+    /// Create a new strategy (see [`Strategy::new`]), where the modifiers are already grouped.
+    /// Each group is always applied as a whole, in the given order, as if it was learned as a
+    /// dependency. If any group is empty, or if any two modifiers (in the same or in different
+    /// groups) change the same configuration expression, `Error::InvalidInput` is returned.
+    pub fn new_with_groups(
+        mut net: Network,
+        groups: Vec<Vec<ConfigModifier>>,
         mut hard_policy: HardPolicy,
         time_budget: Option<Duration>,
     ) -> Result<Box<Self>, Error> {
+        if let Some(i) = groups.iter().position(|group| group.is_empty()) {
+            return Err(Error::InvalidInput(format!("Group {} is empty", i)));
+        }
+        check_modifiers(&groups).map_err(Error::InvalidInput)?;
+        check_routers(&net, &groups).map_err(Error::UnknownRouter)?;
+
         // clear the undo stack
         net.clear_undo_stack();
 
         // check the state
-        hard_policy.set_num_mods_if_none(modifiers.len());
+        hard_policy.set_num_mods_if_none(groups.iter().map(|group| group.len()).sum());
         let mut fw_state = net.get_forwarding_state();
         hard_policy.step(&mut net, &mut fw_state)?;
-        //有输出，check了1次
-        // println!("Policies checked successfully.");
-        // match hard_policy.step(&mut net, &mut fw_state) {
-        //     Ok(_) => println!("Policies checked successfully."),
-        //     Err(e) => println!("Failed to check policies: {:?}", e),
-        // }
 
         if !hard_policy.check() {
             error!("Initial state errors::\n{}", fmt_err(&hard_policy.get_watch_errors(), &net));
             return Err(Error::InvalidInitialState);
         }
 
-        // prepare the groups
-        let mut groups: Vec<Vec<ConfigModifier>> = Vec::with_capacity(modifiers.len());
-        for modifier in modifiers {
-            groups.push(vec![modifier]);
-            //println!("groups: {:?}", groups);
-        }
-
         // prepare the timings
-        let max_group_solve_time: Option<Duration> =
-            time_budget.as_ref().map(|dur| *dur / super::TIME_FRACTION);
+        let max_group_solve_time = group_solve_time(time_budget, super::TIME_FRACTION);
         let stop_time: Option<SystemTime> = time_budget.map(|dur| SystemTime::now() + dur);
         Ok(Box::new(Self {
             net,
             groups,
             hard_policy,
-            rng: rand::thread_rng(),
+            rng: StdRng::from_entropy(),
+            shuffle: true,
+            conflict_weights: false,
+            time_budget,
             stop_time,
             max_group_solve_time,
+            time_fraction: super::TIME_FRACTION,
+            solver: Box::new(AaltafSolver::new()),
+            solver_timeout: max_group_solve_time,
+            solver_validator: None,
+            cross_validate: false,
+            on_progress: None,
+            transcript: None,
+            formula_dump: None,
+            formula_growth_limit: None,
+            stall_limit: None,
+            max_solver_calls: None,
+            memory_budget: None,
+            max_learned_clauses: usize::MAX,
+            solver_errors: SolverErrorKind::all(),
+            max_depth: None,
+            merge_threshold: Some(2),
+            max_expansions: 3,
+            best_first: false,
+            candidate_evaluation: CandidateEvaluation::Undo,
+            conflict_selection: ConflictSelection::MostViolations,
+            try_given_order: true,
+            return_best_on_timeout: false,
+            precedence: Vec::new(),
+            ordering_cost: None,
+            fewest_steps: None,
+            fw_state_cache: None,
+            stats: TrtaStats::default(),
+            strategy_phantom: PhantomData,
+            permutator_phantom: PhantomData,
             #[cfg(feature = "count-states")]
-            num_states: 0,
+            num_tree_states: 0,
+            #[cfg(feature = "count-states")]
+            num_solver_states: 0,
             #[cfg(feature = "count-states")]
             seen_difficult_dependency: false,
         }))
     }
 
-    fn work(&mut self, mut abort: Stopper) -> Result<Vec<ConfigModifier>, Error> {
-        // setup the stack with a randomized frame
-        let mut stack = vec![StackFrame::new(0..self.groups.len(), 0, &mut self.rng)];
-        let mut current_sequence: Vec<usize> = vec![];
+    /// Create a new strategy (see [`Strategy::new`]), where the exploration is seeded with `seed`.
+    /// Multiple runs with the same seed and the same input explore the groups in the same order.
+    pub fn new_seeded(
+        net: Network,
+        modifiers: Vec<ConfigModifier>,
+        hard_policy: HardPolicy,
+        time_budget: Option<Duration>,
+        seed: u64,
+    ) -> Result<Box<Self>, Error> {
+        let mut strategy = Self::new(net, modifiers, hard_policy, time_budget)?;
+        strategy.set_seed(seed);
+        Ok(strategy)
+    }
 
-        // clone the network and the hard policies to work with them for the tree exploration
-        let mut net = self.net.clone();
-        let mut hard_policy = self.hard_policy.clone();
+    /// Reset the seed of the random number generator, which is used to shuffle the groups during
+    /// the exploration.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
 
-        // 创建一个空的 Vec 来存储(source, target)元组对
-        let mut session_pairs = Vec::new();
+    /// Create a copy of the strategy, with the random number generator seeded with `seed`. The
+    /// network, the groups, the hard policy, the time budget (including the deadline) and all
+    /// options are copied, such that multiple forks with different seeds can explore the same
+    /// problem independently. The solver is forked (see [`LtlSolver::fork`]), or replaced by the
+    /// default [`AaltafSolver`] if it cannot be forked. The solver validator and the progress
+    /// callback (as well as the ordering cost) are not copied, and the statistics and the transcript
+    /// start empty.
+    pub fn fork(&self, seed: u64) -> Self {
+        Self {
+            net: self.net.clone(),
+            groups: self.groups.clone(),
+            hard_policy: self.hard_policy.clone(),
+            rng: StdRng::seed_from_u64(seed),
+            shuffle: self.shuffle,
+            conflict_weights: self.conflict_weights,
+            time_budget: self.time_budget,
+            stop_time: self.stop_time,
+            max_group_solve_time: self.max_group_solve_time,
+            time_fraction: self.time_fraction,
+            solver: self.solver.fork().unwrap_or_else(|| Box::new(AaltafSolver::new())),
+            solver_timeout: self.solver_timeout,
+            solver_validator: None,
+            cross_validate: self.cross_validate,
+            on_progress: None,
+            transcript: self.transcript.as_ref().map(|(_, max_len)| (Vec::new(), *max_len)),
+            formula_dump: self.formula_dump.clone(),
+            formula_growth_limit: self.formula_growth_limit,
+            stall_limit: self.stall_limit,
+            max_solver_calls: self.max_solver_calls,
+            memory_budget: self.memory_budget,
+            max_learned_clauses: self.max_learned_clauses,
+            solver_errors: self.solver_errors.clone(),
+            max_depth: self.max_depth,
+            merge_threshold: self.merge_threshold,
+            max_expansions: self.max_expansions,
+            best_first: self.best_first,
+            candidate_evaluation: self.candidate_evaluation,
+            conflict_selection: self.conflict_selection,
+            try_given_order: self.try_given_order,
+            return_best_on_timeout: self.return_best_on_timeout,
+            precedence: self.precedence.clone(),
+            ordering_cost: None,
+            fewest_steps: self.fewest_steps,
+            fw_state_cache: None,
+            stats: TrtaStats::default(),
+            strategy_phantom: PhantomData,
+            permutator_phantom: PhantomData,
+            #[cfg(feature = "count-states")]
+            num_tree_states: 0,
+            #[cfg(feature = "count-states")]
+            num_solver_states: 0,
+            #[cfg(feature = "count-states")]
+            seen_difficult_dependency: false,
+        }
+    }
 
-        // 迭代 modifiers，提取 source 和 target
-        let first_modifiers: Vec<ConfigModifier> = self
-            .groups
-            .iter()
-            .filter_map(|group| group.first().cloned()) // 获取每个group的第一个modifier
-            .collect();
-        // println!("first_modifiers: {:?}", first_modifiers);
-        for modifier in &first_modifiers {
-            match modifier {
-                // 处理 Remove 和 Insert 两种情况
-                ConfigModifier::Remove(BgpSession { source, target, .. })
-                | ConfigModifier::Insert(BgpSession { source, target, .. }) => {
-                    // 将 (source, target) 元组加入到列表中
-                    session_pairs.push((*source, *target));
-                }
-                _ => {}
+    /// Search for up to `limit` distinct valid orderings. After a valid ordering is found, the
+    /// exploration continues with the next ordering of the groups (and the solver is told not to
+    /// return the same sequence again). The orderings are returned in the order in which they were
+    /// found. If the exploration stops early (e.g., because the time budget is used up, or because
+    /// all orderings were explored), the orderings found so far are returned. Only if no valid
+    /// ordering was found (or on an internal error), the error is returned instead.
+    pub fn work_all(
+        &mut self,
+        abort: Stopper,
+        limit: usize,
+    ) -> Result<Vec<Vec<ConfigModifier>>, Error> {
+        let mut found = Vec::new();
+        if limit == 0 {
+            return Ok(found);
+        }
+        match self.explore(abort, limit, &mut found) {
+            Ok(()) => Ok(found),
+            Err(e) if found.is_empty() || matches!(e, Error::Internal(_)) => Err(e),
+            Err(e) => {
+                info!("Found {} valid orderings before stopping: {}", found.len(), e);
+                Ok(found)
             }
         }
+    }
 
-        //打印出生成的列表
-        // println!("Session pairs: {:?}", session_pairs);
-
-        //最终目的是产生aalta_input，送进aalta中，但是循环的是ltl_string
-        let mut ltl_string = "True".to_string();
-        //构建每个状态只能做一个update的约束
-        let mut formula_parts = Vec::new();
+    /// Encode the whole problem into a single LTL formula, and call the solver only once, instead
+    /// of interleaving the exploration with the solver (as `work` does). The conflicts are
+    /// discovered up front by evaluating every group on the initial state, and directly after
+    /// every other group (which can be applied on the initial state). Every conflict is compiled
+    /// into the same constraint, which `work` would learn from it. The ordering returned by the
+    /// solver is checked against the hard policies before returning it.
+    ///
+    /// This mode has the following limitations:
+    ///
+    /// - Conflicts which only appear after two or more other groups are not discovered. If the
+    ///   ordering of the solver violates the hard policies due to such a conflict,
+    ///   `Error::ProbablyNoSafeOrdering` is returned, even though a valid ordering may exist.
+    /// - The constraints are global, even if the conflict only appears after another group. Hence,
+    ///   the formula may be unsatisfiable, even though a valid ordering exists. In this case,
+    ///   `Error::ProbablyNoSafeOrdering` is returned as well.
+    /// - All $n^2$ pairs of groups are evaluated, even if most of them do not conflict.
+    /// - Groups are neither merged, nor are conflicts expanded, and the solver validator is not
+    ///   asked.
+    #[cfg(feature = "ltl-solver")]
+    pub fn solve_monolithic(&mut self) -> Result<Vec<ConfigModifier>, Error> {
+        let num_groups = self.groups.len();
+        let router_groups = ltl::router_groups(&self.groups);
+        let mut formula = self.learned_formula(&self.group_precedence());
+        if let Some((transcript, _)) = self.transcript.as_mut() {
+            transcript.clear();
+        }
+        self.stats = TrtaStats::default();
 
-        //for i in 0..frame.rem_groups.len() {
-        // 24.1.9 for i in 0..self.groups.len() {
-        for i in 0..self.groups.len() {
-            let mut negations = Vec::new();
-            for j in 0..self.groups.len() {
-                // for j in 0..self.groups.len() {
-                if i != j {
-                    negations.push(format!("! x{}", j));
+        let mut net = self.net.clone();
+        let mut hard_policy = self.hard_policy.clone();
+        let prefixes = std::iter::once(None).chain((0..num_groups).map(Some));
+        for first in prefixes {
+            let prefix: Vec<usize> = first.into_iter().collect();
+            let initial = Snapshot::take(&net, &hard_policy);
+            if let Some(first) = first {
+                if self.apply_group(&mut net, &mut hard_policy, first)?.is_err() {
+                    continue;
                 }
             }
-            // let formula = format!("G(x{} -> ({}))", i, negations.join(" & "));
-            let formula = format!("G((x{} & {}) <-> (e{}))", i, negations.join(" & "), i);
-            formula_parts.push(formula);
-        }
-        let mut always_formula_parts = formula_parts.join(" & ");
-        always_formula_parts.push_str(&format!(
-            " & G({}) & {}\n",
-            (0..self.groups.len())
-                .map(|i| format!("((e{}) & N(G(! e{})))", i, i))
-                .collect::<Vec<_>>()
-                .join(" | "),
-            (0..self.groups.len() - 1).fold("true".to_string(), |acc, _| format!("X({})", acc))
-        ));
-        // always_formula_parts.push_str(&format!(
-        //     "{}\n",
-        //     (0..20).map(|i| format!("& (F (x{}))", i)).collect::<Vec<_>>().join(" ")
-        // ));
-
-        // always_formula_parts.push_str(&format!(
-        //     "{}\n",
-        //     (0..self.groups.len()).map(|i| format!("& (F (x{}))", i)).collect::<Vec<_>>().join(" ")
-        // ));
-        println!("formula_parts: {:?}", always_formula_parts);
-
-        loop {
-            // check for iter overflow检查时间是否已耗尽（即处理时间是否超时）
-            if self.stop_time.as_ref().map(|time| time.elapsed().is_ok()).unwrap_or(false) {
-                // time budget is used up!
-                error!("Time budget is used up! No solution was found yet!");
-                return Err(Error::Timeout);
+            for doing in (0..num_groups).filter(|group| !prefix.contains(group)) {
+                let before = Snapshot::take(&net, &hard_policy);
+                match self.apply_group(&mut net, &mut hard_policy, doing)? {
+                    Ok(()) => before.restore(&mut net, &mut hard_policy)?,
+                    Err(failure) => {
+                        let e = failure.error.unwrap_or(NetworkError::NoConvergence);
+                        let fixes = ltl::conflict_fixes(&e, &router_groups, doing, &prefix);
+                        let clause = fixes
+                            .iter()
+                            .filter_map(|fixes| ltl::blackhole_constraint(fixes, doing, &prefix))
+                            .join(" & ");
+                        let clause = if clause.is_empty() {
+                            ltl::block_prefix_formula(&prefix, doing)
+                        } else {
+                            clause
+                        };
+                        self.stats.conflicts.push(ConflictRecord {
+                            prefix: prefix.clone(),
+                            doing,
+                            routers: routers_from_error(&e),
+                            matched_groups: fixes,
+                            clause: clause.clone(),
+                        });
+                        formula.learn(clause);
+                    }
+                }
             }
+            initial.restore(&mut net, &mut hard_policy)?;
+        }
+        info!("Discovered {} conflicts", self.stats.conflicts.len());
 
-            // check for abort criteria检查是否有中止请求
-            if abort.try_is_stop().unwrap_or(false) {
-                info!("Operation was aborted!");
-                return Err(Error::Abort);
+        let ordering = match self.query_solver(&formula.query(), &Stopper::new())? {
+            Some(ordering) => ordering,
+            None => {
+                error!("The monolithic LTL formula is unsatisfiable!");
+                return Err(Error::ProbablyNoSafeOrdering);
             }
+        };
+        validate_ordering(&ordering, num_groups)?;
+        let ordering = utils::finalize_ordering(&self.groups, &ordering);
+        if let Err(e) = self.verify_ordering(&ordering) {
+            error!("The ordering of the monolithic formula is not valid: {}", e);
+            return Err(Error::ProbablyNoSafeOrdering);
+        }
+        Ok(ordering)
+    }
 
-            // get the latest stack frame获取当前堆栈帧（用于管理待处理的组）
-            let frame = match stack.last_mut() {
-                Some(frame) => {
-                    // println!("Current frame: {:?}", frame);
-                    frame
-                }
-                None => {
-                    error!("Could not find any valid ordering!");
-                    return Err(Error::ProbablyNoSafeOrdering);
-                }
-            };
-            // println!("self.groups.len(): {}", self.groups.len());
-            // 假设 done_updates 是一个 Vec<usize>，需要提前定义
-            // let mut done_updates = Vec::new();
-            //let mut doing_update = *frame.rem_groups.get(frame.idx).unwrap();
-
-            // 将 doing_update 添加到 done_updates 列表中
-            // done_updates.push(doing_update);
-
-            // 输出 done_updates 列表
-            // println!("Done updates: {:?}", done_updates);
-            let mut indices = Vec::new();
-            // search the current stack frame for the next        // 查找当前堆栈帧的下一步操作
-            let action: StackAction = match self.get_next_option(&mut net, &mut hard_policy, frame)
-            {
-                Ok(next_idx) => {
-                    // update the current stack frame and prepare the next one
-                    frame.idx = next_idx + 1;
-                    // There exists a valid next step! Update the current sequence and the stack
-                    let next_group_idx = frame.rem_groups[next_idx];
-                    current_sequence.push(next_group_idx);
-                    // println!("current_sequence.len(): {}", current_sequence.len());
-                    // check if all groups have been added to the sequence
-                    if current_sequence.len() == self.groups.len() {
-                        // We are done! found a valid solution!
-                        info!(
-                            "Valid solution was found! Learned {} groups",
-                            self.groups.iter().filter(|g| g.len() > 1).count()
-                        );
-                        return Ok(utils::finalize_ordering(&self.groups, &current_sequence));
-                    }
+    /// Set whether the groups are shuffled before exploring them (which is the default). If not,
+    /// the groups are explored in the order in which the modifiers were passed to `new`. In this
+    /// case, the output is fully deterministic given the input (as long as the solver is).
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        self.shuffle = shuffle;
+    }
 
-                    // Prepare the stack action with the new stack frame
-                    StackAction::Push(StackFrame {
-                        rem_groups: frame
-                            .rem_groups
-                            .iter()
-                            .cloned()
-                            .filter(|x| *x != next_group_idx)
-                            .collect(),
-                        idx: 0, // 这里我们明确设置 idx 为 0 或者根据逻辑需要的特定值
-                        num_undo: self.groups[next_group_idx].len(), // 不使用随机数生成器
-                    })
-                    // StackAction::Push(StackFrame::new(
-                    //     frame.rem_groups.iter().cloned().filter(|x| *x != next_group_idx),
-                    //     self.groups[next_group_idx].len(),
-                    //     &mut self.rng,
-                    // ))
-                }
-                Err(NetworkError::ForwardingBlackHole(check_idx)) => {
-                    println!("Now we have the Extracted NodeIndices: {:?}", check_idx);
-                    let mut formulas = Vec::new();
-
-                    for node in check_idx.iter() {
-                        let mut node_formulas = Vec::new();
-                        //遍历有问题的节点
-                        let mut matched_indices: Vec<usize> = Vec::new(); //把涉及有问题节点的更新的下标取出来
-                                                                          // 遍历 session_pairs 并检查是否匹配
-                        for (i, (source, target)) in session_pairs.iter().enumerate() {
-                            if *source == *node || *target == *node {
-                                // 如果匹配，则将下标存储到 matched_indices
-                                matched_indices.push(i);
-                            }
-                        }
-                        println!("Matched session indices: {:?}", matched_indices);
-                        //每次取出来，如果不等于已经执行的更新，添加约束
-                        for &index in &matched_indices {
-                            // 确保 index 不等于 current_sequence 中的任何一项，并且不等于 frame.rem_groups.get(0)
-                            if !(current_sequence.contains(&index)
-                                || (0..=frame.idx).any(|i| frame.rem_groups.get(i) == Some(&index)))
-                            {
-                                let formula = format!("N(G(! e{:?}))", index);
-                                node_formulas.push(formula);
-                            }
-                        }
-                        if !node_formulas.is_empty() {
-                            // 将每个 node 的公式用括号包裹，并连接起来
-                            let combined_node_formula = format!(
-                                "G(e{} -> ({}))",
-                                *frame.rem_groups.get(frame.idx).unwrap(),
-                                node_formulas.join(" | ")
-                            );
-                            formulas.push(combined_node_formula); // 添加到总公式集合
-                        }
-                    }
+    /// Set whether the random order of the groups is weighted by the conflict history (disabled by
+    /// default). If enabled, groups which could not be applied in many of the previous conflicts
+    /// are less likely to be placed at the front of the order. Since the statistics are reset when
+    /// the exploration starts, this mainly affects the order after a forced reset (see
+    /// [`StrategyTRTA::set_stall_limit`]).
+    pub fn set_conflict_weights(&mut self, conflict_weights: bool) {
+        self.conflict_weights = conflict_weights;
+    }
 
-                    //如果formulas为空，那么要么在没做的更新里面没有直连的bgp session可以解决问题，没做的更新集合为空
-                    let combined_formula = if formulas.is_empty() {
-                        // 初始化 combined_formula
-                        let mut combined_formula =
-                            format!("e{}", *frame.rem_groups.get(frame.idx).unwrap());
+    /// Set the LTL solver, which is called when the exploration is stuck. By default, the external
+    /// `aaltaf` binary is used ([`AaltafSolver`]), spawning a new process for every query. Use
+    /// [`AaltaSession`](crate::strategies::AaltaSession) to keep a single process alive.
+    pub fn set_solver(&mut self, solver: Box<dyn LtlSolver>) {
+        self.solver = solver;
+    }
 
-                        // 从倒数第二个元素到第一个元素构建 LTL 表达式
-                        for i in (0..current_sequence.len()).rev() {
-                            combined_formula =
-                                format!("e{} & X({})", current_sequence[i], combined_formula);
-                        }
+    /// Set the time budget for solving a single group (i.e., for finding a single dependency). This
+    /// also sets the solver timeout (see [`StrategyTRTA::set_solver_timeout`]). By default, this is
+    /// the time budget passed to `new`, divided by 30.
+    pub fn set_max_group_solve_time(&mut self, time: Duration) {
+        self.max_group_solve_time = Some(time);
+        self.solver_timeout = Some(time);
+    }
 
-                        // 给 combined_formula 添加 ! 外围
-                        format!("!({})", combined_formula)
-                    } else {
-                        formulas.join(" & ").to_string()
-                    };
+    /// Set the time budget for solving a single group as a fraction of the total time budget passed
+    /// to `new`, i.e., the total budget is divided by `fraction` (by default, `fraction` is 30). This
+    /// also sets the solver timeout (see [`StrategyTRTA::set_solver_timeout`]). If no time budget
+    /// was given, the time for a single group remains unlimited. A `fraction` of 0 is treated as 1,
+    /// and if the budget is too small to be divided by `fraction`, the entire budget is used for a
+    /// single group.
+    pub fn set_time_fraction(&mut self, fraction: u32) {
+        self.time_fraction = fraction.max(1);
+        self.max_group_solve_time = group_solve_time(self.time_budget, self.time_fraction);
+        self.solver_timeout = self.max_group_solve_time;
+    }
 
-                    // println!("Combined formula: {}", combined_formula);
+    /// Returns the fraction of the total time budget used for solving a single group. See
+    /// [`StrategyTRTA::set_time_fraction`].
+    pub fn time_fraction(&self) -> u32 {
+        self.time_fraction
+    }
 
-                    // // 如果当前执行的更新为最后一个更新，但是无效，此时学习不到任何约束，但仍需阻止当前更新序列
-                    // if self.groups.len() == current_sequence.len() + 1 {
-                    //     // 构建 LTL 公式，从 current_sequence 的最后一个元素开始
-                    //     let mut blocked_sequence =
-                    //         format!("x{}", *frame.rem_groups.get(frame.idx).unwrap());
+    /// Set the maximum time for a single call to the solver. If the solver does not answer within
+    /// this time (or within the overall time budget), it is stopped, and `work` returns
+    /// `Error::Timeout`. By default, this is the time budget for a single group.
+    pub fn set_solver_timeout(&mut self, timeout: Option<Duration>) {
+        self.solver_timeout = timeout;
+    }
 
-                    //     // 从倒数第二个元素到第一个元素构建 LTL 表达式
-                    //     for i in (0..current_sequence.len()).rev() {
-                    //         blocked_sequence =
-                    //             format!("x{} & X(F({}))", current_sequence[i], blocked_sequence);
-                    //     }
-                    //     blocked_sequence = format!("!({})", blocked_sequence);
+    /// Record every query to the solver, along with its result, during the next call to `work`. If
+    /// `max_query_len` is set, then only the first `max_query_len` bytes of every query are kept.
+    /// The transcript can be accessed with [`StrategyTRTA::transcript`].
+    pub fn set_record_transcript(&mut self, max_query_len: Option<usize>) {
+        self.transcript = Some((Vec::new(), max_query_len));
+    }
 
-                    //     // 打印构建好的 LTL 公式
-                    //     println!("Blocked sequence LTL formula: {}", blocked_sequence);
+    /// Write every query to the solver to the file at `path`, along with the parsed result, during
+    /// every call to `work`. The file is truncated at the start of `work`, and the queries are
+    /// numbered starting from 1. This can be used to reproduce a run with a standalone solver.
+    pub fn set_formula_dump(&mut self, path: Option<PathBuf>) {
+        self.formula_dump = path;
+    }
 
-                    //     combined_formula = format!("{} & {}", combined_formula, blocked_sequence);
-                    // }
+    /// Returns the transcript of all solver calls of the last run, or an empty slice if recording
+    /// is not enabled. See [`StrategyTRTA::set_record_transcript`].
+    pub fn transcript(&self) -> &[SolverExchange] {
+        self.transcript.as_ref().map(|(t, _)| t.as_slice()).unwrap_or(&[])
+    }
 
-                    // 构造LTL公式的条件
-                    let prefix = if current_sequence.is_empty() {
-                        "True".to_string()
-                    } else {
-                        // 初始的 LTL 表达式是 prefix 中最后一个元素
-                        let mut prefix_expr =
-                            format!("x{}", current_sequence[current_sequence.len() - 1]);
-
-                        // 从倒数第二个元素到第一个元素构建 LTL 表达式
-                        for i in (0..current_sequence.len() - 1).rev() {
-                            // 这里用 prefix_list.len() - 1
-                            prefix_expr =
-                                format!("x{} & X(F({}))", current_sequence[i], prefix_expr);
-                        }
-                        prefix_expr
-                    };
+    /// Returns the statistics of the last call to `work` (or of the current one, if it was
+    /// interrupted). See [`TrtaStats`].
+    pub fn stats(&self) -> &TrtaStats {
+        &self.stats
+    }
 
-                    // 打印生成的 LTL 表达式
-                    // println!("Generated LTL Prefix: {}", prefix);
-                    let prefix = "True".to_string();
-                    //(prefix -> (combined_formula)) & ltl_string & always_formula_parts & F x0 & F x1 & F x2 & F x3 & F x4 & F x5
-                    ltl_string = format!("({}) & {}", combined_formula, ltl_string);
-                    let aalta_input = format!("({}) & {}", ltl_string, always_formula_parts);
-                    println!("aalta_input: {}", aalta_input);
-                    let mut file = File::create("aalta_input.txt").unwrap();
-                    file.write_all(aalta_input.as_bytes()).unwrap();
-                    file.flush().unwrap();
-                    //新建子线程
-                    let mut child = Command::new("../../../aaltaf/aaltaf") // 替换为你的可执行文件名
-                        .arg("-e")
-                        // .stdin(Stdio::piped())
-                        .stdout(Stdio::piped())
-                        .spawn()
-                        .expect("Failed to start process");
-
-                    // {
-                    //     // 获取子进程的标准输入(aalta_input)
-                    //     let stdin = child.stdin.as_mut().expect("Failed to open stdin");
-                    //     stdin.write_all(aalta_input.as_bytes()).expect("Failed to write to stdin");
-                    //     stdin.flush().expect("Failed to flush stdin");
-                    // }
-                    // println!("Finish aalta input!");
-                    let output = child.stdout.take().expect("Failed to open stdout");
-                    // println!("Begin aalta output!");
-                    // 使用 BufReader 来读取输出
-                    let mut output_str = String::new();
-                    let mut reader = BufReader::new(output);
-                    reader.read_to_string(&mut output_str).expect("Failed to read stdout");
-                    // println!("Output: {}", output_str);
-
-                    // 对aalta的输出进行解析
-                    let lines: Vec<&str> = output_str.lines().collect();
-                    // let mut indices = Vec::new();
-                    // 检查结果是否为sat
-                    if lines.len() > 1 && lines[0].trim() == "sat" {
-                        // 对从第三行之后的结果进行处理
-                        for line in lines.iter().skip(1) {
-                            // skip first two lines (header and "sat")
-                            // 按，分片
-                            for part in line.split(",") {
-                                let trimmed = part.trim();
-
-                                // 检索所有以x开始的变量
-                                if trimmed.starts_with("x") {
-                                    // Extract the number after "x", whether or not it is preceded by "!"
-                                    if let Some(index_str) = trimmed.strip_prefix("x") {
-                                        if let Ok(index) = index_str.trim().parse::<usize>() {
-                                            // Push the extracted index to the indices vector
-                                            indices.push(index);
-                                        }
-                                    }
-                                    //检索在开头但是以（x开始的变量
-                                } else if trimmed.starts_with("(x") {
-                                    // Handle the case for "(xN" where N is the index we want
-                                    if let Some(index_str) = trimmed.strip_prefix("(x") {
-                                        if let Ok(index) = index_str.trim().parse::<usize>() {
-                                            indices.push(index);
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    /// Returns all conflicts of the last call to `work`, in the order in which they were learned.
+    /// If `work` returned `Error::ProbablyNoSafeOrdering`, these are the constraints which made
+    /// the learned formula unsatisfiable, and can be rendered to explain why no ordering was found.
+    /// This is equal to the [`TrtaStats::conflicts`] of [`StrategyTRTA::stats`].
+    pub fn last_conflicts(&self) -> &[ConflictRecord] {
+        &self.stats.conflicts
+    }
 
-                        // 输出解析出来的更新序列
-                        println!("Extracted indices: {:?}", indices);
-                    } else {
-                        println!("Second line is not 'sat', skipping extraction.");
-                    }
-                    // 等待子进程完成
-                    let exit_status = child.wait().expect("Child process wasn't running");
-
-                    // 打印子进程的退出状态
-                    println!("Child exited with status: {}", exit_status);
-
-                    // // 直接退出主进程
-                    // std::process::exit(exit_status.code().unwrap_or(1));
-                    // let stack_frame = StackFrame { idx: 0, rem_groups: indices, num_undo: 0 };
-
-                    // 将新的栈帧添加到栈中
-                    // stack.push(stack_frame);
-                    StackAction::Reset
-                    // StackAction::Push(stack_frame)
-
-                    // #[cfg(feature = "count-states")]
-                    // {
-                    //     self.seen_difficult_dependency = true;
-                    // }
-                    // // There exists no option, that we can take, which would lead to a good result!
-                    // // First, we set the next index to the length of the options, in order to
-                    // // remember that we have checked everything
-                    // frame.idx = frame.rem_groups.len();
-                    // // What we do here is try to find a dependency!
-                    // match self.find_dependency(
-                    //     &mut net,
-                    //     &mut hard_policy,
-                    //     &current_sequence,
-                    //     frame.rem_groups[check_idx],
-                    //     abort.clone(),
-                    // ) {
-                    //     Some((new_group, old_groups)) => {
-                    //         info!("Found a new dependency group!");
-                    //         // add the new ordering to the known groups
-                    //         utils::add_minimal_ordering_as_new_gorup(
-                    //             &mut self.groups,
-                    //             old_groups,
-                    //             Some(new_group),
-                    //         );
-                    //         // reset the stack frame
-                    //         StackAction::Reset
-                    //     }
-                    //     None => {
-                    //         // No dependency group could be found! Continue exploring the search
-                    //         // space
-                    //         info!("Could not find a new dependency group!");
-                    //         StackAction::Pop
-                    //     }
-                    // }
-                }
-                Err(NetworkError::ForwardingLoops(check_idx)) => {
-                    println!("Now we have the Extracted NodeIndices: {:?}", check_idx);
-                    let mut combined_formula_from_loop = Vec::new();
-                    for forwardingloop in check_idx.iter() {
-                        let mut node_formulas = Vec::new();
-                        let mut matched_indices: Vec<usize> = Vec::new(); //把涉及有问题节点的更新的下标取出来
-                        for node in forwardingloop.iter() {
-                            // 遍历有问题的节点
-                            // 遍历 session_pairs 并检查是否匹配
-                            for (i, (source, target)) in session_pairs.iter().enumerate() {
-                                if (*source == *node || *target == *node)
-                                    && !matched_indices.contains(&i)
-                                {
-                                    // 如果匹配，则将下标存储到 matched_indices
-                                    matched_indices.push(i);
-                                }
-                            }
-                            println!("Matched session indices: {:?}", matched_indices);
-                        }
-                        //每次取出来，如果不等于已经执行的更新，添加约束
-                        for &index in &matched_indices {
-                            // 确保 index 不等于 current_sequence 中的任何一项，并且不等于 frame.rem_groups.get(0)
-                            if !(current_sequence.contains(&index)
-                                || (0..=frame.idx).any(|i| frame.rem_groups.get(i) == Some(&index)))
-                            {
-                                let formula = format!("N(G(! e{:?}))", index);
-                                node_formulas.push(formula);
-                            }
-                        }
-                        if !node_formulas.is_empty() {
-                            // 将每个 node 的公式用括号包裹，并连接起来
-                            let combined_node_formula = format!(
-                                "G(e{} -> ({}))",
-                                *frame.rem_groups.get(frame.idx).unwrap(),
-                                node_formulas.join(" | ")
-                            );
-                            combined_formula_from_loop.push(combined_node_formula);
-                            println!(
-                                "combined_formula_from_loop: {:?}",
-                                combined_formula_from_loop
-                            );
-                            // 添加到总公式集合
-                        }
-                    }
+    /// Returns all groups with more than one modifier, i.e., the dependencies learned while
+    /// synthesizing the ordering. The modifiers of each group are in the order in which they must
+    /// be applied.
+    pub fn learned_groups(&self) -> Vec<&[ConfigModifier]> {
+        self.groups.iter().filter(|g| g.len() > 1).map(|g| g.as_slice()).collect()
+    }
 
-                    //如果combined_formula_from_loop为空，那么要么在没做的更新里面没有直连的bgp session可以解决问题，没做的更新集合为空
-                    let combined_formula_form_loops = if combined_formula_from_loop.is_empty() {
-                        // 初始化 combined_formula
-                        let mut combined_formula =
-                            format!("e{}", *frame.rem_groups.get(frame.idx).unwrap());
+    /// Use the `aaltaf` binary at the given path as the solver. By default, the path is read from
+    /// the environment variable `SNOWCAP_AALTAF_PATH`, or `aaltaf` is searched in `$PATH`.
+    pub fn set_solver_path(&mut self, path: impl Into<PathBuf>) {
+        self.solver = Box::new(AaltafSolver::with_path(path));
+    }
 
-                        // 从倒数第二个元素到第一个元素构建 LTL 表达式
-                        for i in (0..current_sequence.len()).rev() {
-                            combined_formula =
-                                format!("e{} & X({})", current_sequence[i], combined_formula);
-                        }
+    /// Set a validator, which is called on every ordering parsed from the solver output, before it
+    /// is used to continue the exploration. If the validator returns `false`, the ordering is
+    /// rejected, and a clause forbidding this exact ordering is added to the formula. This can be
+    /// used to enforce invariants, that are not captured by the LTL formula.
+    pub fn set_solver_validator(&mut self, validator: SolverValidator) {
+        self.solver_validator = Some(validator);
+    }
 
-                        // 给 combined_formula 添加 ! 外围
-                        format!("!({})", combined_formula)
-                    } else {
-                        combined_formula_from_loop.join(" & ").to_string()
-                    };
+    /// Set whether every ordering returned by the solver is simulated on the initial network,
+    /// before it is used to continue the exploration. The LTL formula may not capture all
+    /// semantics of the hard policies. If the ordering violates the hard policies, the conflict is
+    /// learned as a clause forbidding the prefix up to the first failing group, and the ordering
+    /// is not explored. By default, the solver ordering is explored without simulating it first.
+    pub fn set_cross_validate(&mut self, cross_validate: bool) {
+        self.cross_validate = cross_validate;
+    }
 
-                    // println!("combined_formula_form_loops: {}", combined_formula_form_loops);
+    /// Set a callback, which is called with the current sequence of groups (as indices) every time
+    /// a group was successfully applied during the exploration. The callback is on the hot path of
+    /// the exploration, so it must be cheap!
+    pub fn set_on_progress(&mut self, callback: ProgressCallback) {
+        self.on_progress = Some(callback);
+    }
 
-                    // // 如果当前执行的更新为最后一个更新，但是无效，此时学习不到任何约束，但仍需阻止当前更新序列
-                    // if self.groups.len() == current_sequence.len() + 1 {
-                    //     // 构建 LTL 公式，从 current_sequence 的最后一个元素开始
-                    //     let mut blocked_sequence =
-                    //         format!("x{}", *frame.rem_groups.get(frame.idx).unwrap());
+    /// Abort the exploration with `Error::NoProgress` as soon as the learned constraints of the LTL
+    /// formula (i.e., without the ordering constraints) are larger than `max_formula_len` bytes,
+    /// while the longest valid prefix reached before a reset did not improve during the last
+    /// `max_resets` resets. This detects runs where the solver keeps learning new clauses, without
+    /// getting any closer to a solution.
+    pub fn set_formula_growth_limit(&mut self, max_formula_len: usize, max_resets: usize) {
+        self.formula_growth_limit = Some((max_formula_len, max_resets));
+    }
 
-                    //     // 从倒数第二个元素到第一个元素构建 LTL 表达式
-                    //     for i in (0..current_sequence.len()).rev() {
-                    //         blocked_sequence =
-                    //             format!("x{} & X(F({}))", current_sequence[i], blocked_sequence);
-                    //     }
-                    //     blocked_sequence = format!("!({})", blocked_sequence);
+    /// Limit the memory used by the exploration to `budget` bytes. Whenever the exploration is
+    /// stuck, the memory is estimated as the length of the LTL query passed to the solver, plus the
+    /// size of the exploration stack (the current sequence and the groups explored at each
+    /// position). If this estimate exceeds the budget, the oldest learned constraints are dropped
+    /// (if `drop_learned` is set), or the exploration is aborted with
+    /// `Error::MemoryBudgetExceeded`. The budget is also exceeded if dropping all but the most
+    /// recent constraint is not enough.
+    ///
+    /// The estimate is a lower bound of the actual memory: It ignores the allocation overhead, the
+    /// undo history of the network, and the memory of the solver process. Also, the constraint
+    /// learned from the current conflict is only counted at the next check. Dropping learned
+    /// constraints allows the solver to return orderings which were already ruled out, which may
+    /// lead to more resets.
+    pub fn set_memory_budget(&mut self, budget: usize, drop_learned: bool) {
+        self.memory_budget = Some((budget, drop_learned));
+    }
 
-                    //     // 打印构建好的 LTL 公式
-                    //     println!("Blocked sequence LTL formula: {}", blocked_sequence);
+    /// Keep only the `max_learned_clauses` most recently learned constraints in the formula passed
+    /// to the solver, dropping the older ones whenever a new constraint is learned. This is similar
+    /// to clause deletion in SAT solvers, and keeps the solver queries small. The most recent
+    /// constraint is always kept. By default (`usize::MAX`), all constraints are kept. Like with
+    /// [`StrategyTRTA::set_memory_budget`], the solver may then return orderings which were already
+    /// ruled out.
+    pub fn set_max_learned_clauses(&mut self, max_learned_clauses: usize) {
+        self.max_learned_clauses = max_learned_clauses;
+    }
 
-                    //     combined_formula_form_loops =
-                    //         format!("{} & {}", combined_formula_form_loops, blocked_sequence);
-                    // }
+    /// Force a reset of the exploration, if the longest valid sequence reached did not improve
+    /// during `max_iterations` iterations (i.e., orderings checked by the exploration). The
+    /// exploration then continues with a new, random order of the groups, while all learned
+    /// constraints are kept. This breaks the exploration out of a livelock, where it keeps
+    /// backtracking without ever getting further. By default, the exploration is never forced to
+    /// reset.
+    pub fn set_stall_limit(&mut self, max_iterations: usize) {
+        self.stall_limit = Some(max_iterations);
+    }
 
-                    // 构造LTL公式的条件
-                    let prefix = if current_sequence.is_empty() {
-                        "True".to_string()
-                    } else {
-                        // 初始的 LTL 表达式是 prefix 中最后一个元素
-                        let mut prefix_expr =
-                            format!("x{}", current_sequence[current_sequence.len() - 1]);
-
-                        // 从倒数第二个元素到第一个元素构建 LTL 表达式
-                        for i in (0..current_sequence.len() - 1).rev() {
-                            // 这里用 prefix_list.len() - 1
-                            prefix_expr =
-                                format!("x{} & X(F({}))", current_sequence[i], prefix_expr);
-                        }
-                        prefix_expr
-                    };
+    /// Set the kinds of errors, for which the solver is called when the exploration is stuck. For
+    /// all other errors, the exploration backtracks instead. By default, all kinds in
+    /// [`SolverErrorKind`] are solver-worthy.
+    pub fn set_solver_errors(&mut self, kinds: impl IntoIterator<Item = SolverErrorKind>) {
+        self.solver_errors = kinds.into_iter().collect();
+    }
 
-                    // 打印生成的 LTL 表达式
-                    // println!("Generated LTL Prefix: {}", prefix);
-                    let prefix = "True".to_string();
-
-                    //(prefix -> (combined_formula_form_loops)) & ltl_string & always_formula_parts & F x0 & F x1 & F x2 & F x3 & F x4 & F x5
-                    ltl_string = format!("({}) & {}", combined_formula_form_loops, ltl_string);
-                    let aalta_input = format!("({}) & {}", ltl_string, always_formula_parts);
-                    // println!("aalta_input: {}", aalta_input);
-                    let mut file = File::create("aalta_input.txt").unwrap();
-                    file.write_all(aalta_input.as_bytes()).unwrap();
-                    file.flush().unwrap();
-                    //新建子线程
-                    let mut child = Command::new("../../../aaltaf/aaltaf") // 替换为你的可执行文件名
-                        .arg("-e")
-                        // .stdin(Stdio::piped())
-                        .stdout(Stdio::piped())
-                        .spawn()
-                        .expect("Failed to start process");
-
-                    // {
-                    //     // 获取子进程的标准输入(aalta_input)
-                    //     let stdin = child.stdin.as_mut().expect("Failed to open stdin");
-                    //     stdin.write_all(aalta_input.as_bytes()).expect("Failed to write to stdin");
-                    //     stdin.flush().expect("Failed to flush stdin");
-                    // }
-                    // println!("Finish aalta input!");
-                    let output = child.stdout.take().expect("Failed to open stdout");
-                    // println!("Begin aalta output!");
-                    // 使用 BufReader 来读取输出
-                    let mut output_str = String::new();
-                    let mut reader = BufReader::new(output);
-                    reader.read_to_string(&mut output_str).expect("Failed to read stdout");
-                    println!("Output: {}", output_str);
-
-                    // 对aalta的输出进行解析
-                    let lines: Vec<&str> = output_str.lines().collect();
-                    // let mut indices = Vec::new();
-                    // 检查结果是否为sat
-                    if lines.len() > 1 && lines[0].trim() == "sat" {
-                        // 对从第三行之后的结果进行处理
-                        for line in lines.iter().skip(1) {
-                            // skip first two lines (header and "sat")
-                            // 按，分片
-                            for part in line.split(",") {
-                                let trimmed = part.trim();
-
-                                // 检索所有以x开始的变量
-                                if trimmed.starts_with("x") {
-                                    // Extract the number after "x", whether or not it is preceded by "!"
-                                    if let Some(index_str) = trimmed.strip_prefix("x") {
-                                        if let Ok(index) = index_str.trim().parse::<usize>() {
-                                            // Push the extracted index to the indices vector
-                                            indices.push(index);
-                                        }
-                                    }
-                                    //检索在开头但是以（x开始的变量
-                                } else if trimmed.starts_with("(x") {
-                                    // Handle the case for "(xN" where N is the index we want
-                                    if let Some(index_str) = trimmed.strip_prefix("(x") {
-                                        if let Ok(index) = index_str.trim().parse::<usize>() {
-                                            indices.push(index);
-                                        }
-                                    }
-                                }
-                            }
-                        }
+    /// Limit the depth of the exploration stack to `max_depth` groups. Once the current sequence
+    /// holds `max_depth` groups, the alternatives at the next position are no longer explored.
+    /// Instead, the first group which cannot be applied there triggers the dependency (or solver)
+    /// path right away. This trades completeness for speed, since less state needs to be undone
+    /// and applied again. By default, the depth is unlimited.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
 
-                        // 输出解析出来的更新序列
-                        // println!("Extracted indices: {:?}", indices);
-                    } else {
-                        println!("Second line is not 'sat', skipping extraction.");
-                    }
-                    // 等待子进程完成
-                    let exit_status = child.wait().expect("Child process wasn't running");
-
-                    // 打印子进程的退出状态
-                    // println!("Child exited with status: {}", exit_status);
-
-                    // // 直接退出主进程
-                    // std::process::exit(exit_status.code().unwrap_or(1));
-                    // let stack_frame = StackFrame { idx: 0, rem_groups: indices, num_undo: 0 };
-
-                    // 将新的栈帧添加到栈中
-                    // stack.push(stack_frame);
-                    StackAction::Reset
-                    // StackAction::Push(stack_frame)
-
-                    // #[cfg(feature = "count-states")]
-                    // {
-                    //     self.seen_difficult_dependency = true;
-                    // }
-                    // // There exists no option, that we can take, which would lead to a good result!
-                    // // First, we set the next index to the length of the options, in order to
-                    // // remember that we have checked everything
-                    // frame.idx = frame.rem_groups.len();
-                    // // What we do here is try to find a dependency!
-                    // match self.find_dependency(
-                    //     &mut net,
-                    //     &mut hard_policy,
-                    //     &current_sequence,
-                    //     frame.rem_groups[check_idx],
-                    //     abort.clone(),
-                    // ) {
-                    //     Some((new_group, old_groups)) => {
-                    //         info!("Found a new dependency group!");
-                    //         // add the new ordering to the known groups
-                    //         utils::add_minimal_ordering_as_new_gorup(
-                    //             &mut self.groups,
-                    //             old_groups,
-                    //             Some(new_group),
-                    //         );
-                    //         // reset the stack frame
-                    //         StackAction::Reset
-                    //     }
-                    //     None => {
-                    //         // No dependency group could be found! Continue exploring the search
-                    //         // space
-                    //         info!("Could not find a new dependency group!");
-                    //         StackAction::Pop
-                    //     }
-                    // }
-                }
-                _ => StackAction::Reset,
-            };
+    /// Merge two groups into a single one, as soon as the solver path learned `threshold` times that
+    /// one of them is the only group which can fix the problem caused by the other. Afterwards, both
+    /// are always applied together, in this order. Merging resets the learned constraints, since
+    /// they refer to the old groups. If `threshold` is `None`, groups are never merged. By default,
+    /// the threshold is 2.
+    pub fn set_merge_threshold(&mut self, threshold: Option<usize>) {
+        self.merge_threshold = threshold;
+    }
 
-            // at this point, the mutable reference to `stack` (i.e., `frame`) is dropped, which
-            // means that `stack` is no longer borrowed exclusively.
+    /// Set the maximum number of expansions for a single conflict. If the solver proves the learned
+    /// formula to be unsatisfiable, the last conflict is expanded by allowing one more group (which
+    /// was not yet part of it) to fix the problem, and the solver is asked again. Once the limit is
+    /// reached, `work` returns `Error::ProbablyNoSafeOrdering`. By default, the limit is 3.
+    pub fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+    }
 
-            match action {
-                StackAction::Pop => {
-                    // pop the stack, as long as the top frame has no options left
-                    'backtrace: while let Some(frame) = stack.last() {
-                        if frame.idx < frame.rem_groups.len() {
-                            break 'backtrace;
-                        } else {
-                            // undo the net, the hard policy and pop the current sequence
-                            current_sequence.pop();
-                            (0..frame.num_undo).for_each(|_| {
-                                net.undo_action().expect("Cannot undo the action on the network");
-                                hard_policy.undo();
-                            });
-                            // pop the stack
-                            stack.pop();
-                        }
-                    }
-                }
-                StackAction::Push(new_frame) => stack.push(new_frame),
-                StackAction::Reset => {
-                    // reset the stack for the new groups, as well as the sequence, the network and
-                    // the hard policies
-                    stack = vec![StackFrame::new(0..self.groups.len(), 0, &mut self.rng)];
-                    current_sequence.clear();
-                    net = self.net.clone();
-                    hard_policy = self.hard_policy.clone();
-                }
-            }
+    /// Enable or disable the best-first exploration. If enabled, every remaining group is tried at
+    /// the position where the exploration is stuck, and the one which leaves the fewest policy
+    /// violations is used to learn the dependency (or the conflict passed to the solver).
+    /// Otherwise, the exploration is stuck as soon as a group causes an error involving specific
+    /// routers, and the group used to learn the conflict is chosen among the failed ones (see
+    /// [`StrategyTRTA::set_conflict_selection`]). By default, the best-first exploration is
+    /// disabled.
+    pub fn set_best_first(&mut self, best_first: bool) {
+        self.best_first = best_first;
+    }
 
-            if !indices.is_empty() {
-                // 检查 indices 是否为空
-                let stack_frame = StackFrame {
-                    idx: 0,
-                    rem_groups: indices, // 使用 indices
-                    num_undo: 0,
-                };
-                stack.push(stack_frame); // 将新构造的 stack_frame 推入栈中
-            }
+    /// Set how a group is evaluated during the exploration (see [`CandidateEvaluation`]). By
+    /// default, [`CandidateEvaluation::Undo`] is used.
+    pub fn set_candidate_evaluation(&mut self, candidate_evaluation: CandidateEvaluation) {
+        self.candidate_evaluation = candidate_evaluation;
+    }
+
+    /// Set which of the failed groups is used to learn the conflict, once the exploration is stuck
+    /// (see [`ConflictSelection`]). This has no effect in the best-first mode, which always uses
+    /// the group leaving the fewest policy violations. By default,
+    /// [`ConflictSelection::MostViolations`] is used.
+    pub fn set_conflict_selection(&mut self, conflict_selection: ConflictSelection) {
+        self.conflict_selection = conflict_selection;
+    }
+
+    /// Set whether `work` first checks if the modifiers already work in the order in which they
+    /// were given, before exploring any other ordering. If this ordering is valid, it is returned
+    /// right away, without calling the solver or the progress callback. The modifiers applied while
+    /// checking the given order are counted as tree states (see
+    /// [`StrategyTRTA::num_tree_states`]). By default, the given order is tried first.
+    pub fn set_try_given_order(&mut self, try_given_order: bool) {
+        self.try_given_order = try_given_order;
+    }
+
+    /// Set whether `work` returns the best partial ordering once the time budget is used up.
+    /// If enabled, `Error::PartialSolution` is returned instead of `Error::Timeout`. It contains
+    /// the longest sequence of modifiers that could be applied without violating the hard
+    /// policies. The remaining modifiers are missing. If no group could be applied at all,
+    /// `Error::Timeout` is still returned. By default, this is disabled.
+    pub fn set_return_best_on_timeout(&mut self, return_best_on_timeout: bool) {
+        self.return_best_on_timeout = return_best_on_timeout;
+    }
+
+    /// Search for the cheapest ordering according to `cost`, instead of returning the first valid
+    /// one. `work` then collects up to `max_orderings` distinct valid orderings (see
+    /// [`StrategyTRTA::work_all`]), or as many as can be found within the time budget, and returns
+    /// the one with the lowest [`total_cost`]. If multiple orderings have the same cost, the one
+    /// found first is returned.
+    pub fn set_ordering_cost(&mut self, cost: Box<dyn OrderingCost>, max_orderings: usize) {
+        self.ordering_cost = Some((cost, max_orderings.max(1)));
+    }
 
-            // if let StackAction::Reset = action.clone() {
-            //     // 构造新的 StackFrame
-            //     let stack_frame = StackFrame {
-            //         idx: 0,
-            //         rem_groups: indices, // 这里 indices 在上面已经获取
-            //         num_undo: 0,
-            //     };
-            //     stack.push(stack_frame);
-            // }
+    /// Search for the ordering with the fewest scheduling steps, instead of returning the first
+    /// valid one. Since groups may be merged during the exploration, orderings found later may
+    /// consist of fewer groups. `work` then collects up to `max_orderings` distinct valid orderings
+    /// (see [`StrategyTRTA::work_all`]), or as many as can be found within the time budget, and
+    /// returns the one with the fewest groups (see [`TrtaStats::found_steps`]). If multiple
+    /// orderings have the same number of groups, the one found first is returned. Pass `None` to
+    /// return the first valid ordering again. If an ordering cost is set (see
+    /// [`StrategyTRTA::set_ordering_cost`]), it takes precedence.
+    pub fn set_prefer_fewest_steps(&mut self, max_orderings: Option<usize>) {
+        self.fewest_steps = max_orderings.map(|max_orderings| max_orderings.max(1));
+    }
+
+    /// Set precedence constraints between the groups, independent of the hard policies. Each pair
+    /// `(i, j)` requires group `i` to be applied before group `j`, where the indices refer to the
+    /// current groups (i.e., to the modifiers passed to `new`, or to the groups passed to
+    /// `new_with_groups`). The constraints are added to the formula passed to the solver, and
+    /// orderings violating them are skipped during the exploration. They are kept if groups are
+    /// merged later on. If any index is out of bounds, or if the constraints contain a cycle,
+    /// `Error::InvalidInput` is returned, and the previous constraints are kept.
+    pub fn set_precedence(&mut self, precedence: Vec<(usize, usize)>) -> Result<(), Error> {
+        if let Some((i, j)) =
+            precedence.iter().find(|(i, j)| *i >= self.groups.len() || *j >= self.groups.len())
+        {
+            return Err(Error::InvalidInput(format!("Invalid precedence ({}, {})", i, j)));
         }
+        if is_cyclic_directed(&DiGraphMap::<usize, ()>::from_edges(precedence.iter())) {
+            return Err(Error::InvalidInput("The precedence constraints are cyclic".to_string()));
+        }
+        // store the first modifier of each group, such that the constraints are not affected by
+        // changes to the group indices.
+        self.precedence = precedence
+            .into_iter()
+            .map(|(i, j)| (self.groups[i][0].clone(), self.groups[j][0].clone()))
+            .collect();
+        Ok(())
     }
 
-    #[cfg(feature = "count-states")]
-    fn num_states(&self) -> usize {
-        self.num_states
+    /// Returns the precedence constraints (see [`StrategyTRTA::set_precedence`]) as pairs of
+    /// indices of the current groups. Constraints between modifiers of the same group are omitted.
+    fn group_precedence(&self) -> Vec<(usize, usize)> {
+        let group_of = |m: &ConfigModifier| self.groups.iter().position(|g| g.contains(m));
+        self.precedence
+            .iter()
+            .filter_map(|(a, b)| match (group_of(a), group_of(b)) {
+                (Some(i), Some(j)) if i != j => Some((i, j)),
+                _ => None,
+            })
+            .collect()
     }
-}
 
-impl StrategyTRTA {
-    /// Check all remaining possible choices at the current position in the stack. The first option,
-    /// that works is returned (with `Ok(idx)`). However, if none of them seem to work, then one of
-    /// the checked and failed groups is returned at random, which should be used to find a
-    /// dependency group. The returned index corresponds to the position in `frame.rem_groups`!
+    /// Returns `true` if the error is in the set of solver-worthy errors.
+    fn is_solver_worthy(&self, error: &NetworkError) -> bool {
+        SolverErrorKind::of(error).map(|k| self.solver_errors.contains(&k)).unwrap_or(false)
+    }
+
+    /// Apply the ordering to a fresh copy of the initial network and hard policies, checking the
+    /// hard policies on the initial state, after every step, and on the final state (see
+    /// [`verify_ordering`](crate::verify_ordering)). In contrast to the incremental checks during
+    /// the exploration, nothing is reused. If the ordering is not valid, the discrepancy is
+    /// returned as a string.
+    fn verify_ordering(&self, ordering: &[ConfigModifier]) -> Result<(), String> {
+        crate::verify_ordering(&self.net, ordering, &self.hard_policy).map_err(|e| e.to_string())
+    }
+
+    /// Search for the smallest set of modifiers that makes the given `condition` pass, when applied
+    /// on the initial network. Subsets are tried in increasing size, and each subset is applied in
+    /// the order in which the modifiers appear in the groups. If the condition already holds in the
+    /// initial state, then the empty set is returned. If no subset of modifiers makes the condition
+    /// pass, then `None` is returned.
     ///
-    /// In the OK case, the network and the hard policy will remain in the state of the modification
-    /// of which the index is returned
-    fn get_next_option(
-        &mut self,
-        net: &mut Network,
-        hard_policy: &mut HardPolicy,
-        frame: &StackFrame,
-    ) -> Result<usize, NetworkError> {
-        assert!(frame.idx < frame.rem_groups.len());
-        for group_pos in frame.idx..frame.rem_groups.len() {
-            let group_idx = *frame.rem_groups.get(group_pos).unwrap();
-            // println!("Done updates: {:?}", group_idx);
-
-            // perform the modification group
-            let mut mod_ok: bool = true;
-            let mut num_undo: usize = 0;
-            let mut num_undo_policy: usize = 0;
-            let mut blackhole_error_node_indices: Option<Vec<RouterId>> = None;
-            let mut forwarding_loop_error_node_indices: Option<Vec<Vec<RouterId>>> = None;
-            'apply_group: for modifier in self.groups[group_idx].iter() {
-                #[cfg(feature = "count-states")]
-                {
-                    self.num_states += 1;
-                }
-                num_undo += 1;
-                if net.apply_modifier(modifier).is_ok() {
-                    num_undo_policy += 1;
-                    let mut fw_state = net.get_forwarding_state();
-                    // hard_policy.step(net, &mut fw_state).expect("cannot check policies!");
-                    // println!("--------------1---------------!");
-                    let result = hard_policy.step(net, &mut fw_state);
-                    // println!("--------------2---------------!");
-                    match result {
-                        Ok(_) => {} //println!("Policies checked successfully!"),
-                        Err(e) => {
-                            println!("Error checking policies: {:?}", e);
-                            match e {
-                                NetworkError::ForwardingBlackHole(node_indices) => {
-                                    println!(
-                                        "Extracted NodeIndices from BlackHole: {:?}",
-                                        node_indices
-                                    );
-                                    blackhole_error_node_indices = Some(node_indices.clone());
-                                }
-                                NetworkError::ForwardingLoops(path) => {
-                                    println!("Extracted Path from ForwardingLoop: {:?}", path);
-                                    forwarding_loop_error_node_indices = Some(path.clone());
-                                    // 如果逻辑需要也存储路径
-                                }
-                                // 可以处理其他错误类型
-                                _ => {
-                                    println!("Unhandled Error Type: {:?}", e);
-                                }
-                            }
-                        }
+    /// **Warning**: This function explores all $2^n$ subsets in the worst case!
+    pub fn minimal_fix_for(&self, condition: &Condition) -> Option<Vec<ConfigModifier>> {
+        let modifiers: Vec<&ConfigModifier> = self.groups.iter().flatten().collect();
+        (0..=modifiers.len()).find_map(|size| {
+            modifiers.iter().combinations(size).find_map(|subset| {
+                let mut net = self.net.clone();
+                for modifier in subset.iter() {
+                    if net.apply_modifier(modifier).is_err() {
+                        return None;
                     }
-                    //如果不满足性质
-                    if !hard_policy.check() {
-                        mod_ok = false;
-                        break 'apply_group;
-                    }
-                    //如果不能做这些更新
-                } else {
-                    mod_ok = false;
-                    break 'apply_group;
-                }
-            }
-            // println!("mod_ok:{}", mod_ok);
-            // check if the modifier is ok
-            if mod_ok {
-                // everything fine, return the index
-                return Ok(group_pos);
-            } else {
-                // undo the hard policy and the network
-                (0..num_undo_policy).for_each(|_| hard_policy.undo());
-                (0..num_undo).for_each(|_| {
-                    net.undo_action().expect("Cannot perform undo!");
-                });
-                if let Some(node_indices) = blackhole_error_node_indices {
-                    return Err(NetworkError::ForwardingBlackHole(node_indices));
-                    // 直接解构 Some 并返回内部的 Vec
                 }
-                if let Some(path) = forwarding_loop_error_node_indices {
-                    return Err(NetworkError::ForwardingLoops(path)); // 直接解构 Some 并返回内部的 Vec
+                match condition.check(&mut net.get_forwarding_state()) {
+                    Ok(()) => Some(subset.into_iter().map(|m| (*m).clone()).collect()),
+                    Err(_) => None,
                 }
+            })
+        })
+    }
+
+    /// Seed the groups with dependencies learned during previous, structurally similar changes. Each
+    /// [`RoleDependency`] is instantiated with the router that plays each role in this change (see
+    /// [`RoleDependency::instantiate`]). If all modifiers of the instantiated dependency are part of
+    /// this change, and each of them is still its own group, then they are merged into a single
+    /// group with the ordering of the dependency. Dependencies that cannot be instantiated or that
+    /// do not match this change are ignored. Returns the number of dependencies that were added.
+    pub fn add_role_dependencies(
+        &mut self,
+        dependencies: &[RoleDependency],
+        routers: &HashMap<String, RouterId>,
+    ) -> usize {
+        let mut num_added = 0;
+        for dependency in dependencies {
+            let ordering = match dependency.instantiate(routers) {
+                Some(ordering) => ordering,
+                None => continue,
+            };
+            let sub_groups = match ordering
+                .iter()
+                .map(|m| self.groups.iter().position(|g| g.len() == 1 && &g[0] == m))
+                .collect::<Option<Vec<usize>>>()
+            {
+                Some(sub_groups) => sub_groups,
+                None => continue,
+            };
+            if sub_groups.iter().unique().count() != sub_groups.len() {
+                continue;
             }
+            info!("Add a dependency from the warm pool with {} modifiers", ordering.len());
+            utils::add_minimal_ordering_as_new_gorup(&mut self.groups, sub_groups, Some(ordering));
+            num_added += 1;
         }
-        Err(NetworkError::NoConvergence)
-        // if we reach this position, we know that every possible option is bad!
-        //Err(self.rng.gen_range(frame.idx, frame.rem_groups.len()))
+        num_added
     }
 
-    /// This function tries to find a dependency based on the current position. The arguments
-    /// are as follows:
-    ///
-    /// - `net`: Network at state of the good ordering. After returning, the net will have the exact
-    ///   same state as before.
-    /// - `hard_policy`: Hard Policy at state of the good ordering. After returning, the hard policy
-    ///   will have the exact same state as before.
-    /// - `good_ordering`: Ordering of groups, which work up to the point of the bad group
-    /// - `bad_group`: Index of the bad group which causes the problme. This function will search a
-    ///    dependency to solve this bad group.
-    ///
-    /// If a dependency was found successfully, then this function will return the new dependency
-    /// (first argument), along with the set of groups that are part of this new dependency (second
-    /// argument). If no dependency group could be found, then `None` is returned.
-    fn find_dependency(
+    /// Returns the indices of all groups, which contain at least one modifier that touches the
+    /// given router (see [`ConfigModifier::routers`]).
+    pub fn modifiers_touching(&self, router: RouterId) -> Vec<usize> {
+        self.groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.iter().any(|m| m.routers().contains(&router)))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Pass the formula to the solver, and record the query in the statistics, the transcript and
+    /// the formula dump.
+    #[cfg(feature = "ltl-solver")]
+    fn query_solver(
         &mut self,
-        net: &mut Network,            // 引用网络对象，表示当前的网络状态
-        hard_policy: &mut HardPolicy, // 引用硬性策略对象，表示当前的策略状态
-        good_ordering: &[usize],      // 一个引用，代表“良好”的组序列（即没有引发错误的顺序）
-        bad_group: usize,             // 一个引发问题的组索引，表示需要修复的“坏组”
-        abort: Stopper,               // 停止标志，用于中止操作的信号
-    ) -> Option<(Vec<ConfigModifier>, Vec<usize>)> {
-        // apply the modifier to the network to get the errors
-        let mut num_undo = 0;
-        let mut num_undo_policy = 0;
-        let mut errors = None;
-        'apply_group: for modifier in self.groups[bad_group].iter() {
-            num_undo += 1;
-            if net.apply_modifier(modifier).is_ok() {
-                //应用更新
-                num_undo_policy += 1;
-                let mut fw_state = net.get_forwarding_state(); //获取网络的转发状态
-                hard_policy.step(net, &mut fw_state).expect("cannot check policies!");
-                //发现执行以下四行无log，应该是没有进入这个函数
-                // println!("Policies checked successfully.");
-                // match hard_policy.step(net, &mut fw_state) {
-                //     Ok(_) => println!("Policies checked successfully."),
-                //     Err(e) => println!("Failed to check policies: {:?}", e),
-                // }
+        formula: &str,
+        abort: &Stopper,
+    ) -> Result<Option<Vec<usize>>, Error> {
+        if let Some(max_calls) = self.max_solver_calls {
+            if self.stats.solver_calls >= max_calls {
+                info!("Reached the limit of {} solver calls!", max_calls);
+                return Err(Error::SolverCallLimitExceeded(max_calls));
+            }
+        }
+        let solver_start = Instant::now();
+        self.stats.solver_calls += 1;
+        let result = self.solver.check_with_limits(formula, self.solver_deadline(), Some(abort));
+        self.stats.solver_time += solver_start.elapsed();
+        let result = result?;
+        self.record_exchange(formula, &result);
+        self.dump_exchange(formula, &result);
+        Ok(result)
+    }
 
-                if !hard_policy.check() {
-                    errors = Some(hard_policy.get_watch_errors());
-                    break 'apply_group;
+    /// Record the query and the result of a solver call in the transcript (if enabled).
+    fn record_exchange(&mut self, formula: &str, result: &Option<Vec<usize>>) {
+        if let Some((transcript, max_len)) = self.transcript.as_mut() {
+            let mut query = formula.to_string();
+            if let Some(max_len) = max_len {
+                // make sure not to split a character
+                let mut len = (*max_len).min(query.len());
+                while !query.is_char_boundary(len) {
+                    len -= 1;
                 }
-            } else {
-                errors = Some((Vec::new(), vec![Some(PolicyError::NoConvergence)]));
-                break 'apply_group;
+                query.truncate(len);
             }
+            transcript.push(SolverExchange { query, result: result.clone() });
         }
+    }
 
-        // undo the hard policy and the network
-        (0..num_undo_policy).for_each(|_| hard_policy.undo());
-        (0..num_undo).for_each(|_| {
-            net.undo_action().expect("Cannot perform undo!");
-        });
+    /// Append the query and the result of a solver call to the formula dump (if enabled). Failing
+    /// to write the dump does not interrupt the exploration.
+    fn dump_exchange(&self, formula: &str, result: &Option<Vec<usize>>) {
+        if let Some(path) = self.formula_dump.as_ref() {
+            let result = match result {
+                Some(ordering) => format!("sat {:?}", ordering),
+                None => "unsat".to_string(),
+            };
+            let entry = format!(
+                "# query {}\n{}\n# result {}: {}\n",
+                self.stats.solver_calls,
+                formula.trim_end(),
+                self.stats.solver_calls,
+                result
+            );
+            if let Err(e) = OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(entry.as_bytes()))
+            {
+                warn!("Cannot write to the formula dump {}: {}", path.display(), e);
+            }
+        }
+    }
 
-        match errors {
-            // 如果有错误发生，尝试寻找依赖组来解决这个“坏组”
-            Some(errors) => {
-                // 将良好排序加上坏组，形成新的组顺序
+    /// Explore the orderings of the groups until `limit` distinct valid orderings are stored in
+    /// `found`, in which case `Ok(())` is returned. Otherwise, the error why the exploration stopped
+    /// is returned, and `found` contains all valid orderings found so far. If the time budget is
+    /// used up and `return_best_on_timeout` is set, the longest valid prefix is returned as
+    /// `Error::PartialSolution`.
+    fn explore(
+        &mut self,
+        abort: Stopper,
+        limit: usize,
+        found: &mut Vec<Vec<ConfigModifier>>,
+    ) -> Result<(), Error> {
+        let mut best_partial = vec![];
+        match self.explore_tree(abort, limit, found, &mut best_partial) {
+            Err(Error::Timeout) if self.return_best_on_timeout && !best_partial.is_empty() => {
+                info!("Returning the best partial ordering of {} steps", best_partial.len());
+                Err(Error::PartialSolution(best_partial))
+            }
+            result => result,
+        }
+    }
 
-                let ordering = good_ordering
-                    .iter()
-                    .cloned() // 克隆良好排序的每个元素
-                    .chain(std::iter::once(bad_group)) // 将坏组加到顺序末尾
-                    .collect::<Vec<usize>>();
-                utils::find_dependency::<PushBackTreeStrategy<RandomOrdering>>(
+    /// Body of [`StrategyTRTA::explore`]. If `return_best_on_timeout` is set, `best_partial` is
+    /// kept up to date with the longest sequence of modifiers, which could be applied so far.
+    fn explore_tree(
+        &mut self,
+        mut abort: Stopper,
+        limit: usize,
+        found: &mut Vec<Vec<ConfigModifier>>,
+        best_partial: &mut Vec<ConfigModifier>,
+    ) -> Result<(), Error> {
+        // setup the permutator over the group indices, starting with a randomized order
+        let mut permutator = P::new(self.initial_order());
+        let mut current_sequence: Vec<usize> = vec![];
+        // state of the network and the hard policy before applying every group of the current
+        // sequence
+        let mut snapshots: Vec<Snapshot> = vec![];
+        // groups already tried at every position of the current sequence (and at the next one)
+        let mut explored: Vec<Vec<usize>> = vec![vec![]];
+        // groups which failed at the next position since the last one that could be applied there
+        let mut failures: Vec<Failure> = vec![];
+
+        // clone the network and the hard policies to work with them for the tree exploration
+        let mut net = self.net.clone();
+        let mut hard_policy = self.hard_policy.clone();
+
+        // indices of the groups touching each router, to map conflicts to the LTL variables
+        #[cfg(feature = "ltl-solver")]
+        let mut router_groups = ltl::router_groups(&self.groups);
+
+        // number of times each pair (fix, doing) was learned, to merge recurring dependencies
+        #[cfg(feature = "ltl-solver")]
+        let mut co_constraints: HashMap<(usize, usize), usize> = HashMap::new();
+
+        // The goal is to build the formula passed to aaltaf. The constraint that exactly one group
+        // is applied in every state is stored only once, and only the learned constraints are
+        // accumulated.
+        // precedence constraints between the current groups
+        let mut precedence = self.group_precedence();
+
+        #[cfg_attr(not(feature = "ltl-solver"), allow(unused_mut))]
+        let mut formula = self.learned_formula(&precedence);
+
+        if let Some((transcript, _)) = self.transcript.as_mut() {
+            transcript.clear();
+        }
+        self.stats = TrtaStats::default();
+        if let Some(path) = self.formula_dump.as_ref() {
+            if let Err(e) = fs::write(path, "") {
+                warn!("Cannot create the formula dump {}: {}", path.display(), e);
+            }
+        }
+
+        let mut growth_watch = self
+            .formula_growth_limit
+            .map(|(max_formula_len, max_resets)| GrowthWatch::new(max_formula_len, max_resets));
+        let mut progress_watch = self.stall_limit.map(ProgressWatch::new);
+
+        // fast path: often, the modifiers already work in the order in which they were given.
+        if self.try_given_order && precedence.iter().all(|(i, j)| i < j) {
+            let identity: Vec<usize> = (0..self.groups.len()).collect();
+            let ordering = utils::finalize_ordering(&self.groups, &identity);
+            let result = crate::verify_ordering(&self.net, &ordering, &self.hard_policy);
+            #[cfg(feature = "count-states")]
+            {
+                // the modifiers applied while checking the given order are tree states as well
+                self.num_tree_states += match &result {
+                    Ok(()) => ordering.len(),
+                    Err(crate::VerifyError::Network(Some(i), _))
+                    | Err(crate::VerifyError::Violation(i, _)) => i + 1,
+                    Err(_) => 0,
+                };
+            }
+            if result.is_ok() {
+                info!("The given ordering is already valid!");
+                found.push(ordering);
+                self.stats.found_steps.push(self.groups.len());
+                if found.len() >= limit {
+                    return Ok(());
+                }
+            }
+        }
+
+        loop {
+            // check for iter overflow, i.e., whether the time budget is used up
+            if self.stop_time.as_ref().map(|time| time.elapsed().is_ok()).unwrap_or(false) {
+                // time budget is used up!
+                error!("Time budget is used up! No solution was found yet!");
+                return Err(Error::Timeout);
+            }
+
+            // check for abort criteria
+            if abort.try_is_stop().unwrap_or(false) {
+                info!("Operation was aborted!");
+                return Err(Error::Abort);
+            }
+
+            // get the next ordering to explore
+            let ordering = match permutator.next() {
+                Some(ordering) => ordering.as_patches(),
+                None => {
+                    error!("Could not find any valid ordering!");
+                    return Err(Error::ProbablyNoSafeOrdering);
+                }
+            };
+
+            // the ordering only needs to be checked after the prefix it shares with the current
+            // sequence.
+            let common =
+                current_sequence.iter().zip(ordering.iter()).take_while(|(a, b)| a == b).count();
+            if common < current_sequence.len() {
+                // backtrack, by undoing the net, the hard policy and the current sequence
+                self.stats.pops += 1;
+                snapshots[common].restore(&mut net, &mut hard_policy)?;
+                snapshots.truncate(common);
+                current_sequence.truncate(common);
+                explored.truncate(common + 1);
+                failures.clear();
+                // the hard policy must hold exactly one state for every applied modifier
+                debug_assert_eq!(
+                    hard_policy.num_steps(),
+                    self.hard_policy.num_steps() + num_modifiers(&self.groups, &current_sequence)
+                );
+            }
+
+            // apply the remaining groups of the ordering, until one of them fails
+            let mut stuck = false;
+            for &group in ordering[common..].iter() {
+                let pos = current_sequence.len();
+                if !explored[pos].contains(&group) {
+                    explored[pos].push(group);
+                }
+                if precedence.iter().any(|(i, j)| *j == group && !current_sequence.contains(i)) {
+                    // skip all orderings applying this group before one of its predecessors
+                    permutator.fail_pos(pos);
+                    stuck = pos + explored[pos].len() == self.groups.len();
+                    break;
+                }
+                let snapshot = Snapshot::take(&net, &hard_policy);
+                match self.apply_group(&mut net, &mut hard_policy, group)? {
+                    Ok(()) => {
+                        // There exists a valid next step! Update the current sequence
+                        current_sequence.push(group);
+                        snapshots.push(snapshot);
+                        explored.push(vec![]);
+                        failures.clear();
+                        if let Some(callback) = self.on_progress.as_mut() {
+                            callback(&current_sequence);
+                        }
+                        if self.return_best_on_timeout
+                            && num_modifiers(&self.groups, &current_sequence) > best_partial.len()
+                        {
+                            *best_partial =
+                                utils::finalize_ordering(&self.groups, &current_sequence);
+                        }
+                        // check if all groups have been added to the sequence
+                        if current_sequence.len() == self.groups.len() {
+                            // We are done! found a valid solution!
+                            info!(
+                                "Valid solution was found! Learned {} groups",
+                                self.learned_groups().len()
+                            );
+                            let ordering =
+                                utils::finalize_ordering(&self.groups, &current_sequence);
+                            // re-check the entire ordering, to guard against drift of the
+                            // incremental checks.
+                            self.verify_ordering(&ordering).map_err(Error::Internal)?;
+                            if !found.contains(&ordering) {
+                                found.push(ordering);
+                                self.stats.found_steps.push(self.groups.len());
+                            }
+                            if found.len() >= limit {
+                                return Ok(());
+                            }
+                            // continue with the next ordering. The solver must not return this
+                            // sequence again.
+                            #[cfg(feature = "ltl-solver")]
+                            formula.learn(ltl::block_sequence_formula(&current_sequence));
+                            break;
+                        }
+                        self.stats.pushes += 1;
+                    }
+                    Err(failure) => {
+                        // skip all orderings starting with the current sequence and this group
+                        permutator.fail_pos(pos);
+                        // We are stuck at this position, if the failure involves specific
+                        // routers, or if every group was tried. In the best-first mode, all
+                        // groups are tried first.
+                        let exhausted = pos + explored[pos].len() == self.groups.len();
+                        let involved = failure.error.is_some();
+                        // once the stack reaches the maximum depth, the alternatives at this
+                        // position are no longer explored.
+                        let too_deep = self.max_depth.map(|d| pos >= d).unwrap_or(false);
+                        failures.push(failure);
+                        stuck = exhausted || too_deep || (involved && !self.best_first);
+                        break;
+                    }
+                }
+            }
+            // force a reset with a random order, if the exploration does not get any further. The
+            // next ordering backtracks to the prefix it shares with the current sequence.
+            if progress_watch
+                .as_mut()
+                .map(|w| w.is_stalled(current_sequence.len()))
+                .unwrap_or(false)
+            {
+                info!(
+                    "No progress since {} iterations, reset the exploration!",
+                    self.stall_limit.unwrap()
+                );
+                self.stats.forced_resets += 1;
+                let weights = self.group_weights();
+                permutator =
+                    P::new(weighted_order(&mut self.rng, self.groups.len(), weights.as_deref()));
+                explored = vec![vec![]; current_sequence.len() + 1];
+                failures.clear();
+                continue;
+            }
+            if !stuck {
+                continue;
+            }
+
+            // keep the formula and the exploration stack within the memory budget
+            let stack = explored.iter().map(|e| e.len()).sum::<usize>()
+                + current_sequence.len()
+                + snapshots.len();
+            self.check_memory_budget(&mut formula, stack * std::mem::size_of::<usize>())?;
+
+            // We are stuck at the position after the current sequence. Pick the group to resolve
+            // according to the conflict selection, or the least bad one in the best-first mode.
+            let pos = current_sequence.len();
+            if failures.is_empty() {
+                // all remaining groups were skipped due to the precedence constraints.
+                if pos == 0 {
+                    error!("Could not find any valid ordering!");
+                    return Err(Error::ProbablyNoSafeOrdering);
+                }
+                permutator.fail_pos(pos - 1);
+                continue;
+            }
+            let bad_idx = if self.best_first {
+                failures.iter().enumerate().min_by_key(|(_, f)| f.num_violations).unwrap().0
+            } else {
+                self.select_failure(&failures)
+            };
+            let failed: Vec<usize> = failures.iter().map(|f| f.group).collect();
+            let doing = failed[bad_idx];
+            let e = failures[bad_idx].error.take().unwrap_or(NetworkError::NoConvergence);
+            if self.best_first {
+                debug!(
+                    "Least bad group {} with {} violations",
+                    doing, failures[bad_idx].num_violations
+                );
+            }
+
+            let action: ExplorationAction = match e {
+                #[cfg(feature = "ltl-solver")]
+                e if !self.is_solver_worthy(&e) => {
+                    // This error is cheap to resolve by backtracking. Skip the remaining groups at
+                    // this position, without calling the solver.
+                    info!("Skip the solver for error: {}", e);
+                    ExplorationAction::Backtrack
+                }
+                #[cfg(not(feature = "ltl-solver"))]
+                e => {
+                    debug!("Search a dependency for the error: {:?}", e);
+                    #[cfg(feature = "count-states")]
+                    {
+                        self.seen_difficult_dependency = true;
+                    }
+                    // There exists no option, that we can take, which would lead to a good result!
+                    // Pick one of the failed groups as the bad group.
+                    let bad_group = if self.shuffle {
+                        failed[self.rng.gen_range(0, failed.len())]
+                    } else {
+                        doing
+                    };
+                    // What we do here is try to find a dependency!
+                    match self.find_dependency(
+                        &mut net,
+                        &mut hard_policy,
+                        &current_sequence,
+                        bad_group,
+                        abort.clone(),
+                    )? {
+                        Some((new_group, old_groups)) => {
+                            info!("Found a new dependency group!");
+                            // add the new ordering to the known groups
+                            utils::add_minimal_ordering_as_new_gorup(
+                                &mut self.groups,
+                                old_groups,
+                                Some(new_group),
+                            );
+                            precedence = self.group_precedence();
+                            // reset the exploration
+                            ExplorationAction::Reset(Vec::new())
+                        }
+                        None => {
+                            // No dependency group could be found! Continue exploring the search
+                            // space
+                            info!("Could not find a new dependency group!");
+                            ExplorationAction::Backtrack
+                        }
+                    }
+                }
+                #[cfg(feature = "ltl-solver")]
+                e => {
+                    debug!("Learn a constraint from the error: {:?}", e);
+                    let no_convergence = matches!(e, NetworkError::NoConvergence);
+                    // neither the groups already applied, nor those already applied successfully at this
+                    // position can fix the problem
+                    let mut skip = current_sequence.clone();
+                    skip.extend(explored[pos].iter().filter(|group| !failed.contains(group)));
+
+                    // the groups which may fix each problem. They can be expanded if the formula
+                    // turns out to be unsatisfiable.
+                    let mut fixes = ltl::conflict_fixes(&e, &router_groups, doing, &skip);
+                    trace!("Groups fixing the problems: {:?}", fixes);
+                    let formulas = fixes
+                        .iter()
+                        .filter_map(|fixes| ltl::blackhole_constraint(fixes, doing, &skip))
+                        .collect::<Vec<_>>();
+
+                    // If `formulas` is empty, then either none of the remaining groups changes a session that
+                    // can fix the problem, or no group remains.
+                    let combined_formula = if no_convergence {
+                        // None of the remaining groups can be applied directly after the current
+                        // sequence (they cannot be applied, the network does not converge, or the
+                        // policies are violated without any specific router). Forbid all of them
+                        // at this position.
+                        failed
+                            .iter()
+                            .map(|group| ltl::block_prefix_formula(&current_sequence, *group))
+                            .join(" & ")
+                    } else if formulas.is_empty() {
+                        ltl::block_prefix_formula(&current_sequence, doing)
+                    } else {
+                        formulas.join(" & ")
+                    };
+
+                    let record = ConflictRecord {
+                        prefix: current_sequence.clone(),
+                        doing,
+                        routers: routers_from_error(&e),
+                        matched_groups: fixes.clone(),
+                        clause: combined_formula.clone(),
+                    };
+                    trace!("Conflict: {:?}", record);
+                    self.stats.conflicts.push(record);
+
+                    // a group, which is the only fix for a problem, must be applied before
+                    // `doing`. If this was learned repeatedly, both are merged into a single group.
+                    let mut dependency = None;
+                    for fix in fixes.iter().filter_map(|fixes| match fixes.as_slice() {
+                        [fix] => Some(*fix),
+                        _ => None,
+                    }) {
+                        let count = co_constraints.entry((fix, doing)).or_insert(0);
+                        *count += 1;
+                        if self.merge_threshold.map(|t| *count >= t).unwrap_or(false) {
+                            dependency = Some(fix);
+                            break;
+                        }
+                    }
+
+                    if let Some(fix) = dependency {
+                        info!("Merge the groups {} and {} into a new group!", fix, doing);
+                        utils::add_minimal_ordering_as_new_gorup(
+                            &mut self.groups,
+                            vec![fix, doing],
+                            None,
+                        );
+                        // the group indices have changed, and the learned constraints refer to
+                        // the old ones. Start over with the new groups.
+                        router_groups = ltl::router_groups(&self.groups);
+                        precedence = self.group_precedence();
+                        formula = self.learned_formula(&precedence);
+                        co_constraints.clear();
+                        ExplorationAction::Reset(Vec::new())
+                    } else {
+                        formula.learn(combined_formula);
+                        let mut expansions = 0;
+                        let mut ordering = loop {
+                            if let Some(ordering) = self.query_solver(&formula.query(), &abort)? {
+                                break ordering;
+                            }
+                            // The solver proved that no ordering satisfies the learned
+                            // constraints. Resetting the exploration would only repeat the same
+                            // conflicts. Instead, expand the last conflict by allowing one more
+                            // group (which is not yet part of it) to fix the problem. Errors while
+                            // parsing the solver output are returned above instead.
+                            let candidate = if fixes.is_empty() || expansions >= self.max_expansions
+                            {
+                                None
+                            } else {
+                                (0..self.groups.len()).find(|group| {
+                                    *group != doing
+                                        && !skip.contains(group)
+                                        && fixes.iter().all(|fixes| !fixes.contains(group))
+                                })
+                            };
+                            match candidate {
+                                Some(group) => {
+                                    info!("Expand the conflict of group {} with {}", doing, group);
+                                    fixes.iter_mut().for_each(|fixes| fixes.push(group));
+                                    formula.replace_last(
+                                        fixes
+                                            .iter()
+                                            .filter_map(|fixes| {
+                                                ltl::blackhole_constraint(fixes, doing, &skip)
+                                            })
+                                            .join(" & "),
+                                    );
+                                    expansions += 1;
+                                    self.stats.expansions += 1;
+                                }
+                                None => {
+                                    error!("The learned LTL formula is unsatisfiable!");
+                                    return Err(Error::ProbablyNoSafeOrdering);
+                                }
+                            }
+                        };
+                        validate_ordering(&ordering, self.groups.len())?;
+                        // let the user-defined validator veto the solver ordering
+                        if !ordering.is_empty() && !self.accept_solver_ordering(&ordering) {
+                            formula.learn(ltl::block_sequence_formula(&ordering));
+                            ordering.clear();
+                        }
+                        // check the solver ordering against the actual network
+                        if self.cross_validate && !ordering.is_empty() {
+                            if let Some(pos) = self.first_invalid_step(&ordering)? {
+                                info!("Solver ordering {:?} fails at step {}!", ordering, pos);
+                                self.stats.rejected_solver_orderings += 1;
+                                formula.learn(ltl::block_prefix_formula(
+                                    &ordering[..pos],
+                                    ordering[pos],
+                                ));
+                                ordering.clear();
+                            }
+                        }
+                        ExplorationAction::Reset(ordering)
+                    }
+                }
+            };
+
+            match action {
+                ExplorationAction::Backtrack => {
+                    // skip the remaining groups at this position
+                    if pos == 0 {
+                        error!("Could not find any valid ordering!");
+                        return Err(Error::ProbablyNoSafeOrdering);
+                    }
+                    permutator.fail_pos(pos - 1);
+                }
+                ExplorationAction::Reset(ordering) => {
+                    self.stats.resets += 1;
+                    // check that the formula is not growing without any progress
+                    if let Some(watch) = growth_watch.as_mut() {
+                        if watch.is_stalled(formula.learned_len(), current_sequence.len()) {
+                            return Err(Error::NoProgress(
+                                watch.stalled_resets,
+                                formula.learned_len(),
+                            ));
+                        }
+                    }
+                    // reset the permutator for the new groups, as well as the sequence, the
+                    // network and the hard policies. If the solver returned an ordering, the
+                    // exploration starts with this ordering.
+                    permutator =
+                        P::new(if ordering.is_empty() { self.initial_order() } else { ordering });
+                    current_sequence.clear();
+                    explored = vec![vec![]];
+                    failures.clear();
+                    self.reset_network(&mut net, &mut hard_policy, snapshots.first().copied());
+                    snapshots.clear();
+                }
+            }
+        }
+    }
+
+    /// Restore the network and the hard policies to their initial state, by undoing them back to
+    /// the `initial` snapshot, which is much cheaper than cloning them. Only if this is not possible
+    /// (e.g., because the undo stack was cleared), they are cloned again (see
+    /// [`TrtaStats::cloned_resets`]). Without a snapshot, no group was applied since the initial
+    /// state.
+    fn reset_network(
+        &mut self,
+        net: &mut Network,
+        hard_policy: &mut HardPolicy,
+        initial: Option<Snapshot>,
+    ) {
+        let restored = match initial {
+            Some(initial) => initial.restore(net, hard_policy).is_ok(),
+            None => true,
+        };
+        if !restored {
+            warn!("Cannot undo the network, clone it instead!");
+            self.stats.cloned_resets += 1;
+            *net = self.net.clone();
+            *hard_policy = self.hard_policy.clone();
+        }
+    }
+
+    /// Create a new formula for the current groups without any learned constraints, keeping at most
+    /// the configured number of learned constraints (see
+    /// [`StrategyTRTA::set_max_learned_clauses`]).
+    fn learned_formula(&self, precedence: &[(usize, usize)]) -> ltl::LearnedFormula {
+        let mut formula = ltl::LearnedFormula::with_precedence(self.groups.len(), precedence);
+        formula.set_max_learned(self.max_learned_clauses);
+        formula
+    }
+
+    /// Check the memory budget (see [`StrategyTRTA::set_memory_budget`]), given the size of the
+    /// exploration stack in bytes. If allowed, the oldest learned constraints are dropped until the
+    /// estimate fits into the budget.
+    fn check_memory_budget(
+        &self,
+        formula: &mut ltl::LearnedFormula,
+        stack: usize,
+    ) -> Result<(), Error> {
+        let (budget, drop_learned) = match self.memory_budget {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let mut num_dropped = 0;
+        while formula.query_len() + stack > budget {
+            if !drop_learned || !formula.forget_oldest() {
+                error!("Memory budget of {} bytes exceeded!", budget);
+                return Err(Error::MemoryBudgetExceeded(formula.query_len() + stack));
+            }
+            num_dropped += 1;
+        }
+        if num_dropped > 0 {
+            warn!("Dropped {} learned constraints to stay within the memory budget", num_dropped);
+        }
+        Ok(())
+    }
+
+    /// Returns the order of all groups, with which the permutator is created. If the exploration
+    /// is shuffled, the order is random (see [`StrategyTRTA::set_conflict_weights`]).
+    fn initial_order(&mut self) -> Vec<usize> {
+        if self.shuffle {
+            let weights = self.group_weights();
+            weighted_order(&mut self.rng, self.groups.len(), weights.as_deref())
+        } else {
+            (0..self.groups.len()).collect()
+        }
+    }
+
+    /// Returns the weight of each group for the random order, if enabled. The weight of a group
+    /// is `1 / (1 + n)`, where `n` is the number of conflicts in which it could not be applied.
+    fn group_weights(&self) -> Option<Vec<f64>> {
+        if !self.conflict_weights {
+            return None;
+        }
+        let mut counts = vec![0usize; self.groups.len()];
+        for record in self.stats.conflicts.iter() {
+            counts[record.doing] += 1;
+        }
+        Some(counts.into_iter().map(|n| 1.0 / (1 + n) as f64).collect())
+    }
+
+    /// Returns the time at which the current solver call must be stopped, which is the earlier of the
+    /// solver timeout and the overall time budget.
+    fn solver_deadline(&self) -> Option<SystemTime> {
+        let timeout = self.solver_timeout.map(|dur| SystemTime::now() + dur);
+        match (timeout, self.stop_time) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Simulate the ordering of groups on a copy of the initial network and hard policies. Returns
+    /// the position of the first group that cannot be applied, or `None` if all groups can be
+    /// applied.
+    #[cfg(feature = "ltl-solver")]
+    fn first_invalid_step(&mut self, ordering: &[usize]) -> Result<Option<usize>, Error> {
+        let mut net = self.net.clone();
+        let mut hard_policy = self.hard_policy.clone();
+        for (pos, group) in ordering.iter().enumerate() {
+            if self.apply_group(&mut net, &mut hard_policy, *group)?.is_err() {
+                return Ok(Some(pos));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns `true` if the solver ordering is accepted by the validator (or if there is no
+    /// validator set).
+    fn accept_solver_ordering(&self, ordering: &[usize]) -> bool {
+        match self.solver_validator.as_ref() {
+            Some(validator) => {
+                let accepted = validator(ordering);
+                if !accepted {
+                    info!("Solver ordering {:?} was rejected by the validator!", ordering);
+                }
+                accepted
+            }
+            None => true,
+        }
+    }
+
+    /// Returns `true` if the number of policy violations must be computed for every failed group,
+    /// which is needed in the best-first mode, and to select the conflict with the most violations.
+    fn count_violations(&self) -> bool {
+        self.best_first || self.conflict_selection == ConflictSelection::MostViolations
+    }
+
+    /// Returns the index into `failures` of the group used to learn the conflict, according to the
+    /// conflict selection. Only groups whose failure involves specific routers are considered, or
+    /// all of them, if there is none. `failures` must not be empty.
+    fn select_failure(&mut self, failures: &[Failure]) -> usize {
+        let mut candidates: Vec<usize> =
+            (0..failures.len()).filter(|i| failures[*i].error.is_some()).collect();
+        if candidates.is_empty() {
+            candidates = (0..failures.len()).collect();
+        }
+        match self.conflict_selection {
+            ConflictSelection::FirstFail => candidates[0],
+            ConflictSelection::MostViolations => candidates
+                .iter()
+                .rev()
+                // violations are not counted if the group cannot be applied at all
+                .max_by_key(|i| match failures[**i].num_violations {
+                    usize::MAX => 0,
+                    n => n,
+                })
+                .copied()
+                .unwrap(),
+            ConflictSelection::Random => candidates[self.rng.gen_range(0, candidates.len())],
+        }
+    }
+
+    /// Apply the group `group_idx` to the network and the hard policy, and check the policies. If
+    /// the group can be applied, the network and the hard policy remain in the new state. Take a
+    /// [`Snapshot`] beforehand to revert the group later on. Otherwise,
+    /// both are reverted (or left untouched, see [`CandidateEvaluation`]), and the [`Failure`] is
+    /// returned. If the network cannot be reverted (or the group cannot be applied again after the
+    /// speculative evaluation), the network error is returned in the outer result.
+    fn apply_group(
+        &mut self,
+        net: &mut Network,
+        hard_policy: &mut HardPolicy,
+        group_idx: usize,
+    ) -> Result<Result<(), Failure>, Error> {
+        // perform the modification group
+        let mut mod_ok: bool = true;
+        // number of policy violations, if the group cannot be applied at all, it is the worst.
+        let mut num_violations: usize = usize::MAX;
+        // error involving specific routers, where black holes take precedence.
+        let mut router_error: Option<NetworkError> = None;
+        // copy of the network and the hard policy, on which the group is evaluated speculatively
+        let mut scratch = match self.candidate_evaluation {
+            CandidateEvaluation::Undo => None,
+            CandidateEvaluation::SpeculativeClone => Some((net.clone(), hard_policy.clone())),
+        };
+        let (eval_net, eval_policy) = match scratch.as_mut() {
+            Some((scratch_net, scratch_policy)) => (scratch_net, scratch_policy),
+            None => (&mut *net, &mut *hard_policy),
+        };
+        let snapshot = Snapshot::take(eval_net, eval_policy);
+        'apply_group: for modifier in self.groups[group_idx].iter() {
+            #[cfg(feature = "count-states")]
+            {
+                self.num_tree_states += 1;
+            }
+            if eval_net.apply_modifier(modifier).is_ok() {
+                // only recompute the routes affected by the modifier
+                let mut fw_state = match self.fw_state_cache.take() {
+                    Some(mut fw_state) => {
+                        fw_state.update(eval_net);
+                        fw_state
+                    }
+                    None => eval_net.get_forwarding_state(),
+                };
+                // hard_policy.step(net, &mut fw_state).expect("cannot check policies!");
+                let num_steps = eval_policy.num_steps();
+                let result = eval_policy.step(eval_net, &mut fw_state);
+                self.fw_state_cache = Some(fw_state);
+                // black holes and forwarding loops are reported after the state was recorded,
+                // other errors before.
+                let recorded = eval_policy.num_steps() > num_steps;
+                if let Err(e) = result {
+                    debug!("Error checking policies: {:?}", e);
+                    let routers = routers_from_error(&e);
+                    if routers.is_empty() {
+                        debug!("Unhandled Error Type: {:?}", e);
+                    } else if !matches!(router_error, Some(NetworkError::ForwardingBlackHole(_))) {
+                        trace!("Extracted NodeIndices: {:?}", routers);
+                        router_error = Some(e);
+                    }
+                    // do not rely on `check`, its result is meaningless if `step` failed. It is
+                    // only used to rank the failure.
+                    if self.count_violations() && recorded {
+                        eval_policy.check();
+                        num_violations =
+                            eval_policy.get_watch_errors().1.iter().filter(|e| e.is_some()).count();
+                    }
+                    mod_ok = false;
+                    break 'apply_group;
+                }
+                // the hard policies are violated
+                if !eval_policy.check() {
+                    if self.count_violations() {
+                        num_violations =
+                            eval_policy.get_watch_errors().1.iter().filter(|e| e.is_some()).count();
+                    }
+                    mod_ok = false;
+                    break 'apply_group;
+                }
+            } else {
+                // the modifier cannot be applied, or the network does not converge (and has no
+                // meaningful forwarding state)
+                mod_ok = false;
+                break 'apply_group;
+            }
+        }
+        // check if the modifier is ok
+        if mod_ok {
+            if let Some((_, scratch_policy)) = scratch {
+                // apply the group on the network itself, such that it can be undone later.
+                for modifier in self.groups[group_idx].iter() {
+                    net.apply_modifier(modifier)?;
+                }
+                *hard_policy = scratch_policy;
+            }
+            // everything fine
+            Ok(Ok(()))
+        } else {
+            if self.candidate_evaluation == CandidateEvaluation::Undo {
+                // undo the hard policy and the network
+                snapshot.restore(eval_net, eval_policy)?;
+            }
+            Ok(Err(Failure { group: group_idx, error: router_error, num_violations }))
+        }
+    }
+
+    /// This function tries to find a dependency based on the current position. The arguments
+    /// are as follows:
+    ///
+    /// - `net`: Network at state of the good ordering. After returning, the net will have the exact
+    ///   same state as before.
+    /// - `hard_policy`: Hard Policy at state of the good ordering. After returning, the hard policy
+    ///   will have the exact same state as before.
+    /// - `good_ordering`: Ordering of groups, which work up to the point of the bad group
+    /// - `bad_group`: Index of the bad group which causes the problme. This function will search a
+    ///    dependency to solve this bad group.
+    ///
+    /// If a dependency was found successfully, then this function will return the new dependency
+    /// (first argument), along with the set of groups that are part of this new dependency (second
+    /// argument). If no dependency group could be found, then `None` is returned. If the network
+    /// cannot be reverted, the network error is returned.
+    #[cfg_attr(feature = "ltl-solver", allow(dead_code))]
+    fn find_dependency(
+        &mut self,
+        net: &mut Network,            // current state of the network
+        hard_policy: &mut HardPolicy, // current state of the hard policies
+        good_ordering: &[usize],      // groups applied so far, without causing any error
+        bad_group: usize,             // group causing the problem, which needs to be fixed
+        abort: Stopper,               // signal to abort the search
+    ) -> Result<Option<Dependency>, Error> {
+        // apply the modifier to the network to get the errors
+        let snapshot = Snapshot::take(net, hard_policy);
+        let mut errors = None;
+        'apply_group: for modifier in self.groups[bad_group].iter() {
+            #[cfg(feature = "count-states")]
+            {
+                self.num_solver_states += 1;
+            }
+            if net.apply_modifier(modifier).is_ok() {
+                let mut fw_state = net.get_forwarding_state();
+                // forwarding errors are recorded in the hard policy, and checked below
+                let _ = hard_policy.step(net, &mut fw_state);
+
+                if !hard_policy.check() {
+                    errors = Some(hard_policy.get_watch_errors());
+                    break 'apply_group;
+                }
+            } else {
+                // the modifier cannot be applied, or the network does not converge (and has no
+                // meaningful forwarding state)
+                errors = Some((Vec::new(), vec![Some(PolicyError::NoConvergence)]));
+                break 'apply_group;
+            }
+        }
+
+        // undo the hard policy and the network
+        snapshot.restore(net, hard_policy)?;
+
+        Ok(match errors {
+            // an error occurred, search for the dependency that fixes the bad group
+            Some(errors) => {
+                // the good ordering, followed by the bad group
+                let ordering = good_ordering
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(bad_group))
+                    .collect::<Vec<usize>>();
+                utils::find_dependency::<S>(
                     &self.net,
                     &self.groups,
                     &self.hard_policy,
@@ -1062,12 +1885,35 @@ impl StrategyTRTA {
                     self.max_group_solve_time,
                     abort,
                     #[cfg(feature = "count-states")]
-                    &mut self.num_states,
+                    &mut self.num_solver_states,
                 )
             }
-            // 如果没有错误发生，抛出异常，表明传入的坏组实际上是“好的”（没有问题）
-            None => panic!("The bad group, passed into this function seems to be fine!"),
-        }
+            // The policies are satisfied, but the group was rejected, because `step` failed
+            // (e.g., on a black hole which the policy allows). No dependency can be derived from
+            // the watch errors.
+            None => {
+                debug!("The bad group only causes errors while checking the policies!");
+                None
+            }
+        })
+    }
+
+    /// Returns the number of states explored by the tree exploration, i.e., while searching for the
+    /// next group to apply.
+    ///
+    /// *This method is only available if the `"count-states"` feature is enabled!*
+    #[cfg(feature = "count-states")]
+    pub fn num_tree_states(&self) -> usize {
+        self.num_tree_states
+    }
+
+    /// Returns the number of states explored while searching for a dependency, after the tree
+    /// exploration got stuck.
+    ///
+    /// *This method is only available if the `"count-states"` feature is enabled!*
+    #[cfg(feature = "count-states")]
+    pub fn num_solver_states(&self) -> usize {
+        self.num_solver_states
     }
 
     /// Returns true if, during exploration, we encountered a dependency without immediate effect.
@@ -1079,28 +1925,1715 @@ impl StrategyTRTA {
     }
 }
 
+/// Single call to the solver, recorded in the transcript of [`StrategyTRTA`]. See
+/// [`StrategyTRTA::set_record_transcript`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverExchange {
+    /// Formula passed to the solver (possibly truncated)
+    pub query: String,
+    /// Ordering returned by the solver, or `None` if the formula was unsatisfiable.
+    pub result: Option<Vec<usize>>,
+}
+
+/// Statistics of a single run of [`StrategyTRTA::work`], which can be accessed with
+/// [`StrategyTRTA::stats`]. Backtracking counts once, even if it undoes multiple groups.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrtaStats {
+    /// Number of calls to the solver
+    pub solver_calls: usize,
+    /// Number of times the exploration was reset (after learning a new constraint or group)
+    pub resets: usize,
+    /// Number of resets, for which the network and the hard policies could not be undone, and
+    /// were cloned instead
+    pub cloned_resets: usize,
+    /// Number of times the exploration backtracked
+    pub pops: usize,
+    /// Number of steps taken further down the exploration tree (i.e., groups applied successfully,
+    /// except for the last one of a valid ordering)
+    pub pushes: usize,
+    /// Total time spent in the solver
+    pub solver_time: Duration,
+    /// Number of times a conflict was expanded after the solver proved the formula unsatisfiable
+    /// (see [`StrategyTRTA::set_max_expansions`])
+    pub expansions: usize,
+    /// Number of times the exploration was reset, because it did not make any progress (see
+    /// [`StrategyTRTA::set_stall_limit`])
+    pub forced_resets: usize,
+    /// All conflicts processed when the exploration was stuck, in the order in which they
+    /// occurred. Conflicts are only recorded with the `ltl-solver` feature.
+    pub conflicts: Vec<ConflictRecord>,
+    /// Number of groups (i.e., scheduling steps) of every valid ordering, in the order in which
+    /// they were found.
+    pub found_steps: Vec<usize>,
+    /// Number of solver orderings which violated the hard policies when simulating them (see
+    /// [`StrategyTRTA::set_cross_validate`])
+    pub rejected_solver_orderings: usize,
+}
+
+/// Returns a random order of the indices `0..n`. If weights are given, the order is sampled without
+/// replacement, such that indices with a larger weight are more likely to be placed in front.
+/// Otherwise, or if the weights are invalid (e.g., negative, or all zero), the order is uniformly
+/// shuffled.
+fn weighted_order<R: Rng>(rng: &mut R, n: usize, weights: Option<&[f64]>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..n).collect();
+    let weights = match weights {
+        Some(w) if w.len() == n && WeightedIndex::new(w).is_ok() => w,
+        _ => {
+            order.shuffle(rng);
+            return order;
+        }
+    };
+    let mut result = Vec::with_capacity(n);
+    let mut rem_weights: Vec<f64> = weights.to_vec();
+    while !order.is_empty() {
+        // the remaining weights may all be zero, in which case the rest is shuffled uniformly.
+        let pos = match WeightedIndex::new(&rem_weights) {
+            Ok(dist) => dist.sample(rng),
+            Err(_) => rng.gen_range(0, order.len()),
+        };
+        result.push(order.remove(pos));
+        rem_weights.remove(pos);
+    }
+    result
+}
+
+/// Conflict processed by [`StrategyTRTA::work`], when the exploration is stuck and a constraint is
+/// derived from the error. Each record is logged at the `trace` level, and collected in
+/// [`TrtaStats::conflicts`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictRecord {
+    /// Groups of the valid prefix, after which the exploration was stuck
+    pub prefix: Vec<usize>,
+    /// Group which could not be applied after the prefix
+    pub doing: usize,
+    /// Routers involved in the error
+    pub routers: Vec<RouterId>,
+    /// For each problem of the error, the groups which may fix it
+    pub matched_groups: Vec<Vec<usize>>,
+    /// Constraint derived from the conflict
+    pub clause: String,
+}
+
+/// How [`StrategyTRTA`] evaluates a group during the exploration. See
+/// [`StrategyTRTA::set_candidate_evaluation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateEvaluation {
+    /// Apply the group on the network and check the policies. If the group cannot be applied, all
+    /// changes are undone.
+    Undo,
+    /// Apply the group and check the policies on a copy of the network, which is discarded if the
+    /// group cannot be applied. Only if it can be applied, the group is applied on the network
+    /// itself, such that it can be undone later on. This avoids undoing the groups which cannot be
+    /// applied, at the cost of copying the network for every group, and applying the valid groups
+    /// twice.
+    SpeculativeClone,
+}
+
+/// Which of the failed groups [`StrategyTRTA`] uses to learn a conflict, once the exploration is
+/// stuck. Only groups whose failure involves specific routers are considered, unless there is none.
+/// See [`StrategyTRTA::set_conflict_selection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSelection {
+    /// The first group that failed. The learned conflict depends on the order in which the groups
+    /// were tried.
+    FirstFail,
+    /// The group that leaves the most policy violations. If multiple groups leave the same number
+    /// of violations, the first of them is used.
+    MostViolations,
+    /// A random group among the failed ones.
+    Random,
+}
+
+/// Kinds of errors, for which [`StrategyTRTA`] may call the solver to learn a new constraint. See
+/// [`StrategyTRTA::set_solver_errors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SolverErrorKind {
+    /// Some routers have no route towards a prefix (`NetworkError::ForwardingBlackHole`)
+    ForwardingBlackHole,
+    /// Packets are forwarded in a loop (`NetworkError::ForwardingLoop` or
+    /// `NetworkError::ForwardingLoops`)
+    ForwardingLoop,
+    /// Any other error involving specific routers (e.g., `NetworkError::InvalidBgpTable`)
+    RouterError,
+    /// None of the remaining groups can be applied after the current sequence, because applying
+    /// them fails, the network does not converge, or the policies are violated without involving
+    /// specific routers (`NetworkError::NoConvergence`). The learned constraint forbids all these
+    /// groups directly after the current sequence.
+    NoConvergence,
+}
+
+impl SolverErrorKind {
+    /// Returns the set of all kinds.
+    pub fn all() -> HashSet<Self> {
+        vec![
+            Self::ForwardingBlackHole,
+            Self::ForwardingLoop,
+            Self::RouterError,
+            Self::NoConvergence,
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    /// Returns the kind of the error, or `None` if the solver cannot handle this error.
+    pub fn of(error: &NetworkError) -> Option<Self> {
+        match error {
+            NetworkError::ForwardingBlackHole(_) => Some(Self::ForwardingBlackHole),
+            NetworkError::ForwardingLoop(_) | NetworkError::ForwardingLoops(_) => {
+                Some(Self::ForwardingLoop)
+            }
+            NetworkError::NoConvergence => Some(Self::NoConvergence),
+            e if !routers_from_error(e).is_empty() => Some(Self::RouterError),
+            _ => None,
+        }
+    }
+}
+
+/// Check that no two modifiers change the same configuration expression. Otherwise, the result
+/// depends on the ordering of these modifiers, and they would collide in the LTL variables. The
+/// returned string contains the offending modifiers.
+fn check_modifiers(groups: &[Vec<ConfigModifier>]) -> Result<(), String> {
+    let mut seen: HashMap<ConfigExprKey, &ConfigModifier> = HashMap::new();
+    for modifier in groups.iter().flatten() {
+        match seen.insert(modifier.key(), modifier) {
+            Some(other) if other == modifier => {
+                return Err(format!("Duplicate modifier: {:?}", modifier));
+            }
+            Some(other) => {
+                return Err(format!("Conflicting modifiers: {:?} and {:?}", other, modifier));
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Error returned if `work_all` or `explore` succeeds without finding any ordering, which would be
+/// a bug in the exploration.
+fn no_ordering_found(source: &str) -> Error {
+    Error::Internal(format!("{} succeeded without finding any ordering", source))
+}
+
+/// Check that all routers referenced by the modifiers (see [`ConfigModifier::routers`]) exist
+/// in the network. Otherwise, conflicts at these routers could never be matched to the groups. The
+/// first unknown router is returned.
+fn check_routers(net: &Network, groups: &[Vec<ConfigModifier>]) -> Result<(), RouterId> {
+    groups
+        .iter()
+        .flatten()
+        .flat_map(|modifier| modifier.routers())
+        .find(|router| matches!(net.get_device(*router), NetworkDevice::None))
+        .map_or(Ok(()), Err)
+}
+
+/// Returns the number of modifiers in the given sequence of groups.
+fn num_modifiers(groups: &[Vec<ConfigModifier>], sequence: &[usize]) -> usize {
+    sequence.iter().map(|g| groups[*g].len()).sum()
+}
+
+/// Check that the ordering returned by the solver is a permutation of all `num_groups` groups,
+/// i.e., every group appears exactly once.
+fn validate_ordering(indices: &[usize], num_groups: usize) -> Result<(), Error> {
+    let mut seen = vec![false; num_groups];
+    for idx in indices {
+        match seen.get_mut(*idx) {
+            Some(s) if !*s => *s = true,
+            _ => return Err(Error::InvalidSolverOrdering(indices.to_vec())),
+        }
+    }
+    if indices.len() == num_groups {
+        Ok(())
+    } else {
+        Err(Error::InvalidSolverOrdering(indices.to_vec()))
+    }
+}
+
+/// Returns all routers involved in the error (without duplicates), or an empty vector if the error
+/// does not refer to any specific router.
+pub(super) fn routers_from_error(e: &NetworkError) -> Vec<RouterId> {
+    let routers = match e {
+        NetworkError::ForwardingBlackHole(path) | NetworkError::ForwardingLoop(path) => {
+            path.clone()
+        }
+        NetworkError::ForwardingLoops(loops) => loops.iter().flatten().cloned().collect(),
+        NetworkError::DeviceNotFound(r)
+        | NetworkError::DeviceIsExternalRouter(r)
+        | NetworkError::InvalidBgpTable(r) => vec![*r],
+        NetworkError::InvalidBgpSessionType(a, b, _) | NetworkError::RoutersNotConnected(a, b) => {
+            vec![*a, *b]
+        }
+        _ => Vec::new(),
+    };
+    routers.into_iter().unique().collect()
+}
+
+/// Divide the total time budget by `fraction` to get the time budget for solving a single group.
+/// Never returns a zero budget, unless the total budget is zero.
+fn group_solve_time(time_budget: Option<Duration>, fraction: u32) -> Option<Duration> {
+    time_budget.map(|dur| {
+        let group_time = dur / fraction.max(1);
+        if group_time.is_zero() {
+            warn!("Time budget of {:?} is too small to be divided by {}", dur, fraction);
+            dur
+        } else {
+            group_time
+        }
+    })
+}
+
+/// Watches the growth of the LTL formula in relation to the progress of the exploration. See
+/// [`StrategyTRTA::set_formula_growth_limit`].
+#[derive(Debug, Clone)]
+struct GrowthWatch {
+    max_formula_len: usize,
+    max_resets: usize,
+    best_prefix: usize,
+    stalled_resets: usize,
+}
+
+impl GrowthWatch {
+    fn new(max_formula_len: usize, max_resets: usize) -> Self {
+        Self { max_formula_len, max_resets, best_prefix: 0, stalled_resets: 0 }
+    }
+
+    /// Register a reset, with the current length of the formula, and the length of the valid
+    /// prefix at which the exploration was stuck. Returns `true` if the formula is too large, and
+    /// the prefix has not improved for too many resets.
+    fn is_stalled(&mut self, formula_len: usize, prefix_len: usize) -> bool {
+        if prefix_len > self.best_prefix {
+            self.best_prefix = prefix_len;
+            self.stalled_resets = 0;
+        } else {
+            self.stalled_resets += 1;
+        }
+        if formula_len > self.max_formula_len && self.stalled_resets >= self.max_resets {
+            error!(
+                "No progress since {} resets (best prefix: {}), but the formula has grown to {} bytes!",
+                self.stalled_resets, self.best_prefix, formula_len
+            );
+            return true;
+        }
+        false
+    }
+}
+
+/// Watches the length of the longest valid sequence reached by the exploration. See
+/// [`StrategyTRTA::set_stall_limit`].
 #[derive(Debug, Clone)]
-enum StackAction {
-    Pop,
-    Push(StackFrame),
-    Reset,
+struct ProgressWatch {
+    max_iterations: usize,
+    best_len: usize,
+    iterations: usize,
+}
+
+impl ProgressWatch {
+    fn new(max_iterations: usize) -> Self {
+        Self { max_iterations, best_len: 0, iterations: 0 }
+    }
+
+    /// Register an iteration, with the length of the current sequence. Returns `true` if the
+    /// sequence has not improved for too many iterations. In this case, the count is restarted.
+    fn is_stalled(&mut self, len: usize) -> bool {
+        if len > self.best_len {
+            self.best_len = len;
+            self.iterations = 0;
+            return false;
+        }
+        self.iterations += 1;
+        if self.iterations >= self.max_iterations {
+            self.iterations = 0;
+            return true;
+        }
+        false
+    }
 }
 
-/// Single stack frame for the iteration
 #[derive(Debug, Clone)]
-struct StackFrame {
-    /// Number of calls to undo, in order to undo this step
-    num_undo: usize,
-    /// Remaining groups to try at this position
-    rem_groups: Vec<usize>,
-    /// index into rem_groups to check next, after all previous branches have been explroed.
-    idx: usize,
+enum ExplorationAction {
+    /// Skip the remaining groups at the position where the exploration is stuck.
+    Backtrack,
+    /// Reset the exploration. If the ordering is not empty, the exploration starts with this
+    /// ordering. Otherwise, the order is random.
+    Reset(Vec<usize>),
+}
+
+/// Group which could not be applied at some position of the exploration.
+#[derive(Debug)]
+struct Failure {
+    /// Index of the group
+    group: usize,
+    /// Error involving specific routers, if any
+    error: Option<NetworkError>,
+    /// Number of policy violations after applying the group (only computed in the best-first mode,
+    /// and `usize::MAX` otherwise, or if the group cannot be applied at all)
+    num_violations: usize,
 }
 
-impl StackFrame {
-    fn new(options: impl Iterator<Item = usize>, num_undo: usize, rng: &mut ThreadRng) -> Self {
-        let mut rem_groups: Vec<usize> = options.collect();
-        rem_groups.shuffle(rng);
-        Self { num_undo, rem_groups, idx: 0 }
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dep_groups::solver::RecordedSolver;
+    use crate::example_networks::{ExampleNetwork, FirewallNet, SimpleNet};
+    use crate::hard_policies::PathCondition;
+    use crate::modifier_ordering::SimpleOrdering;
+    use crate::netsim::config::ConfigExpr::{BgpSession, IgpLinkWeight};
+    use crate::netsim::config::ConfigModifier::*;
+    use crate::netsim::BgpSessionType::{EBgp, IBgpClient, IBgpPeer};
+    use crate::netsim::Prefix;
+    use crate::test::simple_net_problem;
+    use itertools::iproduct;
+    use maplit::hashmap;
+
+    #[test]
+    fn modifiers_touching_router() {
+        let net = SimpleNet::net(0);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![
+            Insert(BgpSession { source: r4, target: r1, session_type: IBgpPeer }),
+            Remove(BgpSession { source: r1, target: r2, session_type: IBgpPeer }),
+            Insert(BgpSession { source: r4, target: r2, session_type: IBgpPeer }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        assert_eq!(strategy.modifiers_touching(r2), vec![1, 2]);
+        assert_eq!(strategy.modifiers_touching(r4), vec![0, 2]);
+        assert_eq!(strategy.modifiers_touching(r1), vec![0, 1]);
+    }
+
+    #[test]
+    fn alternative_inner_strategy() {
+        let net = FirewallNet::net(0);
+        let cf = FirewallNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = FirewallNet::get_policy(&net, 0);
+        let mut strategy = <StrategyTRTA<PushBackTreeStrategy<SimpleOrdering>>>::new(
+            net,
+            patch.modifiers,
+            hard_policy,
+            None,
+        )
+        .unwrap();
+        // find the group which cannot be applied on its own (the order of the patch is not fixed)
+        let bad_group = (0..strategy.groups.len())
+            .find(|i| {
+                let mut net = strategy.net.clone();
+                let mut hard_policy = strategy.hard_policy.clone();
+                net.apply_modifier(&strategy.groups[*i][0]).unwrap();
+                let mut fw_state = net.get_forwarding_state();
+                hard_policy.step(&mut net, &mut fw_state).unwrap();
+                !hard_policy.check()
+            })
+            .unwrap();
+        let mut net = strategy.net.clone();
+        let mut hard_policy = strategy.hard_policy.clone();
+        let (dependency, groups) = strategy
+            .find_dependency(&mut net, &mut hard_policy, &[], bad_group, Stopper::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1], bad_group);
+        assert_eq!(dependency.len(), 2);
+        assert_eq!(dependency[1], strategy.groups[bad_group][0]);
+    }
+
+    #[test]
+    fn minimal_fix() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let modifiers = vec![
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+            Insert(BgpSession { source: r4, target: r2, session_type: IBgpPeer }),
+            Insert(BgpSession { source: r4, target: e4, session_type: EBgp }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+
+        // r4 only leaves the network via e4 once the eBGP session is established
+        let condition = Condition::Reachable(r4, Prefix(0), Some(PathCondition::Edge(r4, e4)));
+        assert_eq!(
+            strategy.minimal_fix_for(&condition),
+            Some(vec![Insert(BgpSession { source: r4, target: e4, session_type: EBgp })])
+        );
+
+        // the condition already holds in the initial state
+        let condition = Condition::Reachable(r4, Prefix(0), None);
+        assert_eq!(strategy.minimal_fix_for(&condition), Some(vec![]));
+
+        // no modifier makes r1 leave the network via r4
+        let condition = Condition::Reachable(r1, Prefix(0), Some(PathCondition::Node(r4)));
+        assert_eq!(strategy.minimal_fix_for(&condition), None);
+    }
+
+    #[test]
+    fn conflict_selection() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_seed(42);
+        let failure = |group, involved: bool, num_violations| Failure {
+            group,
+            error: if involved { Some(NetworkError::ForwardingBlackHole(vec![r4])) } else { None },
+            num_violations,
+        };
+        let failures = vec![
+            failure(0, false, 5),
+            failure(1, true, 1),
+            failure(2, true, usize::MAX),
+            failure(3, true, 3),
+            failure(4, true, 3),
+        ];
+        // the default is to take the group with the most violations, and the first one on ties
+        assert!(strategy.count_violations());
+        assert_eq!(strategy.select_failure(&failures), 3);
+        strategy.set_conflict_selection(ConflictSelection::FirstFail);
+        assert!(!strategy.count_violations());
+        assert_eq!(strategy.select_failure(&failures), 1);
+        strategy.set_conflict_selection(ConflictSelection::Random);
+        let picked: HashSet<usize> = (0..100).map(|_| strategy.select_failure(&failures)).collect();
+        assert_eq!(picked, (1..5).collect());
+        // without any failure involving specific routers, all of them are considered
+        let failures = vec![failure(0, false, 1), failure(1, false, 2)];
+        strategy.set_conflict_selection(ConflictSelection::MostViolations);
+        assert_eq!(strategy.select_failure(&failures), 1);
+        strategy.set_conflict_selection(ConflictSelection::FirstFail);
+        assert_eq!(strategy.select_failure(&failures), 0);
+    }
+
+    #[test]
+    fn skip_solver() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        // removing the only session of r4 always creates a black hole
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        assert!(strategy.is_solver_worthy(&NetworkError::ForwardingBlackHole(vec![r4])));
+        strategy.set_solver_errors(vec![SolverErrorKind::ForwardingLoop]);
+        assert!(!strategy.is_solver_worthy(&NetworkError::ForwardingBlackHole(vec![r4])));
+        // without the solver, the black hole is handled by backtracking
+        match strategy.work(Stopper::new()) {
+            Err(Error::ProbablyNoSafeOrdering) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn return_best_on_timeout() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r3 = net.get_router_id("r3").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let weight = |weight| IgpLinkWeight { source: r2, target: r3, weight };
+        // removing the only session of r4 always creates a black hole
+        let modifiers = vec![
+            Update { from: weight(1.0), to: weight(2.0) },
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        for return_best in [false, true] {
+            let budget = Duration::from_millis(10);
+            let mut strategy = <StrategyTRTA>::new(
+                net.clone(),
+                modifiers.clone(),
+                hard_policy.clone(),
+                Some(budget),
+            )
+            .unwrap();
+            strategy.set_try_given_order(false);
+            strategy.set_shuffle(false);
+            strategy.set_solver_errors(vec![SolverErrorKind::ForwardingLoop]);
+            strategy.set_return_best_on_timeout(return_best);
+            // use up the time budget as soon as the first group is applied
+            strategy.set_on_progress(Box::new(move |_| std::thread::sleep(2 * budget)));
+            match strategy.work(Stopper::new()) {
+                Err(Error::PartialSolution(ordering)) if return_best => {
+                    assert_eq!(ordering, vec![modifiers[0].clone()])
+                }
+                Err(Error::Timeout) if !return_best => {}
+                r => panic!("Unexpected result: {:?}", r),
+            }
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn recorded_solver_solution() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        // if the removal is tried first, the solver is asked, and tells to insert first.
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![1, 0])])));
+        assert_eq!(
+            strategy.work(Stopper::new()).unwrap(),
+            vec![modifiers[1].clone(), modifiers[0].clone()]
+        );
+    }
+
+    #[test]
+    fn reset_network() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        let mut net = strategy.net.clone();
+        let mut hard_policy = strategy.hard_policy.clone();
+
+        // the network is undone to the initial snapshot
+        let initial = Snapshot::take(&net, &hard_policy);
+        strategy.apply_group(&mut net, &mut hard_policy, 1).unwrap().unwrap();
+        strategy.apply_group(&mut net, &mut hard_policy, 0).unwrap().unwrap();
+        strategy.reset_network(&mut net, &mut hard_policy, Some(initial));
+        assert_eq!(net, strategy.net);
+        assert_eq!(hard_policy.num_steps(), strategy.hard_policy.num_steps());
+        assert_eq!(strategy.stats().cloned_resets, 0);
+
+        // if the undo stack was cleared, the snapshot is no longer part of the history, and the
+        // initial network is cloned instead.
+        strategy.apply_group(&mut net, &mut hard_policy, 1).unwrap().unwrap();
+        let snapshot = Snapshot::take(&net, &hard_policy);
+        strategy.apply_group(&mut net, &mut hard_policy, 0).unwrap().unwrap();
+        net.clear_undo_stack();
+        strategy.reset_network(&mut net, &mut hard_policy, Some(snapshot));
+        assert_eq!(net, strategy.net);
+        assert_eq!(hard_policy.num_steps(), strategy.hard_policy.num_steps());
+        assert_eq!(strategy.stats().cloned_resets, 1);
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn cross_validate() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        for cross_validate in [false, true] {
+            let mut strategy =
+                <StrategyTRTA>::new(net.clone(), modifiers.clone(), hard_policy.clone(), None)
+                    .unwrap();
+            strategy.set_try_given_order(false);
+            strategy.set_shuffle(false);
+            strategy.set_cross_validate(cross_validate);
+            // the first solver ordering removes the session first, which creates a black hole.
+            strategy.set_solver(Box::new(RecordedSolver::new(vec![
+                Some(vec![0, 1]),
+                Some(vec![1, 0]),
+            ])));
+            assert_eq!(
+                strategy.work(Stopper::new()).unwrap(),
+                vec![modifiers[1].clone(), modifiers[0].clone()]
+            );
+            assert_eq!(strategy.stats().rejected_solver_orderings, cross_validate as usize);
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn conflict_record() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let r4 = net.get_router_id("r4").unwrap();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_shuffle(false);
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![1, 0])])));
+        strategy.work(Stopper::new()).unwrap();
+        // removing the session first creates a black hole at r4, which is fixed by the insertion.
+        let conflicts = &strategy.stats().conflicts;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].prefix, Vec::<usize>::new());
+        assert_eq!(conflicts[0].doing, 0);
+        assert_eq!(conflicts[0].routers, vec![r4]);
+        assert_eq!(conflicts[0].matched_groups, vec![vec![1]]);
+        assert!(strategy.stats().solver_calls >= 1);
+        assert!(!conflicts[0].clause.is_empty());
+        // the network was undone for the reset, and not cloned
+        assert_eq!(strategy.stats().resets, 1);
+        assert_eq!(strategy.stats().cloned_resets, 0);
+    }
+
+    #[test]
+    fn memory_budget() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_shuffle(false);
+        // the exploration gets stuck, but the base formula alone exceeds the budget.
+        strategy.set_memory_budget(1, true);
+        match strategy.work(Stopper::new()) {
+            Err(Error::MemoryBudgetExceeded(size)) => assert!(size > 1),
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn recorded_solver_no_solution() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![None])));
+        match strategy.work(Stopper::new()) {
+            Err(Error::ProbablyNoSafeOrdering) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        // the exploration is stopped right away, without resetting
+        assert_eq!(strategy.stats().solver_calls, 1);
+        assert_eq!(strategy.stats().resets, 0);
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn solver_parse_error() {
+        /// Solver with an output that cannot be parsed.
+        struct Garbled;
+        impl LtlSolver for Garbled {
+            fn check(&mut self, _formula: &str) -> Result<Option<Vec<usize>>, Error> {
+                Err(Error::SolverParse("please wait...".to_string()))
+            }
+        }
+
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_solver(Box::new(Garbled));
+        // a parse failure is not mistaken for an unsatisfiable formula
+        match strategy.work(Stopper::new()) {
+            Err(Error::SolverParse(_)) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        assert_eq!(strategy.stats().resets, 0);
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn solver_transcript() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![0]), None])));
+        strategy.set_record_transcript(Some(10));
+        assert!(strategy.work(Stopper::new()).is_err());
+        let transcript = strategy.transcript();
+        assert_eq!(transcript.len(), 2);
+        assert_eq!(transcript[0].result, Some(vec![0]));
+        assert_eq!(transcript[1].result, None);
+        assert!(transcript.iter().all(|e| e.query.len() == 10));
+    }
+
+    #[test]
+    fn no_convergence_is_stuck() {
+        let mut net = SimpleNet::net(1);
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let modifiers = vec![Insert(BgpSession { source: r4, target: e4, session_type: EBgp })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        // the network cannot converge after any modifier, with only two messages allowed
+        net.set_msg_limit(Some(2));
+        let mut probe = net.clone();
+        assert!(probe.is_converged());
+        assert!(probe.apply_modifier(&modifiers[0]).is_err());
+        assert!(!probe.is_converged());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        // without learning from non-converging states, the solver must not be called
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![])));
+        strategy.set_solver_errors(vec![SolverErrorKind::ForwardingBlackHole]);
+        match strategy.work(Stopper::new()) {
+            Err(Error::ProbablyNoSafeOrdering) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn no_convergence_constraint() {
+        let mut net = SimpleNet::net(1);
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let modifiers = vec![Insert(BgpSession { source: r4, target: e4, session_type: EBgp })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        // the network cannot converge after any modifier, with only two messages allowed
+        net.set_msg_limit(Some(2));
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![None])));
+        strategy.set_record_transcript(None);
+        match strategy.work(Stopper::new()) {
+            Err(Error::ProbablyNoSafeOrdering) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        // the group is forbidden as the first group
+        let query = &strategy.transcript()[0].query;
+        assert!(query.starts_with(&format!("({}) & ", ltl::block_prefix_formula(&[], 0))));
+    }
+
+    #[test]
+    fn warm_pool_by_role() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r3 = net.get_router_id("r3").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+
+        // dependency learned while moving r4 from r1 to the route reflector r2
+        let learned = RoleDependency::new(
+            &[
+                Insert(BgpSession { source: r2, target: r4, session_type: IBgpClient }),
+                Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+            ],
+            &hashmap! {
+                r1 => "old".to_string(),
+                r2 => "reflector".to_string(),
+                r4 => "client".to_string(),
+            },
+        )
+        .unwrap();
+
+        // now, move r3 in the same way
+        let modifiers = vec![
+            Remove(BgpSession { source: r1, target: r3, session_type: IBgpPeer }),
+            Insert(BgpSession { source: r2, target: r3, session_type: IBgpClient }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        let routers = hashmap! {
+            "old".to_string() => r1,
+            "reflector".to_string() => r2,
+            "client".to_string() => r3,
+        };
+        assert_eq!(strategy.add_role_dependencies(&[learned], &routers), 1);
+        assert_eq!(strategy.groups.len(), 1);
+        // the solver must not be called
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![])));
+        assert_eq!(
+            strategy.work(Stopper::new()).unwrap(),
+            vec![
+                Insert(BgpSession { source: r2, target: r3, session_type: IBgpClient }),
+                Remove(BgpSession { source: r1, target: r3, session_type: IBgpPeer }),
+            ]
+        );
+    }
+
+    #[test]
+    fn final_check() {
+        let net = SimpleNet::net(0);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![
+            Insert(BgpSession { source: r4, target: r1, session_type: IBgpPeer }),
+            Insert(BgpSession { source: r4, target: r2, session_type: IBgpPeer }),
+            Remove(BgpSession { source: r1, target: r2, session_type: IBgpPeer }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        assert_eq!(strategy.verify_ordering(&modifiers), Ok(()));
+    }
+
+    #[test]
+    fn growth_without_progress() {
+        let mut watch = GrowthWatch::new(100, 3);
+        // progress is made, and the formula is small
+        assert!(!watch.is_stalled(10, 1));
+        assert!(!watch.is_stalled(20, 2));
+        // no progress, but the formula is still small
+        assert!(!watch.is_stalled(50, 2));
+        assert!(!watch.is_stalled(80, 1));
+        // progress resets the counter, even if the formula is already large
+        assert!(!watch.is_stalled(120, 3));
+        assert!(!watch.is_stalled(140, 3));
+        assert!(!watch.is_stalled(160, 2));
+        assert!(watch.is_stalled(180, 3));
+        assert_eq!(watch.stalled_resets, 3);
+    }
+
+    #[test]
+    fn routers_of_errors() {
+        let r = |i: usize| RouterId::new(i);
+        assert_eq!(
+            routers_from_error(&NetworkError::ForwardingBlackHole(vec![r(2), r(1)])),
+            vec![r(2), r(1)]
+        );
+        assert_eq!(
+            routers_from_error(&NetworkError::ForwardingLoop(vec![r(0), r(1), r(0)])),
+            vec![r(0), r(1)]
+        );
+        assert_eq!(
+            routers_from_error(&NetworkError::ForwardingLoops(vec![
+                vec![r(0), r(1)],
+                vec![r(1), r(2)]
+            ])),
+            vec![r(0), r(1), r(2)]
+        );
+        assert_eq!(
+            routers_from_error(&NetworkError::RoutersNotConnected(r(3), r(4))),
+            vec![r(3), r(4)]
+        );
+        assert_eq!(routers_from_error(&NetworkError::NoConvergence), vec![]);
+
+        assert_eq!(
+            SolverErrorKind::of(&NetworkError::ForwardingLoop(vec![r(0)])),
+            Some(SolverErrorKind::ForwardingLoop)
+        );
+        assert_eq!(
+            SolverErrorKind::of(&NetworkError::InvalidBgpTable(r(0))),
+            Some(SolverErrorKind::RouterError)
+        );
+        assert_eq!(
+            SolverErrorKind::of(&NetworkError::NoConvergence),
+            Some(SolverErrorKind::NoConvergence)
+        );
+        assert_eq!(SolverErrorKind::of(&NetworkError::ConvergenceLoop(vec![], vec![])), None);
+    }
+
+    #[test]
+    fn solver_ordering_is_permutation() {
+        assert!(validate_ordering(&[2, 0, 1], 3).is_ok());
+        assert!(validate_ordering(&[], 0).is_ok());
+        assert!(matches!(validate_ordering(&[], 3), Err(Error::InvalidSolverOrdering(_))));
+        assert!(matches!(validate_ordering(&[0, 1], 3), Err(Error::InvalidSolverOrdering(_))));
+        assert!(matches!(validate_ordering(&[0, 1, 1], 3), Err(Error::InvalidSolverOrdering(_))));
+        assert!(matches!(validate_ordering(&[0, 1, 3], 3), Err(Error::InvalidSolverOrdering(_))));
+    }
+
+    #[test]
+    fn learned_groups() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        assert!(strategy.learned_groups().is_empty());
+        utils::add_minimal_ordering_as_new_gorup(&mut strategy.groups, vec![1, 0], None);
+        assert_eq!(
+            strategy.learned_groups(),
+            vec![&[modifiers[1].clone(), modifiers[0].clone()][..]]
+        );
+    }
+
+    #[test]
+    fn step_error_rejects_group() {
+        let (net, modifiers, _) = simple_net_problem();
+        let r4 = net.get_router_id("r4").unwrap();
+        // all routers must only be reachable at the end
+        let conditions = iproduct!(net.get_routers().into_iter(), net.get_known_prefixes().iter())
+            .map(|(r, p)| Condition::Reachable(r, *p, None))
+            .collect::<Vec<_>>();
+        let all = (0..conditions.len()).collect::<Vec<_>>();
+        let hard_policy = HardPolicy::until_globally(conditions, &[], &all);
+        // removing the session first creates a black hole, and `step` returns an error. The policy
+        // allows it, and no watch error is set.
+        let mut policy = hard_policy.clone();
+        let mut removed = net.clone();
+        let mut fw_state = removed.get_forwarding_state();
+        policy.step(&mut removed, &mut fw_state).unwrap();
+        removed.apply_modifier(&modifiers[0]).unwrap();
+        let mut fw_state = removed.get_forwarding_state();
+        assert!(policy.step(&mut removed, &mut fw_state).is_err());
+        assert_eq!(policy.num_steps(), 2);
+        assert!(policy.check());
+        // the group must still be rejected.
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        let mut net = strategy.net.clone();
+        let mut hard_policy = strategy.hard_policy.clone();
+        let failure = strategy.apply_group(&mut net, &mut hard_policy, 0).unwrap().unwrap_err();
+        assert_eq!(failure.error, Some(NetworkError::ForwardingBlackHole(vec![r4])));
+        assert_eq!(net, strategy.net);
+        assert_eq!(hard_policy.num_steps(), 1);
+
+        #[cfg(feature = "ltl-solver")]
+        {
+            strategy.set_try_given_order(false);
+            strategy.set_shuffle(false);
+            strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![1, 0])])));
+            assert_eq!(
+                strategy.work(Stopper::new()).unwrap(),
+                vec![modifiers[1].clone(), modifiers[0].clone()]
+            );
+        }
+    }
+
+    #[test]
+    fn stall_limit() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_shuffle(false);
+        // the removal fails first, without extending the sequence.
+        strategy.set_stall_limit(1);
+        assert_eq!(
+            strategy.work(Stopper::new()).unwrap(),
+            vec![modifiers[1].clone(), modifiers[0].clone()]
+        );
+        assert!(strategy.stats().forced_resets >= 1);
+        assert_eq!(strategy.stats().solver_calls, 0);
+    }
+
+    #[test]
+    fn progress_watch() {
+        let mut watch = ProgressWatch::new(2);
+        assert!(!watch.is_stalled(1));
+        assert!(!watch.is_stalled(1));
+        assert!(watch.is_stalled(0));
+        assert!(!watch.is_stalled(1));
+        assert!(!watch.is_stalled(2));
+        assert!(!watch.is_stalled(2));
+        assert!(watch.is_stalled(1));
+    }
+
+    #[test]
+    fn time_budget() {
+        let net = SimpleNet::net(0);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let budget = Some(Duration::from_secs(60));
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, budget).unwrap();
+        assert_eq!(strategy.max_group_solve_time, Some(Duration::from_secs(2)));
+        strategy.set_time_fraction(4);
+        assert_eq!(strategy.max_group_solve_time, Some(Duration::from_secs(15)));
+        assert_eq!(strategy.solver_timeout, Some(Duration::from_secs(15)));
+        strategy.set_max_group_solve_time(Duration::from_secs(5));
+        assert_eq!(strategy.max_group_solve_time, Some(Duration::from_secs(5)));
+        assert_eq!(strategy.solver_timeout, Some(Duration::from_secs(5)));
+        // a fraction of 0 is treated as 1
+        strategy.set_time_fraction(0);
+        assert_eq!(strategy.time_fraction(), 1);
+        assert_eq!(strategy.max_group_solve_time, budget);
+
+        // never use a zero budget per group
+        assert_eq!(
+            group_solve_time(Some(Duration::from_nanos(10)), 30),
+            Some(Duration::from_nanos(10))
+        );
+        assert_eq!(
+            group_solve_time(Some(Duration::from_nanos(60)), 30),
+            Some(Duration::from_nanos(2))
+        );
+        assert_eq!(group_solve_time(None, 30), None);
+    }
+
+    #[test]
+    fn seeded_runs() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        // without any condition, every ordering is valid
+        let hard_policy = HardPolicy::globally(vec![]);
+        let run = |seed: u64| {
+            let mut strategy = <StrategyTRTA>::new_seeded(
+                net.clone(),
+                patch.modifiers.clone(),
+                hard_policy.clone(),
+                None,
+                seed,
+            )
+            .unwrap();
+            strategy.set_try_given_order(false);
+            strategy.set_solver(Box::new(RecordedSolver::new(vec![])));
+            strategy.work(Stopper::new())
+        };
+        for seed in 0..5 {
+            let ordering = run(seed).unwrap();
+            assert_eq!(ordering.len(), patch.modifiers.len());
+            assert_eq!(run(seed).unwrap(), ordering);
+        }
+    }
+
+    #[test]
+    fn fork() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy = <StrategyTRTA>::new(
+            net.clone(),
+            patch.modifiers.clone(),
+            hard_policy.clone(),
+            Some(Duration::from_secs(60)),
+        )
+        .unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![])));
+        strategy.set_max_depth(Some(2));
+        for seed in 0..5 {
+            let mut fork = strategy.fork(seed);
+            assert_eq!(fork.groups, strategy.groups);
+            assert_eq!(fork.stop_time, strategy.stop_time);
+            assert_eq!(fork.max_depth, Some(2));
+            // a fork explores the same way as a strategy created with the same seed
+            let mut seeded = <StrategyTRTA>::new_seeded(
+                net.clone(),
+                patch.modifiers.clone(),
+                hard_policy.clone(),
+                None,
+                seed,
+            )
+            .unwrap();
+            seeded.set_try_given_order(false);
+            seeded.set_solver(Box::new(RecordedSolver::new(vec![])));
+            seeded.set_max_depth(Some(2));
+            assert_eq!(fork.work(Stopper::new()).unwrap(), seeded.work(Stopper::new()).unwrap());
+        }
+    }
+
+    #[test]
+    fn ordered_exploration() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        // without any condition, every ordering is valid, and the first one is taken.
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy =
+            <StrategyTRTA>::new(net, patch.modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_shuffle(false);
+        assert_eq!(strategy.work(Stopper::new()).unwrap(), patch.modifiers);
+    }
+
+    #[test]
+    fn alternative_permutator() {
+        use crate::permutators::RandomTreePermutator;
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy = <StrategyTRTA<
+            PushBackTreeStrategy<RandomOrdering>,
+            RandomTreePermutator<usize>,
+        >>::new(net, patch.modifiers.clone(), hard_policy, None)
+        .unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![])));
+        let ordering = strategy.work(Stopper::new()).unwrap();
+        assert_eq!(ordering.len(), patch.modifiers.len());
+        assert_eq!(strategy.stats().pops, 0);
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn bounded_depth() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r3 = net.get_router_id("r3").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let weight = |weight| IgpLinkWeight { source: r2, target: r3, weight };
+        // the session can only be removed after the insertion, which is not the given order.
+        let modifiers = vec![
+            Update { from: weight(1.0), to: weight(2.0) },
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+            Insert(BgpSession { source: r4, target: e4, session_type: EBgp }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        for max_depth in [None, Some(0), Some(1)] {
+            let mut strategy =
+                <StrategyTRTA>::new(net.clone(), modifiers.clone(), hard_policy.clone(), None)
+                    .unwrap();
+            strategy.set_try_given_order(false);
+            strategy.set_shuffle(false);
+            // without the bound, the failed removal is followed by trying the insertion instead
+            strategy.set_best_first(true);
+            strategy.set_max_depth(max_depth);
+            strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![0, 2, 1])])));
+            assert_eq!(
+                strategy.work(Stopper::new()).unwrap(),
+                vec![modifiers[0].clone(), modifiers[2].clone(), modifiers[1].clone()]
+            );
+            // once the stack reaches the bound, the conflict is resolved right away
+            assert_eq!(strategy.stats().solver_calls, max_depth.is_some() as usize);
+            assert_eq!(strategy.stats().conflicts.is_empty(), max_depth.is_none());
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy =
+            <StrategyTRTA>::new(net, patch.modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_try_given_order(false);
+        assert_eq!(strategy.stats(), &TrtaStats::default());
+        strategy.work(Stopper::new()).unwrap();
+        // every group but the last one is pushed, and nothing goes wrong.
+        let n = patch.modifiers.len();
+        let expected = TrtaStats { pushes: n - 1, found_steps: vec![n], ..Default::default() };
+        assert_eq!(strategy.stats(), &expected);
+        // the statistics are reset with every run
+        strategy.work(Stopper::new()).unwrap();
+        assert_eq!(strategy.stats(), &expected);
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn formula_dump() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let modifiers = vec![Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer })];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let path = std::env::temp_dir().join(format!("formula_dump_{}.txt", std::process::id()));
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![0]), None])));
+        strategy.set_record_transcript(None);
+        strategy.set_formula_dump(Some(path.clone()));
+        assert!(strategy.work(Stopper::new()).is_err());
+        let dump = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = dump.lines().collect();
+        let transcript = strategy.transcript();
+        assert_eq!(
+            lines,
+            vec![
+                "# query 1",
+                transcript[0].query.trim_end(),
+                "# result 1: sat [0]",
+                "# query 2",
+                transcript[1].query.trim_end(),
+                "# result 2: unsat",
+            ]
+        );
+    }
+
+    #[test]
+    fn on_progress() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy =
+            <StrategyTRTA>::new(net, patch.modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_shuffle(false);
+        strategy.set_try_given_order(false);
+        let sequences: Rc<RefCell<Vec<Vec<usize>>>> = Rc::new(RefCell::new(Vec::new()));
+        let observed = sequences.clone();
+        strategy.set_on_progress(Box::new(move |seq| observed.borrow_mut().push(seq.to_vec())));
+        strategy.work(Stopper::new()).unwrap();
+        let n = patch.modifiers.len();
+        assert_eq!(*sequences.borrow(), (1..=n).map(|i| (0..i).collect()).collect::<Vec<Vec<_>>>());
+    }
+
+    #[test]
+    fn work_with_trace() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy =
+            <StrategyTRTA>::new(net.clone(), patch.modifiers, hard_policy, None).unwrap();
+        let (ordering, trace) = strategy.work_with_trace(Stopper::new()).unwrap();
+        assert_eq!(trace.len(), ordering.len());
+        // compare the trace with the forwarding state of a manual replay
+        let mut replay = net.clone();
+        for (modifier, fw_state) in ordering.iter().zip(trace.iter()) {
+            replay.apply_modifier(modifier).unwrap();
+            let expected = replay.get_forwarding_state();
+            for router in net.get_routers() {
+                for prefix in net.get_known_prefixes() {
+                    assert_eq!(
+                        fw_state.get_next_hop(router, *prefix).unwrap(),
+                        expected.get_next_hop(router, *prefix).unwrap()
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn merge_repeated_dependency() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy =
+            <StrategyTRTA>::new(net.clone(), modifiers.clone(), hard_policy.clone(), None).unwrap();
+        strategy.set_shuffle(false);
+        // the solver insists on the wrong ordering, so the dependency is learned twice.
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![0, 1])])));
+        assert_eq!(strategy.groups.len(), 2);
+        assert_eq!(
+            strategy.work(Stopper::new()).unwrap(),
+            vec![modifiers[1].clone(), modifiers[0].clone()]
+        );
+        assert_eq!(strategy.groups.len(), 1);
+        assert_eq!(strategy.stats().solver_calls, 1);
+
+        // without merging, the solver is asked again
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_shuffle(false);
+        strategy.set_merge_threshold(None);
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![0, 1]), None])));
+        assert!(strategy.work(Stopper::new()).is_err());
+        assert_eq!(strategy.groups.len(), 2);
+        assert_eq!(strategy.stats().solver_calls, 2);
+    }
+
+    #[test]
+    fn pre_grouped_modifiers() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let insert = Insert(BgpSession { source: r4, target: e4, session_type: EBgp });
+        let remove = Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer });
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        // the known dependency is given as a group, so the solver is never called.
+        let mut strategy = <StrategyTRTA>::new_with_groups(
+            net.clone(),
+            vec![vec![insert.clone(), remove.clone()]],
+            hard_policy.clone(),
+            None,
+        )
+        .unwrap();
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![])));
+        assert_eq!(strategy.work(Stopper::new()).unwrap(), vec![insert.clone(), remove]);
+
+        match <StrategyTRTA>::new_with_groups(net, vec![vec![insert], vec![]], hard_policy, None) {
+            Err(Error::InvalidInput(_)) => {}
+            r => panic!("Unexpected result: {:?}", r.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn duplicate_modifiers() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let insert = Insert(BgpSession { source: r4, target: e4, session_type: EBgp });
+        let remove = Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer });
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let check = |modifiers: Vec<ConfigModifier>| match <StrategyTRTA>::new(
+            net.clone(),
+            modifiers,
+            hard_policy.clone(),
+            None,
+        ) {
+            Ok(_) => None,
+            Err(Error::InvalidInput(msg)) => Some(msg),
+            Err(e) => panic!("Unexpected error: {}", e),
+        };
+        assert_eq!(check(vec![insert.clone(), remove.clone()]), None);
+        let msg = check(vec![insert.clone(), remove.clone(), insert.clone()]).unwrap();
+        assert!(msg.contains("Duplicate"));
+        assert!(msg.contains(&format!("{:?}", insert)));
+        // the session is both inserted and removed (in the other direction)
+        let conflict = Remove(BgpSession { source: e4, target: r4, session_type: EBgp });
+        let msg = check(vec![insert.clone(), conflict.clone()]).unwrap();
+        assert!(msg.contains("Conflicting"));
+        assert!(msg.contains(&format!("{:?}", conflict)));
+        // the same holds across groups
+        assert!(<StrategyTRTA>::new_with_groups(
+            net.clone(),
+            vec![vec![insert.clone(), remove], vec![insert]],
+            hard_policy.clone(),
+            None
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "count-states")]
+    #[test]
+    fn count_states() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy =
+            <StrategyTRTA>::new(net, patch.modifiers.clone(), hard_policy, None).unwrap();
+        strategy.work(Stopper::new()).unwrap();
+        // every modifier is applied once by the tree exploration, and nothing goes wrong.
+        assert_eq!(strategy.num_tree_states(), patch.modifiers.len());
+        assert_eq!(strategy.num_solver_states(), 0);
+        assert_eq!(strategy.num_states(), patch.modifiers.len());
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn expansion() {
+        let (net, mut modifiers, hard_policy) = simple_net_problem();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r3 = net.get_router_id("r3").unwrap();
+        modifiers.push(Insert(BgpSession { source: r2, target: r3, session_type: IBgpPeer }));
+        let mut strategy =
+            <StrategyTRTA>::new(net.clone(), modifiers.clone(), hard_policy.clone(), None).unwrap();
+        strategy.set_shuffle(false);
+        strategy.set_record_transcript(None);
+        // the first formula is unsatisfiable, but the expanded one is not.
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![None, Some(vec![1, 0, 2])])));
+        assert_eq!(
+            strategy.work(Stopper::new()).unwrap(),
+            vec![modifiers[1].clone(), modifiers[0].clone(), modifiers[2].clone()]
+        );
+        assert_eq!(strategy.stats().expansions, 1);
+        let transcript = strategy.transcript();
+        assert!(transcript[0].query.starts_with("(G(e0 -> (N(G(! e1))))) & "));
+        assert!(transcript[1].query.starts_with("(G(e0 -> (N(G(! e1)) | N(G(! e2))))) & "));
+
+        // without expansions, the exploration stops right away
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        strategy.set_shuffle(false);
+        strategy.set_max_expansions(0);
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![None])));
+        match strategy.work(Stopper::new()) {
+            Err(Error::ProbablyNoSafeOrdering) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        assert_eq!(strategy.stats().solver_calls, 1);
+    }
+
+    #[test]
+    fn given_order() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let patch = net.current_config().get_diff(&cf);
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy =
+            <StrategyTRTA>::new(net, patch.modifiers.clone(), hard_policy, None).unwrap();
+        let called = Rc::new(Cell::new(false));
+        let observed = called.clone();
+        strategy.set_on_progress(Box::new(move |_| observed.set(true)));
+        // the given order is valid, and returned without exploring anything
+        assert_eq!(strategy.work(Stopper::new()).unwrap(), patch.modifiers);
+        assert_eq!(
+            strategy.stats(),
+            &TrtaStats { found_steps: vec![patch.modifiers.len()], ..Default::default() }
+        );
+        assert!(!called.get());
+    }
+
+    #[test]
+    fn precedence() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let modifiers = net.current_config().get_diff(&cf).modifiers;
+        let n = modifiers.len();
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_shuffle(false);
+        assert!(strategy.set_precedence(vec![(0, 1), (1, 0)]).is_err());
+        assert!(strategy.set_precedence(vec![(0, n)]).is_err());
+        strategy.set_precedence(vec![(n - 1, 0)]).unwrap();
+        let ordering = strategy.work(Stopper::new()).unwrap();
+        let pos = |m: &ConfigModifier| ordering.iter().position(|x| x == m).unwrap();
+        assert!(pos(&modifiers[n - 1]) < pos(&modifiers[0]));
+        // the remaining modifiers are explored in the given order
+        assert_eq!(ordering[0], modifiers[1]);
+    }
+
+    #[test]
+    fn work_all() {
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let modifiers = net.current_config().get_diff(&cf).modifiers;
+        let num_orderings: usize = (1..=modifiers.len()).product();
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_shuffle(false);
+
+        let orderings = strategy.work_all(Stopper::new(), 2).unwrap();
+        assert_eq!(orderings.len(), 2);
+        assert_eq!(orderings[0], modifiers);
+        assert_ne!(orderings[0], orderings[1]);
+
+        // all orderings are found, if the limit is large enough
+        let orderings = strategy.work_all(Stopper::new(), num_orderings + 1).unwrap();
+        assert_eq!(orderings.len(), num_orderings);
+        for (i, ordering) in orderings.iter().enumerate() {
+            assert_eq!(ordering.len(), modifiers.len());
+            assert!(modifiers.iter().all(|m| ordering.contains(m)));
+            assert!(!orderings[..i].contains(ordering));
+        }
+        assert!(strategy.work_all(Stopper::new(), 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ordering_cost() {
+        use crate::netsim::config::{ConfigExpr, ConfigExprKey};
+
+        /// Cost of one for every state, in which the expression is changed
+        struct ChangedCost(ConfigExprKey, Option<ConfigExpr>);
+
+        impl OrderingCost for ChangedCost {
+            fn cost(&self, net: &Network, _step: &ConfigModifier) -> u64 {
+                (net.current_config().expr.get(&self.0) != self.1.as_ref()) as u64
+            }
+        }
+
+        let net = SimpleNet::net(0);
+        let cf = SimpleNet::final_config(&net, 0);
+        let modifiers = net.current_config().get_diff(&cf).modifiers;
+        let n = modifiers.len();
+        let key = modifiers[n - 2].key();
+        let cost = ChangedCost(key.clone(), net.current_config().expr.get(&key).cloned());
+        let hard_policy = HardPolicy::globally(vec![]);
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_shuffle(false);
+        strategy.set_ordering_cost(Box::new(cost), 100);
+        // the cheapest ordering changes the expression last
+        let ordering = strategy.work(Stopper::new()).unwrap();
+        assert_eq!(ordering.last(), Some(&modifiers[n - 2]));
+        assert_eq!(ordering.len(), n);
+    }
+
+    #[test]
+    fn best_first() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e1 = net.get_router_id("e1").unwrap();
+        // removing the eBGP session causes a black hole at every router, removing the iBGP
+        // session only at r4.
+        let modifiers = vec![
+            Remove(BgpSession { source: r1, target: e1, session_type: EBgp }),
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        let mut net = strategy.net.clone();
+        let mut hard_policy = strategy.hard_policy.clone();
+
+        // violations are only counted in the best-first mode, or to select the conflict with the
+        // most violations
+        strategy.set_conflict_selection(ConflictSelection::FirstFail);
+        let failure = strategy.apply_group(&mut net, &mut hard_policy, 1).unwrap().unwrap_err();
+        assert_eq!(failure.num_violations, usize::MAX);
+
+        strategy.set_best_first(true);
+        let ebgp_failure =
+            strategy.apply_group(&mut net, &mut hard_policy, 0).unwrap().unwrap_err();
+        let failure = strategy.apply_group(&mut net, &mut hard_policy, 1).unwrap().unwrap_err();
+        assert_eq!(failure.error, Some(NetworkError::ForwardingBlackHole(vec![r4])));
+        assert!(failure.num_violations < ebgp_failure.num_violations);
+        // the network and the hard policy are left unchanged
+        assert_eq!(net, strategy.net);
+        assert!(strategy.apply_group(&mut net, &mut hard_policy, 0).unwrap().is_err());
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn solve_monolithic() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![1, 0])])));
+        strategy.set_record_transcript(None);
+        assert_eq!(
+            strategy.solve_monolithic().unwrap(),
+            vec![modifiers[1].clone(), modifiers[0].clone()]
+        );
+        assert_eq!(strategy.stats().solver_calls, 1);
+        // removing the session on the initial state creates a black hole, which the insertion
+        // fixes. This is the only conflict.
+        let conflicts = &strategy.stats().conflicts;
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].prefix, Vec::<usize>::new());
+        assert_eq!(conflicts[0].doing, 0);
+        assert_eq!(conflicts[0].clause, "G(e0 -> (N(G(! e1))))");
+        let query = &strategy.transcript()[0].query;
+        assert!(query.starts_with("(G(e0 -> (N(G(! e1))))) & "));
+
+        // an invalid ordering of the solver is not returned
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![0, 1])])));
+        assert!(matches!(strategy.solve_monolithic(), Err(Error::ProbablyNoSafeOrdering)));
+        // neither is an unsatisfiable formula
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![None])));
+        assert!(matches!(strategy.solve_monolithic(), Err(Error::ProbablyNoSafeOrdering)));
+    }
+
+    #[test]
+    fn unknown_router() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let dangling = RouterId::new(100);
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let modifiers =
+            vec![Insert(BgpSession { source: r1, target: dangling, session_type: IBgpPeer })];
+        match <StrategyTRTA>::new(net, modifiers, hard_policy, None) {
+            Err(Error::UnknownRouter(router)) => assert_eq!(router, dangling),
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("The dangling router was not detected!"),
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn prefer_fewest_steps() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r2 = net.get_router_id("r2").unwrap();
+        let r3 = net.get_router_id("r3").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e4 = net.get_router_id("e4").unwrap();
+        let weight = |weight| IgpLinkWeight { source: r2, target: r3, weight };
+        let modifiers = vec![
+            Update { from: weight(1.0), to: weight(2.0) },
+            Insert(BgpSession { source: r4, target: e4, session_type: EBgp }),
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_try_given_order(false);
+        strategy.set_shuffle(false);
+        strategy.set_merge_threshold(Some(1));
+        strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![2, 0, 1]), None])));
+        strategy.set_prefer_fewest_steps(Some(10));
+        // The first ordering is found with three groups. Then, the solver tells to remove the
+        // session first, the insertion and the removal are merged, and the next ordering is found
+        // with two groups.
+        assert_eq!(
+            strategy.work(Stopper::new()).unwrap(),
+            vec![modifiers[1].clone(), modifiers[2].clone(), modifiers[0].clone()]
+        );
+        assert_eq!(strategy.stats().found_steps, vec![3, 2]);
+    }
+
+    #[test]
+    fn speculative_clone() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let r4 = net.get_router_id("r4").unwrap();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+        strategy.set_candidate_evaluation(CandidateEvaluation::SpeculativeClone);
+        let mut net = strategy.net.clone();
+        let mut hard_policy = strategy.hard_policy.clone();
+
+        // the failing group leaves the network and the hard policy unchanged
+        let failure = strategy.apply_group(&mut net, &mut hard_policy, 0).unwrap().unwrap_err();
+        assert_eq!(failure.error, Some(NetworkError::ForwardingBlackHole(vec![r4])));
+        assert_eq!(net, strategy.net);
+        assert_eq!(hard_policy.num_steps(), strategy.hard_policy.num_steps());
+
+        // the valid group is applied on the network, and can be undone
+        let snapshot = Snapshot::take(&net, &hard_policy);
+        strategy.apply_group(&mut net, &mut hard_policy, 1).unwrap().unwrap();
+        assert_ne!(net, strategy.net);
+        assert_eq!(hard_policy.num_steps(), strategy.hard_policy.num_steps() + 1);
+        snapshot.restore(&mut net, &mut hard_policy).unwrap();
+        assert_eq!(net, strategy.net);
+        assert_eq!(hard_policy.num_steps(), strategy.hard_policy.num_steps());
+
+        // both evaluations find the same ordering
+        #[cfg(feature = "ltl-solver")]
+        {
+            strategy.set_try_given_order(false);
+            strategy.set_shuffle(false);
+            strategy.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![1, 0])])));
+            assert_eq!(
+                strategy.work(Stopper::new()).unwrap(),
+                vec![modifiers[1].clone(), modifiers[0].clone()]
+            );
+        }
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn best_first_conflict() {
+        let net = SimpleNet::net(1);
+        let r1 = net.get_router_id("r1").unwrap();
+        let r4 = net.get_router_id("r4").unwrap();
+        let e1 = net.get_router_id("e1").unwrap();
+        let modifiers = vec![
+            Remove(BgpSession { source: r1, target: e1, session_type: EBgp }),
+            Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+        ];
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+        for best_first in [false, true].iter().cloned() {
+            let mut strategy =
+                <StrategyTRTA>::new(net.clone(), modifiers.clone(), hard_policy.clone(), None)
+                    .unwrap();
+            strategy.set_shuffle(false);
+            strategy.set_best_first(best_first);
+            strategy.set_merge_threshold(None);
+            strategy.set_max_expansions(0);
+            strategy.set_record_transcript(None);
+            strategy.set_solver(Box::new(RecordedSolver::new(vec![None])));
+            assert!(matches!(strategy.work(Stopper::new()), Err(Error::ProbablyNoSafeOrdering)));
+            let query = &strategy.transcript()[0].query;
+            if best_first {
+                // both groups were tried, and the one causing a single black hole is used
+                let clause = ltl::block_prefix_formula(&[], 1);
+                assert!(query.starts_with(&format!("({}) & ", clause)), "{}", query);
+            } else {
+                // the first group is used right away
+                assert!(query.starts_with("(G(e0 -> (N(G(! e1))))"), "{}", query);
+            }
+        }
+    }
+
+    #[test]
+    fn weighted_order() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for weights in [None, Some(vec![1.0, -1.0, 1.0]), Some(vec![0.0; 3])].iter() {
+            let mut order = super::weighted_order(&mut rng, 3, weights.as_deref());
+            order.sort_unstable();
+            assert_eq!(order, vec![0, 1, 2]);
+        }
+        // groups with zero weight are never placed before the others
+        for _ in 0..20 {
+            let order = super::weighted_order(&mut rng, 3, Some(&[0.0, 1.0, 0.0]));
+            assert_eq!(order[0], 1);
+        }
+        // larger weights are more likely to be in front
+        let first = (0..1000)
+            .filter(|_| super::weighted_order(&mut rng, 2, Some(&[9.0, 1.0]))[0] == 0)
+            .count();
+        assert!(first > 800, "{}", first);
+    }
+
+    #[test]
+    fn conflict_weights() {
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let r4 = net.get_router_id("r4").unwrap();
+        let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        assert_eq!(strategy.group_weights(), None);
+        strategy.set_conflict_weights(true);
+        assert_eq!(strategy.group_weights(), Some(vec![1.0, 1.0]));
+        for _ in 0..3 {
+            strategy.stats.conflicts.push(ConflictRecord {
+                prefix: vec![],
+                doing: 0,
+                routers: vec![r4],
+                matched_groups: vec![vec![1]],
+                clause: String::new(),
+            });
+        }
+        assert_eq!(strategy.group_weights(), Some(vec![0.25, 1.0]));
+        let order = strategy.initial_order();
+        assert_eq!(order.len(), 2);
     }
 }
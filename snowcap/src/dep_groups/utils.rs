@@ -23,7 +23,7 @@
 
 use crate::hard_policies::{HardPolicy, PolicyError, WatchErrors};
 use crate::netsim::config::ConfigModifier;
-use crate::netsim::{printer, Network, NetworkError};
+use crate::netsim::{printer, Network, NetworkError, SnapshotToken};
 use crate::strategies::{GroupStrategy, Strategy};
 use crate::{Error, Stopper};
 
@@ -626,6 +626,34 @@ pub(super) fn finalize_ordering(
     ordering.iter().map(|g| groups[*g].iter()).flatten().cloned().collect()
 }
 
+/// State of the network and the hard policy, to which both can be restored after exploring a
+/// group, no matter how many modifiers were applied and how many states were recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Snapshot {
+    net: SnapshotToken,
+    num_steps: usize,
+}
+
+impl Snapshot {
+    /// Take a snapshot of the current state of the network and the hard policy.
+    pub(super) fn take(net: &Network, hard_policy: &HardPolicy) -> Self {
+        Self { net: net.snapshot(), num_steps: hard_policy.num_steps() }
+    }
+
+    /// Restore the network and the hard policy to the state of the snapshot. If the network
+    /// cannot be restored, the error is returned (see [`Network::restore`]).
+    pub(super) fn restore(
+        self,
+        net: &mut Network,
+        hard_policy: &mut HardPolicy,
+    ) -> Result<(), NetworkError> {
+        while hard_policy.num_steps() > self.num_steps {
+            hard_policy.undo();
+        }
+        net.restore(self.net)
+    }
+}
+
 /// Format the group ordering into a nice multiline string
 pub(super) fn fmt_group_ord(
     groups: &[Vec<ConfigModifier>],
@@ -17,7 +17,7 @@
 
 //! Module containing all error types
 
-use crate::netsim::{config::ConfigModifier, ConfigError, NetworkError};
+use crate::netsim::{config::ConfigModifier, ConfigError, NetworkError, RouterId};
 use crate::topology_zoo::ZooTopologyError;
 use thiserror::Error;
 
@@ -46,6 +46,41 @@ pub enum Error {
     /// Used up all of the time budget
     #[error("The time budget was used up without finding any solution")]
     Timeout,
+    /// Used up all of the time budget, but the given modifiers can be applied in this order
+    /// without violating the hard policies. The remaining modifiers are not part of the ordering.
+    #[error("The time budget was used up: Only a partial ordering of {} steps was found", .0.len())]
+    PartialSolution(Vec<ConfigModifier>),
+    /// The solver-guided search did not make any progress for the given number of resets, while
+    /// the LTL formula grew beyond the configured limit (in bytes).
+    #[error("No progress after {0} resets, while the LTL formula grew to {1} bytes")]
+    NoProgress(usize, usize),
+    /// The estimated memory (in bytes) of the LTL formula and the exploration stack exceeded the
+    /// configured memory budget.
+    #[error("The estimated memory usage of {0} bytes exceeds the memory budget")]
+    MemoryBudgetExceeded(usize),
+    /// The maximum number of calls to the external solver was reached (see
+    /// [`SolverCallLimited`](crate::strategies::SolverCallLimited)).
+    #[error("The limit of {0} solver calls was reached")]
+    SolverCallLimitExceeded(usize),
+    /// The external LTL solver could not be started, or the communication with it failed.
+    #[error("LTL solver is unavailable: {0}")]
+    SolverUnavailable(String),
+    /// The output of the external LTL solver could not be parsed.
+    #[error("Cannot parse the output of the LTL solver: {0}")]
+    SolverParse(String),
+    /// The LTL solver returned an ordering, which is not a permutation of all groups.
+    #[error("LTL solver returned an invalid ordering: {0:?}")]
+    InvalidSolverOrdering(Vec<usize>),
+    /// The input passed to the strategy is invalid.
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    /// A modifier references a router, which does not exist in the network.
+    #[error("Unknown router: {0:?}")]
+    UnknownRouter(RouterId),
+    /// Internal inconsistency, which should never happen (e.g., the final state of a synthesized
+    /// ordering does not satisfy the hard policies).
+    #[error("Internal Error: {0}")]
+    Internal(String),
     /// On an operation abort
     #[error("The operation was aborted")]
     Abort,
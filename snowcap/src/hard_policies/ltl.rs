@@ -324,6 +324,13 @@ impl HardPolicy {
         }
     }
 
+    /// Returns the number of states in the history, i.e., the number of successful calls to `step`
+    /// minus the number of calls to `undo`. If `step` returns an error while the state could not
+    /// be computed, the state is not added to the history.
+    pub fn num_steps(&self) -> usize {
+        self.history.len()
+    }
+
     /// Reset the strucutre, such that no state exists.
     pub fn reset(&mut self) {
         self.history.clear();
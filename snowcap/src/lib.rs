@@ -91,10 +91,28 @@
 //!
 //! ## Features
 //!
+//! - *`bench`*: If this feature is enabled, then the module [`bench`] is available, to compare
+//!   different strategies on generated problems of increasing size. This enables `count-states`.
+//!
 //! - *`count-states`*: If this feature is enabled, then [strategies](strategies::Strategy) and
 //!   [optimizers](optimizers::Optimizer) will contain the method `num_states`, to get the number
 //!   of network states that have been explored.
 //!
+//! - *`debug-stack`*: If this feature is enabled, then
+//!   [`OptimizerTRTA`](optimizers::OptimizerTRTA) remembers its exploration stack, which can be
+//!   inspected with `stack_frames`, for instance after it has run into a timeout.
+//!
+//! - *`ltl-solver`* (enabled by default): If this feature is enabled, then
+//!   [`StrategyTRTA`](strategies::StrategyTRTA) calls an external LTL solver (`aaltaf`) to learn
+//!   constraints when the exploration is stuck. Otherwise, it searches for dependencies like the
+//!   [`DepGroupsStrategy`](strategies::DepGroupsStrategy), and no solver is required.
+//!
+//! - *`serde`*: If this feature is enabled, then the configuration types (like
+//!   [`ConfigModifier`](netsim::config::ConfigModifier)) implement `Serialize` and `Deserialize`,
+//!   and orderings can be stored and loaded with [`plan::ordering_to_json`] and
+//!   [`plan::ordering_from_json`]. The [`plan`] module, which computes the fingerprint of an
+//!   ordering from this serialization, is only available with this feature.
+//!
 //! ## Usage
 //!
 //! To use this module, you need to do first prepare your [network](netsim::Network) to
@@ -138,6 +156,8 @@ pub mod example_networks;
 mod test;
 pub mod topology_zoo;
 
+#[cfg(feature = "bench")]
+pub mod bench;
 mod dep_groups;
 mod error;
 pub mod hard_policies;
@@ -145,6 +165,8 @@ pub mod modifier_ordering;
 pub mod netsim;
 pub mod optimizers;
 pub mod permutators;
+#[cfg(feature = "serde")]
+pub mod plan;
 pub mod soft_policies;
 //pub mod static_analysis;
 pub mod strategies;
@@ -152,7 +174,10 @@ pub mod strategies;
 //pub mod transient_behavior;
 
 mod synthesize;
-pub use synthesize::{optimize, synthesize, synthesize_parallel};
+pub use synthesize::{
+    apply_ordering, optimize, run_portfolio, synthesize, synthesize_parallel, verify_ordering,
+    VerifyError,
+};
 
 pub use error::Error;
 
@@ -0,0 +1,66 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # Fan-Out Ordering
+
+use super::{CompleteOrdering, ModifierOrdering, SimpleOrdering};
+use crate::netsim::config::ConfigModifier;
+use std::cmp::Ordering;
+
+/// #Fan-Out Ordering
+/// The following ordering is used:
+/// - Number of distinct routers touched by the modifier, in descending order
+/// - Ids of the touched routers (sorted), in ascending order
+/// - [`SimpleOrdering`] for all remaining ties
+///
+/// Modifiers touching many routers (e.g., BGP sessions between two routers) are placed before
+/// modifiers which only change a single router (e.g., route-maps).
+pub struct FanOutOrdering {}
+
+impl ModifierOrdering<ConfigModifier> for FanOutOrdering {
+    fn order(a: &ConfigModifier, b: &ConfigModifier) -> Ordering {
+        let ra = a.routers();
+        let rb = b.routers();
+        rb.len().cmp(&ra.len()).then_with(|| ra.cmp(&rb)).then_with(|| SimpleOrdering::order(a, b))
+    }
+}
+
+impl CompleteOrdering for FanOutOrdering {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::netsim::config::ConfigExpr::{BgpRouteMap, BgpSession};
+    use crate::netsim::config::ConfigModifier::Insert;
+    use crate::netsim::route_map::{RouteMapBuilder, RouteMapDirection};
+    use crate::netsim::BgpSessionType::*;
+
+    #[test]
+    fn test_sort() {
+        let p1 = Insert(BgpSession { source: 1.into(), target: 2.into(), session_type: EBgp });
+        let p2 = Insert(BgpSession { source: 1.into(), target: 3.into(), session_type: EBgp });
+        let p3 = Insert(BgpSession { source: 0.into(), target: 0.into(), session_type: EBgp });
+        let p4 = Insert(BgpRouteMap {
+            router: 2.into(),
+            direction: RouteMapDirection::Incoming,
+            map: RouteMapBuilder::new().order(10).allow().build(),
+        });
+        let mut data = vec![p4.clone(), p3.clone(), p2.clone(), p1.clone()];
+        FanOutOrdering::sort(&mut data);
+        assert_eq![data, vec![p1, p2, p3, p4]];
+    }
+}
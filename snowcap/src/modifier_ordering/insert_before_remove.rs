@@ -0,0 +1,70 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # Insert-Before-Remove Ordering
+
+use super::ModifierOrdering;
+use crate::netsim::config::ConfigModifier::{self, Insert, Remove, Update};
+use std::cmp::Ordering;
+
+/// #Insert-Before-Remove Ordering
+/// The following ordering is used:
+/// - Modification type: Insert < Update < Remove
+///
+/// Modifiers of the same type compare as equal. Since sorting is stable, their relative order is
+/// preserved. This makes it useful as a make-before-break heuristic, where all new sessions are
+/// established before any old one is torn down. This is not a
+/// [`CompleteOrdering`](super::CompleteOrdering).
+pub struct InsertBeforeRemoveOrdering {}
+
+impl ModifierOrdering<ConfigModifier> for InsertBeforeRemoveOrdering {
+    fn order(a: &ConfigModifier, b: &ConfigModifier) -> Ordering {
+        rank(a).cmp(&rank(b))
+    }
+}
+
+fn rank(modifier: &ConfigModifier) -> u8 {
+    match modifier {
+        Insert(_) => 0,
+        Update { .. } => 1,
+        Remove(_) => 2,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::netsim::config::ConfigExpr::{BgpSession, IgpLinkWeight};
+    use crate::netsim::BgpSessionType::*;
+
+    #[test]
+    fn test_sort() {
+        let s =
+            |a: u32, b: u32| BgpSession { source: a.into(), target: b.into(), session_type: EBgp };
+        let i1 = Insert(s(3, 4));
+        let i2 = Insert(s(1, 2));
+        let r1 = Remove(s(2, 3));
+        let r2 = Remove(s(0, 1));
+        let u1 = Update {
+            from: IgpLinkWeight { source: 0.into(), target: 1.into(), weight: 1.0 },
+            to: IgpLinkWeight { source: 0.into(), target: 1.into(), weight: 2.0 },
+        };
+        let mut data = vec![r1.clone(), i1.clone(), u1.clone(), r2.clone(), i2.clone()];
+        InsertBeforeRemoveOrdering::sort(&mut data);
+        assert_eq![data, vec![i1, i2, u1, r1, r2]];
+    }
+}
@@ -35,6 +35,12 @@ pub use unordered::NoOrdering;
 mod random;
 pub use random::RandomOrdering;
 
+mod fan_out;
+pub use fan_out::FanOutOrdering;
+
+mod insert_before_remove;
+pub use insert_before_remove::InsertBeforeRemoveOrdering;
+
 use std::cmp::Ordering;
 
 /// # ModifierOrdering
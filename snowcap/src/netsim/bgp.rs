@@ -89,6 +89,7 @@ impl std::hash::Hash for BgpRoute {
 
 /// Type of a BGP session
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BgpSessionType {
     /// iBGP session with a peer (or from a coient with a Route Reflector)
     IBgpPeer,
@@ -131,29 +131,31 @@ impl Config {
             ConfigModifier::Insert(expr) => {
                 if let Some(old_expr) = self.expr.insert(expr.key(), expr.clone()) {
                     self.expr.insert(old_expr.key(), old_expr);
-                    return Err(ConfigError::ConfigModifierError(modifier.clone()));
+                    return Err(ConfigError::ConfigModifierError(Box::new(modifier.clone())));
                 }
             }
             ConfigModifier::Remove(expr) => match self.expr.remove(&expr.key()) {
                 Some(old_expr) if &old_expr != expr => {
                     self.expr.insert(old_expr.key(), old_expr);
-                    return Err(ConfigError::ConfigModifierError(modifier.clone()));
+                    return Err(ConfigError::ConfigModifierError(Box::new(modifier.clone())));
                 }
-                None => return Err(ConfigError::ConfigModifierError(modifier.clone())),
+                None => return Err(ConfigError::ConfigModifierError(Box::new(modifier.clone()))),
                 _ => {}
             },
             ConfigModifier::Update { from: expr_a, to: expr_b } => {
                 // check if both are similar
                 let key = expr_a.key();
                 if key != expr_b.key() {
-                    return Err(ConfigError::ConfigModifierError(modifier.clone()));
+                    return Err(ConfigError::ConfigModifierError(Box::new(modifier.clone())));
                 }
                 match self.expr.remove(&key) {
                     Some(old_expr) if &old_expr != expr_a => {
                         self.expr.insert(key, old_expr);
-                        return Err(ConfigError::ConfigModifierError(modifier.clone()));
+                        return Err(ConfigError::ConfigModifierError(Box::new(modifier.clone())));
+                    }
+                    None => {
+                        return Err(ConfigError::ConfigModifierError(Box::new(modifier.clone())))
                     }
-                    None => return Err(ConfigError::ConfigModifierError(modifier.clone())),
                     _ => {}
                 }
                 self.expr.insert(key, expr_b.clone());
@@ -244,6 +246,7 @@ impl PartialEq for Config {
 /// # Single configuration expression
 /// The expression sets a specific thing in the network.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConfigExpr {
     /// Sets the link weight of a single link (directional)
     /// TODO make sure that the weight is strictly smaller than infinity.
@@ -315,13 +318,20 @@ impl ConfigExpr {
         }
     }
 
-    /// Returns the router IDs on which the configuration is applied and have to be changed.
+    /// Returns the router IDs referenced by the expression. These are the routers, whose
+    /// configuration or adjacency is changed by the expression:
+    ///
+    /// - `BgpSession`: both BGP speakers (`source`, `target`).
+    /// - `IgpLinkWeight`: both ends of the link (`source`, `target`).
+    /// - `StaticRoute`: the configured router, and the neighbor to which packets are forwarded
+    ///   (`router`, `target`).
+    /// - `BgpRouteMap`: only the configured router (`router`).
     pub fn routers(&self) -> Vec<RouterId> {
         match self {
             ConfigExpr::IgpLinkWeight { source, target, .. } => vec![*source, *target],
             ConfigExpr::BgpSession { source, target, .. } => vec![*source, *target],
             ConfigExpr::BgpRouteMap { router, .. } => vec![*router],
-            ConfigExpr::StaticRoute { router, .. } => vec![*router],
+            ConfigExpr::StaticRoute { router, target, .. } => vec![*router, *target],
         }
     }
 }
@@ -379,6 +389,7 @@ pub enum ConfigExprKey {
 /// A single patch to apply on a configuration. The modifier can either insert a new expression,
 /// update an existing expression or remove an old expression.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConfigModifier {
     /// Insert a new expression
     Insert(ConfigExpr),
@@ -403,13 +414,22 @@ impl ConfigModifier {
         }
     }
 
-    /// Returns the RouterId(s) of the router(s) which will be updated by this modifier
+    /// Returns the RouterId(s) of the router(s) touched by this modifier (see
+    /// [`ConfigExpr::routers`]), sorted and without duplicates. For `ConfigModifier::Update`, the
+    /// routers of both the old and the new expression are returned.
     pub fn routers(&self) -> Vec<RouterId> {
-        match self {
+        let mut routers = match self {
             Self::Insert(e) => e.routers(),
             Self::Remove(e) => e.routers(),
-            Self::Update { to, .. } => to.routers(),
-        }
+            Self::Update { from, to } => {
+                let mut routers = from.routers();
+                routers.extend(to.routers());
+                routers
+            }
+        };
+        routers.sort();
+        routers.dedup();
+        routers
     }
 
     /// Reverses the modifier. An insert becomes a remove, and viceversa. An update updates from the
@@ -427,6 +447,7 @@ impl ConfigModifier {
 /// A series of `ConfigModifiers` which can be applied on a `Config` to get a new `Config`. The
 /// series is an ordered list, and the modifiers are applied in the order they were added.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConfigPatch {
     /// List of all modifiers, in the order in which they are applied.
     pub modifiers: Vec<ConfigModifier>,
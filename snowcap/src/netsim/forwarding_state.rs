@@ -89,10 +89,121 @@ impl ForwardingState {
             .collect::<HashMap<Prefix, usize>>();
         let num_prefixes = prefixes.len();
 
-        // initialize state
+        // collect the external routers, and the next hops of all routers
+        let external_routers: HashSet<RouterId> = net.get_external_routers().into_iter().collect();
+        let state = Self::collect_state(net, &prefixes, &external_routers);
+
+        // prepare the cache
+        let cache = repeat(None).take(num_prefixes * num_devices).collect();
+
+        Self { num_prefixes, num_devices, state, prefixes, external_routers, cache }
+    }
+
+    /// Update the forwarding state to match the current state of the network. The next hops of all
+    /// routers are collected again, but only the cached routes which traverse a router whose next
+    /// hop has changed are invalidated. All other cached routes are kept. If the set of routers or
+    /// prefixes has changed, the state is rebuilt from scratch.
+    ///
+    /// The result is always the same as [`ForwardingState::from_net`], no matter from which network
+    /// the state was extracted previously.
+    pub fn update(&mut self, net: &Network) {
+        if self.is_outdated(net) {
+            *self = Self::from_net(net);
+            return;
+        }
+
+        let state = Self::collect_state(net, &self.prefixes, &self.external_routers);
+
+        // collect all routers that have changed their next hop, for each prefix
+        let mut changed: Vec<HashSet<RouterId>> = vec![HashSet::new(); self.num_prefixes];
+        for (idx, (old, new)) in self.state.iter().zip(state.iter()).enumerate() {
+            if old != new {
+                changed[idx % self.num_prefixes].insert(((idx / self.num_prefixes) as u32).into());
+            }
+        }
+        self.state = state;
+        self.invalidate_cache(&changed);
+    }
+
+    /// Update the forwarding state, assuming that only the `routers` may have changed their next
+    /// hops since the state was extracted. Only the next hops of these routers are collected
+    /// again, and only the cached routes which traverse one of them (if its next hop has changed)
+    /// are invalidated. If the set of routers or prefixes has changed, the state is rebuilt from
+    /// scratch.
+    ///
+    /// The result is the same as [`ForwardingState::from_net`] only if no other router has changed
+    /// its next hop. Use [`ForwardingState::update`] if this is unknown.
+    pub fn update_routers(&mut self, net: &Network, routers: &[RouterId]) {
+        if self.is_outdated(net) {
+            *self = Self::from_net(net);
+            return;
+        }
+
+        let mut changed: Vec<HashSet<RouterId>> = vec![HashSet::new(); self.num_prefixes];
+        for router in routers.iter() {
+            let next_hops: Vec<(usize, Option<RouterId>)> = match net.get_device(*router) {
+                NetworkDevice::InternalRouter(r) => {
+                    self.prefixes.iter().map(|(p, pid)| (*pid, r.get_next_hop(*p))).collect()
+                }
+                // external routers point to themselves for every prefix they advertise
+                NetworkDevice::ExternalRouter(r) => {
+                    let advertised = r.advertised_prefixes();
+                    self.prefixes
+                        .iter()
+                        .map(|(p, pid)| (*pid, Some(*router).filter(|_| advertised.contains(p))))
+                        .collect()
+                }
+                NetworkDevice::None => continue,
+            };
+            for (pid, next_hop) in next_hops {
+                let idx = get_idx(router.index(), pid, self.num_prefixes);
+                if self.state[idx] != next_hop {
+                    self.state[idx] = next_hop;
+                    changed[pid].insert(*router);
+                }
+            }
+        }
+        self.invalidate_cache(&changed);
+    }
+
+    /// Returns `true` if the set of routers or prefixes of the network differs from the one of the
+    /// forwarding state, in which case the state needs to be rebuilt from scratch.
+    fn is_outdated(&self, net: &Network) -> bool {
+        let external_routers: HashSet<RouterId> = net.get_external_routers().into_iter().collect();
+        let known_prefixes = net.get_known_prefixes();
+        self.num_devices != net.num_devices()
+            || self.external_routers != external_routers
+            || self.prefixes.len() != known_prefixes.len()
+            || known_prefixes.iter().any(|p| !self.prefixes.contains_key(p))
+    }
+
+    /// Invalidate all cached routes which traverse a router in `changed`, which contains the
+    /// routers that have changed their next hop for each prefix index.
+    fn invalidate_cache(&mut self, changed: &[HashSet<RouterId>]) {
+        for (idx, entry) in self.cache.iter_mut().enumerate() {
+            let changed = &changed[idx % self.num_prefixes];
+            if changed.is_empty() {
+                continue;
+            }
+            if let Some((_, path)) = entry {
+                if path.iter().any(|r| changed.contains(r)) {
+                    *entry = None;
+                }
+            }
+        }
+    }
+
+    /// Collect the next hop of every router for every prefix. External routers point to
+    /// themselves for every prefix they advertise.
+    fn collect_state(
+        net: &Network,
+        prefixes: &HashMap<Prefix, usize>,
+        external_routers: &HashSet<RouterId>,
+    ) -> Vec<Option<RouterId>> {
+        let num_prefixes = prefixes.len();
         let mut state: Vec<Option<RouterId>> =
-            repeat(None).take(num_prefixes * num_devices).collect();
-        for rid in 0..num_devices as u32 {
+            repeat(None).take(num_prefixes * net.num_devices()).collect();
+        for rid in 0..net.num_devices() as u32 {
             if let NetworkDevice::InternalRouter(r) = net.get_device(rid.into()) {
                 for (p, pid) in prefixes.iter() {
                     state[get_idx(rid as usize, *pid, num_prefixes)] = r.get_next_hop(*p);
@@ -100,19 +211,14 @@ impl ForwardingState {
             }
         }
 
-        // collect the external routers, and chagne the forwarding state such that we remember which
-        // prefix they know a route to.
-        let external_routers: HashSet<RouterId> = net.get_external_routers().into_iter().collect();
+        // change the forwarding state such that we remember which prefix the external routers
+        // know a route to.
         for r in external_routers.iter() {
             for p in net.get_device(*r).unwrap_external().advertised_prefixes() {
                 state[get_idx(r.index(), *prefixes.get(&p).unwrap(), num_prefixes)] = Some(*r);
             }
         }
-
-        // prepare the cache
-        let cache = repeat(None).take(num_prefixes * num_devices).collect();
-
-        Self { num_prefixes, num_devices, state, prefixes, external_routers, cache }
+        state
     }
 
     /// Returns the route from the source router to a specific prefix. This function uses the cached
@@ -110,8 +110,8 @@ pub mod printer;
 
 pub use bgp::BgpSessionType;
 pub use forwarding_state::ForwardingState;
-pub use network::Network;
+pub use network::{Network, SnapshotToken};
 pub use types::{
     AsId, ConfigError, DeviceError, IgpNetwork, LinkWeight, NetworkDevice, NetworkError, Prefix,
-    RouterId,
+    RouterId, TopologyNode,
 };
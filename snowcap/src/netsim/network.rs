@@ -29,13 +29,15 @@ use crate::netsim::external_router::ExternalRouter;
 use crate::netsim::printer;
 use crate::netsim::route_map::RouteMapDirection;
 use crate::netsim::router::Router;
-use crate::netsim::types::{IgpNetwork, NetworkDevice};
+use crate::netsim::types::{IgpNetwork, NetworkDevice, TopologyNode};
 use crate::netsim::{
     AsId, ConfigError, ForwardingState, LinkWeight, NetworkError, Prefix, RouterId,
 };
 
 use log::*;
 use petgraph::algo::FloatMeasure;
+use petgraph::graph::Graph;
+use petgraph::Undirected;
 #[cfg(feature = "transient-violation")]
 use rand::prelude::*;
 use std::collections::{HashMap, HashSet};
@@ -150,6 +152,14 @@ pub struct Network {
     skip_queue: bool,
 }
 
+/// Token identifying a state of the network, created by [`Network::snapshot`]. The network can be
+/// brought back to this state with [`Network::restore`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SnapshotToken {
+    /// Length of the event history at the time of the snapshot
+    history_len: usize,
+}
+
 impl Clone for Network {
     /// Cloning the network does not clone the event history, and any of the undo traces.
     fn clone(&self) -> Self {
@@ -242,6 +252,51 @@ impl Network {
         self.net.add_edge(target, source, LinkWeight::infinite());
     }
 
+    /// Create a network from a topology built with `petgraph`, and apply the configuration. Every
+    /// node becomes a router, and every edge a link (the edge weights are ignored, since the link
+    /// weights are part of the configuration). The nodes are added in the order of their index,
+    /// such that the node with index `i` becomes the router with `RouterId` `i`. Hence, the
+    /// configuration can reference the routers by the node indices of the graph. If the
+    /// configuration cannot be applied, then an error is returned.
+    ///
+    /// ```rust
+    /// # use snowcap::netsim::{Network, TopologyNode, config::Config, config::ConfigExpr};
+    /// # use petgraph::graph::Graph;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut graph = Graph::new_undirected();
+    /// let r1 = graph.add_node(TopologyNode::Internal("r1".to_string()));
+    /// let r2 = graph.add_node(TopologyNode::Internal("r2".to_string()));
+    /// graph.add_edge(r1, r2, ());
+    /// let mut config = Config::new();
+    /// config.add(ConfigExpr::IgpLinkWeight { source: r1, target: r2, weight: 1.0 })?;
+    /// config.add(ConfigExpr::IgpLinkWeight { source: r2, target: r1, weight: 1.0 })?;
+    /// let net = Network::from_petgraph(&graph, &config)?;
+    /// assert_eq!(net.get_router_name(r2)?, "r2");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_petgraph<E>(
+        graph: &Graph<TopologyNode, E, Undirected, u32>,
+        config: &Config,
+    ) -> Result<Self, NetworkError> {
+        let mut net = Self::new();
+        for node in graph.node_indices() {
+            let router_id = match &graph[node] {
+                TopologyNode::Internal(name) => net.add_router(name.clone()),
+                TopologyNode::External(name, as_id) => {
+                    net.add_external_router(name.clone(), *as_id)
+                }
+            };
+            debug_assert_eq!(router_id, node);
+        }
+        for edge in graph.edge_indices() {
+            let (source, target) = graph.edge_endpoints(edge).unwrap();
+            net.add_link(source, target);
+        }
+        net.set_config(config)?;
+        Ok(net)
+    }
+
     /// Set the provided network-wide configuration. The network first computes the patch from the
     /// current configuration to the next one, and applies the patch. If the patch cannot be
     /// applied, then an error is returned. Note, that this function may apply a large number of
@@ -525,11 +580,93 @@ impl Network {
         }
     }
 
+    /// Take a snapshot of the current state of the network. Use [`Network::restore`] to return to
+    /// this state later on, which undoes all actions performed since the snapshot, without keeping
+    /// track of how many actions that are.
+    ///
+    /// The snapshot should be taken when the network has converged. It is only valid for this
+    /// network, as long as the state is part of its history. Cloning the network or calling
+    /// [`Network::clear_undo_stack`] invalidates all snapshots.
+    pub fn snapshot(&self) -> SnapshotToken {
+        SnapshotToken { history_len: self.event_history.len() }
+    }
+
+    /// Restore the state of the network at the time of the `snapshot`, by undoing all actions
+    /// performed since then (see [`Network::undo_action`]). If the state is no longer part of the
+    /// undo history (e.g., because the network was cloned, or the snapshot was taken while the
+    /// network had not converged), `NetworkError::HistoryError` is returned.
+    pub fn restore(&mut self, snapshot: SnapshotToken) -> Result<(), NetworkError> {
+        if snapshot.history_len > self.event_history.len() {
+            return Err(NetworkError::HistoryError("Snapshot is not part of the history!"));
+        }
+        while self.event_history.len() > snapshot.history_len {
+            self.undo_action()?;
+        }
+        if self.event_history.len() != snapshot.history_len {
+            return Err(NetworkError::HistoryError("Snapshot was taken during an action!"));
+        }
+        Ok(())
+    }
+
     /// Compute and return the current forwarding state.
     pub fn get_forwarding_state(&self) -> ForwardingState {
         ForwardingState::from_net(self)
     }
 
+    /// Compute the current forwarding state, starting from the forwarding state `prev`, which was
+    /// extracted at the time of the `snapshot`. Only the next hops of the routers touched since
+    /// then are collected again, i.e., the routers of every applied modifier (see
+    /// [`ConfigModifier::routers`]) and the routers that have processed a BGP message. Then, only
+    /// the cached routes traversing a changed next hop are recomputed (see
+    /// [`ForwardingState::update_routers`]). This is much cheaper than
+    /// [`Network::get_forwarding_state`] when only few routers have changed.
+    ///
+    /// If an IGP link weight has changed (which may change the next hop of every router), or the
+    /// snapshot is no longer part of the undo history, the next hops of all routers are collected
+    /// (see [`ForwardingState::update`]).
+    pub fn get_forwarding_state_delta(
+        &self,
+        mut prev: ForwardingState,
+        snapshot: SnapshotToken,
+    ) -> ForwardingState {
+        match self.routers_changed_since(snapshot) {
+            Some(routers) => prev.update_routers(self, &routers),
+            None => prev.update(self),
+        }
+        prev
+    }
+
+    /// Returns all routers whose next hops may have changed since the `snapshot`, based on the
+    /// event history. `None` is returned if this includes every router, or if the snapshot is no
+    /// longer part of the history.
+    fn routers_changed_since(&self, snapshot: SnapshotToken) -> Option<Vec<RouterId>> {
+        let mut routers = Vec::new();
+        for (event, _) in self.event_history.get(snapshot.history_len..)? {
+            match event {
+                Event::Bgp(_, to, _) => routers.push(*to),
+                Event::Config(ConfigModifier::Insert(ConfigExpr::IgpLinkWeight { .. }))
+                | Event::Config(ConfigModifier::Remove(ConfigExpr::IgpLinkWeight { .. }))
+                | Event::Config(ConfigModifier::Update {
+                    to: ConfigExpr::IgpLinkWeight { .. },
+                    ..
+                }) => return None,
+                Event::Config(modifier) => routers.extend(modifier.routers()),
+                Event::AdvertiseExternalRoute(router, _)
+                | Event::WithdrawExternalRoute(router, _) => routers.push(*router),
+            }
+        }
+        routers.sort();
+        routers.dedup();
+        Some(routers)
+    }
+
+    /// Returns `true` if the network has converged, i.e., there are no events left in the queue.
+    /// This is not the case if the message limit was reached while applying a modifier. The
+    /// forwarding state of a network that has not converged is meaningless.
+    pub fn is_converged(&self) -> bool {
+        self.queue.is_empty()
+    }
+
     // ********************
     // * Helper Functions *
     // ********************
@@ -986,9 +1123,9 @@ impl Network {
                         .modify_static_route(*p1, *t)?;
                     Ok(())
                 }
-                _ => Err(NetworkError::ConfigError(ConfigError::ConfigModifierError(
+                _ => Err(NetworkError::ConfigError(ConfigError::ConfigModifierError(Box::new(
                     modifier.clone(),
-                ))),
+                )))),
             },
         }
     }
@@ -1288,7 +1425,7 @@ impl Network {
                             Err(NetworkError::DeviceNotFound(to))
                         }
                     }
-                    e => Err(NetworkError::InvalidEvent(e)),
+                    e => Err(NetworkError::InvalidEvent(Box::new(e))),
                 }?;
 
                 if fw_state_change {
@@ -1414,7 +1551,7 @@ impl Network {
                         return Err(NetworkError::DeviceNotFound(to));
                     }
                 }
-                e => return Err(NetworkError::InvalidEvent(e)),
+                e => return Err(NetworkError::InvalidEvent(Box::new(e))),
             };
             self.event_history.push((event, Some(parent_event_id)));
             Ok(true)
@@ -42,6 +42,7 @@ use std::fmt;
 ///     .build();
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RouteMap {
     /// In which order should the route maps be checked. Lower values mean that they are checked
     /// earlier.
@@ -327,6 +328,7 @@ impl RouteMapBuilder {
 
 /// State of a route map, which can either be allow or deny
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteMapState {
     /// Set the state to allow
     Allow,
@@ -348,6 +350,7 @@ impl RouteMapState {
 
 /// Match statement of the route map. Can be combined to generate complex match statements
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteMapMatch {
     /// Matches on the neighbor (exact value only)
     Neighbor(RouterId),
@@ -379,6 +382,7 @@ impl RouteMapMatch {
 
 /// Generic RouteMapMatchClause to match on all, a range or on a specific element
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteMapMatchClause<T> {
     /// Matches a range of values (inclusive)
     Range(T, T),
@@ -443,6 +447,7 @@ impl fmt::Display for RouteMapMatchClause<AsId> {
 
 /// Clause to match on the as path
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteMapMatchAsPath {
     /// Contains a specific AsId
     Contains(AsId),
@@ -473,6 +478,7 @@ impl fmt::Display for RouteMapMatchAsPath {
 
 /// Set action, if a route map matches
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteMapSet {
     /// overwrite the next hop
     NextHop(RouterId),
@@ -505,6 +511,7 @@ impl RouteMapSet {
 
 /// Direction of the Route Map
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteMapDirection {
     /// Incoming Route Map
     Incoming,
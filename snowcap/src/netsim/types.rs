@@ -32,15 +32,27 @@ type IndexType = u32;
 pub type RouterId = NodeIndex<IndexType>;
 /// IP Prefix (simple representation)
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Prefix(pub u32);
 /// AS Number
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AsId(pub u32);
 /// Link Weight for the IGP graph
 pub type LinkWeight = f32;
 /// IGP Network graph
 pub type IgpNetwork = StableGraph<(), LinkWeight, Directed, IndexType>;
 
+/// Node of a topology, from which a network is created with
+/// [`Network::from_petgraph`](crate::netsim::Network::from_petgraph).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyNode {
+    /// Internal router with the given name
+    Internal(String),
+    /// External router with the given name, belonging to the given AS
+    External(String, AsId),
+}
+
 /// Configuration Error
 #[derive(Error, Debug, PartialEq)]
 pub enum ConfigError {
@@ -52,7 +64,7 @@ pub enum ConfigError {
     /// 2. The ConfigModifier::Remove would remove an non-existing expression
     /// 3. The ConfigModifier::Update would update an non-existing expression
     #[error("The ConfigModifier cannot be applied: {0:?}")]
-    ConfigModifierError(ConfigModifier),
+    ConfigModifierError(Box<ConfigModifier>),
 }
 
 /// # Network Device (similar to `Option`)
@@ -248,7 +260,7 @@ pub enum NetworkError {
     UnexpectedEventConvergenceLoop,
     /// Event cannot be handled by the network
     #[error("Cannot handle the event: {0:?}")]
-    InvalidEvent(Event),
+    InvalidEvent(Box<Event>),
     /// History is invalid
     #[error("History is invalid: {0}")]
     HistoryError(&'static str),
@@ -0,0 +1,261 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # Chained Permutator
+//!
+//! This is a permutator that combines two other permutators, each permuting a different part of
+//! the sequence.
+
+use super::{Permutator, PermutatorItem};
+use crate::netsim::config::ConfigModifier;
+
+/// # Chained Permutator
+///
+/// This is a permutator that combines two other permutators. The first permutator `A` permutes the
+/// first part of the elements, and the second permutator `B` permutes the second part. Each
+/// permutation of `A` is followed by every permutation of `B`, which is restarted for every
+/// permutation of `A`. Hence, all permutations of `A` and `B` are combined (as a cartesian
+/// product), and the elements of the first part always come before those of the second part.
+///
+/// `fail_pos` is forwarded to `A` if the position lies within the first part. In this case, all
+/// remaining permutations of `B` are skipped as well. Otherwise, it is forwarded to `B` (relative
+/// to the start of the second part).
+///
+/// `reset` restarts both permutators, without changing how the elements are split.
+///
+/// Use [`ChainedPermutator::with_parts`] to choose how the elements are split. `Permutator::new`
+/// splits the input in the middle, where the first part gets the smaller half.
+pub struct ChainedPermutator<A, B, T = ConfigModifier> {
+    /// Elements of the second part, needed to restart `B` for every permutation of `A`
+    second: Vec<T>,
+    /// Permutator over the first part
+    first: A,
+    /// The current permutation of the first part, together with the permutator over the second
+    /// part. If it is `None`, the next permutation of `A` is taken.
+    current: Option<(Vec<T>, B)>,
+}
+
+impl<A, B, T> ChainedPermutator<A, B, T>
+where
+    A: Permutator<T> + Iterator,
+    A::Item: PermutatorItem<T>,
+    B: Permutator<T> + Iterator,
+    B::Item: PermutatorItem<T>,
+    T: Clone,
+{
+    /// Creates the permutator, where `first` is permuted by `A`, and `second` by `B`.
+    pub fn with_parts(first: Vec<T>, second: Vec<T>) -> Self {
+        Self { second, first: A::new(first), current: None }
+    }
+}
+
+impl<A, B, T> Permutator<T> for ChainedPermutator<A, B, T>
+where
+    A: Permutator<T> + Iterator,
+    A::Item: PermutatorItem<T>,
+    B: Permutator<T> + Iterator,
+    B::Item: PermutatorItem<T>,
+    T: Clone,
+{
+    fn new(mut input: Vec<T>) -> Self {
+        let second = input.split_off(input.len() / 2);
+        Self::with_parts(input, second)
+    }
+
+    fn fail_pos(&mut self, pos: usize) {
+        let len = match self.current.as_ref() {
+            Some((prefix, _)) => prefix.len(),
+            None => return,
+        };
+        if pos < len {
+            // skip all remaining permutations of the second part
+            self.first.fail_pos(pos);
+            self.current = None;
+        } else if let Some((_, second)) = self.current.as_mut() {
+            second.fail_pos(pos - len);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.current = None;
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        if let Some((prefix, second)) = self.current.as_ref() {
+            if let Some(suffix) = second.peek() {
+                let mut permutation = prefix.clone();
+                permutation.extend(suffix);
+                return Some(permutation);
+            }
+        }
+        // the second part is exhausted, so the next permutation of the first part is followed by
+        // the first permutation of the second part.
+        let mut permutation = self.first.peek()?;
+        permutation.extend(B::new(self.second.clone()).peek()?);
+        Some(permutation)
+    }
+}
+
+impl<A, B, T> Iterator for ChainedPermutator<A, B, T>
+where
+    A: Permutator<T> + Iterator,
+    A::Item: PermutatorItem<T>,
+    B: Permutator<T> + Iterator,
+    B::Item: PermutatorItem<T>,
+    T: Clone,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((prefix, second)) = self.current.as_mut() {
+                if let Some(suffix) = second.next() {
+                    let mut permutation = prefix.clone();
+                    permutation.extend(suffix.as_patches());
+                    return Some(permutation);
+                }
+            }
+            // the second part is exhausted, continue with the next permutation of the first part.
+            let prefix = self.first.next()?.as_patches();
+            self.current = Some((prefix, B::new(self.second.clone())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::modifier_ordering::NoOrdering;
+    use crate::permutators::{HeapsPermutator, TreePermutator};
+
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Elems {
+        A,
+        B,
+        C,
+        D,
+        E,
+    }
+
+    use Elems::*;
+
+    type Tree = TreePermutator<NoOrdering, Elems>;
+    type CurrentPermutator = ChainedPermutator<Tree, Tree, Elems>;
+
+    #[test]
+    fn test_chained_0() {
+        let data: Vec<Elems> = vec![];
+        let permutations: Vec<Vec<Elems>> = CurrentPermutator::new(data).collect();
+        assert_eq!(permutations, vec![vec![]]);
+    }
+
+    #[test]
+    fn test_chained_1() {
+        let data: Vec<Elems> = vec![A];
+        let permutations: Vec<Vec<Elems>> = CurrentPermutator::new(data).collect();
+        assert_eq!(permutations, vec![vec![A]]);
+    }
+
+    #[test]
+    fn test_chained_3() {
+        let data: Vec<Elems> = vec![A, B, C];
+        let permutations: Vec<Vec<Elems>> = CurrentPermutator::new(data).collect();
+        assert_eq!(permutations, vec![vec![A, B, C], vec![A, C, B]]);
+    }
+
+    #[test]
+    fn test_chained_parts() {
+        let permutations: Vec<Vec<Elems>> =
+            CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]).collect();
+        assert_eq!(permutations.len(), 2 * 6);
+        let prefixes: Vec<Vec<Elems>> = Tree::new(vec![A, B]).collect();
+        let suffixes: Vec<Vec<Elems>> = Tree::new(vec![C, D, E]).collect();
+        let expected: Vec<Vec<Elems>> = prefixes
+            .iter()
+            .flat_map(|p| suffixes.iter().map(move |s| p.iter().chain(s.iter()).cloned().collect()))
+            .collect();
+        assert_eq!(permutations, expected);
+
+        // the parts can be permuted by different permutators
+        type Heaps = HeapsPermutator<NoOrdering, Elems>;
+        let permutations: Vec<Vec<Elems>> =
+            ChainedPermutator::<Heaps, Tree, Elems>::with_parts(vec![A, B], vec![C, D]).collect();
+        assert_eq!(
+            permutations,
+            vec![vec![A, B, C, D], vec![A, B, D, C], vec![B, A, C, D], vec![B, A, D, C]]
+        );
+    }
+
+    #[test]
+    fn test_chained_fail_pos() {
+        // failing within the second part only skips permutations of the second part
+        let mut permutator = CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]);
+        assert_eq!(permutator.next(), Some(vec![A, B, C, D, E]));
+        permutator.fail_pos(2);
+        assert_eq!(permutator.next(), Some(vec![A, B, D, C, E]));
+
+        // failing at the end of the first part skips all remaining permutations of the second part
+        let mut permutator = CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]);
+        assert_eq!(permutator.next(), Some(vec![A, B, C, D, E]));
+        permutator.fail_pos(1);
+        assert_eq!(permutator.next(), Some(vec![B, A, C, D, E]));
+
+        // failing within the first part is forwarded to the first permutator
+        let mut permutator = CurrentPermutator::with_parts(vec![A, B, C], vec![D, E]);
+        assert_eq!(permutator.next(), Some(vec![A, B, C, D, E]));
+        permutator.fail_pos(0);
+        assert_eq!(permutator.next(), Some(vec![B, A, C, D, E]));
+
+        // after the last permutation, nothing is left
+        let mut permutator = CurrentPermutator::with_parts(vec![A], vec![B]);
+        assert_eq!(permutator.next(), Some(vec![A, B]));
+        permutator.fail_pos(0);
+        assert_eq!(permutator.next(), None);
+    }
+
+    #[test]
+    fn test_chained_reset() {
+        let all: Vec<Vec<Elems>> =
+            CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]).collect();
+        let mut permutator = CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]);
+        permutator.next();
+        permutator.fail_pos(0);
+        permutator.next();
+        permutator.reset();
+        assert_eq!(permutator.collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn test_chained_peek() {
+        let mut permutator = CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]);
+        let mut count = 0;
+        while let Some(peeked) = permutator.peek() {
+            assert_eq!(permutator.next(), Some(peeked));
+            count += 1;
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(count, 2 * 6);
+
+        // peek after skipping the remaining permutations of the second part
+        let mut permutator = CurrentPermutator::with_parts(vec![A, B], vec![C, D, E]);
+        permutator.next();
+        permutator.fail_pos(1);
+        assert_eq!(permutator.peek(), Some(vec![B, A, C, D, E]));
+        assert_eq!(permutator.next(), Some(vec![B, A, C, D, E]));
+    }
+}
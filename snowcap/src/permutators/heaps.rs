@@ -31,6 +31,8 @@ use std::marker::PhantomData;
 ///
 /// The `HeapsPermutator` is only implemented for `ConfigModifier` and `usize`.
 pub struct HeapsPermutator<O = NoOrdering, T = ConfigModifier> {
+    /// The sorted input, needed to reset the permutator, since `data` is permuted in place
+    input: Vec<T>,
     data: Vec<T>,
     state: Vec<usize>,
     i: usize,
@@ -53,6 +55,7 @@ where
             state.push(0)
         }
         HeapsPermutator {
+            input: input.clone(),
             data: input,
             state,
             i: 0,
@@ -61,6 +64,27 @@ where
             ordering: PhantomData,
         }
     }
+
+    fn reset(&mut self) {
+        self.data = self.input.clone();
+        self.state.iter_mut().for_each(|s| *s = 0);
+        self.i = 0;
+        self.started = false;
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        // compute the next permutation on a copy, which does not need to be reset
+        let mut copy: Self = HeapsPermutator {
+            input: Vec::new(),
+            data: self.data.clone(),
+            state: self.state.clone(),
+            i: self.i,
+            len: self.len,
+            started: self.started,
+            ordering: PhantomData,
+        };
+        copy.next()
+    }
 }
 
 impl<O, T> Iterator for HeapsPermutator<O, T>
@@ -220,4 +244,27 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_heaps_reset() {
+        let data: Vec<usize> = vec![0, 1, 2, 3];
+        let all: Vec<Vec<usize>> =
+            HeapsPermutator::<NoOrdering, usize>::new(data.clone()).collect();
+        let mut permutator = HeapsPermutator::<NoOrdering, usize>::new(data);
+        permutator.nth(4);
+        permutator.reset();
+        assert_eq!(permutator.collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn test_heaps_peek() {
+        let mut permutator = HeapsPermutator::<NoOrdering, usize>::new(vec![0, 1, 2, 3]);
+        let mut count = 0;
+        while let Some(peeked) = permutator.peek() {
+            assert_eq!(permutator.next(), Some(peeked));
+            count += 1;
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(count, 24);
+    }
 }
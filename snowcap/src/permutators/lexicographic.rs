@@ -30,7 +30,14 @@ use std::marker::PhantomData;
 /// can be chosen using [`ModifierOrdering`](crate::modifier_ordering::ModifierOrdering),
 /// which must be a [`CompleteOrdering`](crate::modifier_ordering::CompleteOrdering).
 ///
-/// The `LexicographicPermutator` is only implemented for `ConfigModifier`.
+/// It yields the same sequence as the [`TreePermutator`](super::TreePermutator) with the same
+/// ordering, but it computes the next permutation in place, using the total order of `O`. Hence,
+/// it only stores the current permutation, while the `TreePermutator` keeps a vector of remaining
+/// choices for every position, which needs `O(n^2)` memory. `fail_pos` is supported by sorting the
+/// tail after `pos` in descending order, which makes the next call to `next` leave the failed
+/// prefix. Prefer the `LexicographicPermutator` for long sequences with a `CompleteOrdering`, and
+/// the `TreePermutator` if the ordering is not complete (e.g., `NoOrdering`), or if the indices of
+/// the current permutation are needed.
 pub struct LexicographicPermutator<O, T = ConfigModifier> {
     data: Vec<T>,
     len: usize,
@@ -55,6 +62,32 @@ where
         }
         LexicographicPermutator { data: input, len, start: true, ordering: PhantomData }
     }
+
+    /// Skip all permutations starting with the first `pos + 1` elements of the last permutation.
+    /// The elements after `pos` are sorted in descending order, which is the last permutation with
+    /// this prefix. Calling this function with `pos >= len - 2` does not skip anything.
+    fn fail_pos(&mut self, pos: usize) {
+        if self.start || pos + 1 >= self.len {
+            return;
+        }
+        self.data[(pos + 1)..].sort_by(|a, b| O::order(b, a));
+    }
+
+    fn reset(&mut self) {
+        O::sort(&mut self.data);
+        self.start = true;
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        // compute the next permutation on a copy
+        let mut copy: Self = LexicographicPermutator {
+            data: self.data.clone(),
+            len: self.len,
+            start: self.start,
+            ordering: PhantomData,
+        };
+        copy.next()
+    }
 }
 
 impl<O, T> Iterator for LexicographicPermutator<O, T>
@@ -116,6 +149,7 @@ mod test {
     use crate::modifier_ordering::SimpleOrdering;
     use crate::netsim::config::{ConfigExpr::BgpSession, ConfigModifier::*};
     use crate::netsim::BgpSessionType::EBgp;
+    use crate::permutators::TreePermutator;
 
     #[test]
     fn test_reverse_vec_part() {
@@ -200,4 +234,64 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_ordered_permutator_skip() {
+        let p1 = Insert(BgpSession { source: 1.into(), target: 1.into(), session_type: EBgp });
+        let p2 = Insert(BgpSession { source: 2.into(), target: 2.into(), session_type: EBgp });
+        let p3 = Insert(BgpSession { source: 3.into(), target: 3.into(), session_type: EBgp });
+        let p4 = Insert(BgpSession { source: 4.into(), target: 4.into(), session_type: EBgp });
+        let data = vec![p4.clone(), p3.clone(), p2.clone(), p1.clone()];
+        let mut permutator = LexicographicPermutator::<SimpleOrdering>::new(data);
+        let mut permutations: Vec<Vec<ConfigModifier>> = Vec::new();
+        permutations.push(permutator.next().unwrap());
+        permutator.fail_pos(0);
+        permutations.push(permutator.next().unwrap());
+        permutator.fail_pos(1);
+        permutations.push(permutator.next().unwrap());
+        permutator.fail_pos(2);
+        permutations.push(permutator.next().unwrap());
+        permutator.fail_pos(3);
+        permutations.push(permutator.next().unwrap());
+        assert_eq!(
+            permutations,
+            vec![
+                vec![p1.clone(), p2.clone(), p3.clone(), p4.clone()],
+                vec![p2.clone(), p1.clone(), p3.clone(), p4.clone()],
+                vec![p2.clone(), p3.clone(), p1.clone(), p4.clone()],
+                vec![p2.clone(), p3.clone(), p4.clone(), p1.clone()],
+                vec![p2.clone(), p4.clone(), p1.clone(), p3.clone()],
+            ]
+        );
+
+        permutator.reset();
+        assert_eq!(permutator.next(), Some(vec![p1, p2, p3, p4]));
+    }
+
+    #[test]
+    fn test_same_as_tree_permutator() {
+        let data: Vec<ConfigModifier> = (0..5)
+            .map(|i| Insert(BgpSession { source: i.into(), target: i.into(), session_type: EBgp }))
+            .rev()
+            .collect();
+        let mut lex = LexicographicPermutator::<SimpleOrdering>::new(data.clone());
+        let mut tree = TreePermutator::<SimpleOrdering>::new(data);
+        let mut i = 0;
+        loop {
+            let peeked = lex.peek();
+            assert_eq!(peeked, tree.peek());
+            let (a, b) = (lex.next(), tree.next());
+            assert_eq!(a, peeked);
+            assert_eq!(a, b);
+            if a.is_none() {
+                break;
+            }
+            // skip some prefixes in both permutators
+            if i % 7 == 3 {
+                lex.fail_pos(i % 4);
+                tree.fail_pos(i % 4);
+            }
+            i += 1;
+        }
+    }
 }
@@ -29,8 +29,9 @@
 //! - **[`LexicographicPermutator`]**: Simple permutator, which returns all permutations in a
 //!   lexicographic ordering. It is implemented for any type, which implements the `Copy` trait, and
 //!   requires a [`CompleteOrdering`](crate::modifier_ordering::ModifierOrdering) for the chosen
-//!   type. This permutator does not re-implement `fail_pos`, and thus does not make use of the
-//!   feedback functionality for permutators.
+//!   type. It yields the same sequence as the [`TreePermutator`], but computes the next
+//!   permutation in place, without storing the remaining choices for each position. It
+//!   re-implements `fail_pos` by sorting the tail after the failed position in descending order.
 //!
 //! - **[`SJTPermutator`]**: Permutator implementing the Steinhaus-Johnson-Trotter algorithm. It is
 //!   implemented for any type, which implements the `Copy` trait. This permutator does not
@@ -57,6 +58,12 @@
 //!   shuffled, every time a new branch in the tree is entered. As for the `TreePermutator`, this
 //!   permutator re-implements `fail_pos` to reduce the number of permutations for dependencies with
 //!   an *immediate effect*.
+//!
+//! - **[`ChainedPermutator`]**: This is a meta-permutator that combines two permutators, the first
+//!   one permuting the first part of the elements, and the second one permuting the remaining
+//!   elements. For every permutation of the first part, all permutations of the second part are
+//!   yielded. It re-implements `fail_pos` by forwarding it to the permutator of the respective
+//!   part.
 
 mod heaps;
 pub use heaps::HeapsPermutator;
@@ -76,6 +83,9 @@ pub use tree::TreePermutator;
 mod random_tree;
 pub use random_tree::RandomTreePermutator;
 
+mod chained;
+pub use chained::ChainedPermutator;
+
 /// Permutator trait
 pub trait Permutator<T>
 where
@@ -88,7 +98,21 @@ where
     /// This function may reduce the number of permutations, by skipping permutations which start
     /// the exact same way as the last call to `next`, up to the position `pos`. Not every
     /// permutator has this funciton implemented.
+    ///
+    /// The last permutation is the only one starting with its first `len - 1` elements. Hence,
+    /// `fail_pos(len - 2)` and `fail_pos(len - 1)` (as well as any larger `pos`) skip nothing, and
+    /// the next call to `next` returns the same permutation as without calling `fail_pos`.
     fn fail_pos(&mut self, _pos: usize) {}
+
+    /// Restart the iteration from the first permutation, forgetting all previous calls to
+    /// `fail_pos`. Afterwards, the permutator yields the same sequence as a new one created from
+    /// the same input. The only exception is the [`RandomTreePermutator`], which shuffles the input
+    /// again.
+    fn reset(&mut self);
+
+    /// Returns the permutation which the next call to `next` would return, without advancing the
+    /// permutator. `None` is returned if all permutations were already returned.
+    fn peek(&self) -> Option<Vec<T>>;
 }
 
 /// This is an empty trait to tell the compiler which types can be returned by the Permutator
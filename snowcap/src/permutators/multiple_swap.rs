@@ -40,11 +40,10 @@ pub struct MultipleSwapPermutator<P, T = ConfigModifier> {
     /// since for small `len`, the next prime number is larger than, or equal to `len - 1`, in which
     /// case it makes no sense to wait that long. `num` is always a prime number.
     num: usize,
-    /// The first iteration is always returned.
-    started: bool,
-    /// The first teration, which gets `None` at a point where we would like to return it, we know
-    /// that we have iterated over all combinations. At this point, we need to always return None.
-    finished: bool,
+    /// The permutation to return on the next call to `next`. It is computed one step ahead, such
+    /// that `peek` does not need to advance the permutator. Once it is `None`, we have iterated
+    /// over all combinations, and we need to always return None.
+    upcoming: Option<Vec<T>>,
 }
 
 impl<P, T> Permutator<T> for MultipleSwapPermutator<P, T>
@@ -55,36 +54,35 @@ where
 {
     fn new(input: Vec<T>) -> Self {
         let len = input.len();
+        let mut permutator = P::new(input.clone());
+        // The first iteration is always returned.
+        let upcoming = permutator.next().map(|p| p.as_patches());
         Self {
-            data: input.clone(),
-            permutator: P::new(input),
+            data: input,
+            permutator,
             num: if len <= 3 { 1 } else { Primes::all().find(|p| *p > len).unwrap() },
-            started: false,
-            finished: false,
+            upcoming,
         }
     }
+
+    fn reset(&mut self) {
+        self.permutator = P::new(self.data.clone());
+        self.upcoming = self.permutator.next().map(|p| p.as_patches());
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        self.upcoming.clone()
+    }
 }
 
-impl<P, T> Iterator for MultipleSwapPermutator<P, T>
+impl<P, T> MultipleSwapPermutator<P, T>
 where
     P: Permutator<T> + Iterator,
     P::Item: PermutatorItem<T>,
     T: Clone,
 {
-    type Item = P::Item;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        // handle the start
-        if !self.started {
-            self.started = true;
-            return self.permutator.next();
-        }
-
-        // if finished, always return None
-        if self.finished {
-            return None;
-        }
-
+    /// Computes the permutation after `upcoming`.
+    fn advance(&mut self) -> Option<Vec<T>> {
         // repeat for num - 1 times, to take the next, and rebuild the permutator if it fails
         for _ in 0..(self.num - 1) {
             if self.permutator.next().is_none() {
@@ -93,14 +91,24 @@ where
             }
         }
 
-        // now, take the permutator result, and check if it is now done.
-        match self.permutator.next() {
-            Some(elem) => Some(elem),
-            None => {
-                self.finished = true;
-                None
-            }
-        }
+        // now, take the permutator result. If it is `None`, the permutator is done.
+        self.permutator.next().map(|p| p.as_patches())
+    }
+}
+
+impl<P, T> Iterator for MultipleSwapPermutator<P, T>
+where
+    P: Permutator<T> + Iterator,
+    P::Item: PermutatorItem<T>,
+    T: Clone,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // if finished, always return None
+        let current = self.upcoming.take()?;
+        self.upcoming = self.advance();
+        Some(current)
     }
 }
 
@@ -204,4 +212,26 @@ mod test {
         assert_eq!(Primes::all().skip_while(|p| *p <= 4).next().unwrap(), 5);
         assert_eq!(Primes::all().skip_while(|p| *p <= 5).next().unwrap(), 7);
     }
+
+    #[test]
+    fn test_multiple_swap_reset() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        let all: Vec<Vec<Elems>> = CurrentPermutator::new(data.clone()).collect();
+        let mut permutator = CurrentPermutator::new(data);
+        while permutator.next().is_some() {}
+        permutator.reset();
+        assert_eq!(permutator.collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn test_multiple_swap_peek() {
+        let mut permutator = CurrentPermutator::new(vec![A, B, C, D]);
+        let mut count = 0;
+        while let Some(peeked) = permutator.peek() {
+            assert_eq!(permutator.next(), Some(peeked));
+            count += 1;
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(count, 24);
+    }
 }
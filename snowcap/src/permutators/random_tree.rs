@@ -24,7 +24,8 @@ use rand::prelude::*;
 /// # Random Tree Permutator
 ///
 /// The random tree permutator is similar to the [`TreePermutator`](super::TreePermutator), but
-/// with the difference that it is shuffled every time a new branch of the tree is entered.
+/// with the difference that it is shuffled every time a new branch of the tree is entered. `reset`
+/// shuffles the input again, so the permutations are yielded in a different order than before.
 pub struct RandomTreePermutator<T = ConfigModifier> {
     data: Vec<T>,
     state: Vec<usize>,
@@ -33,7 +34,9 @@ pub struct RandomTreePermutator<T = ConfigModifier> {
     remaining: Vec<Vec<usize>>,
     len: usize,
     started: bool,
-    rng: ThreadRng,
+    /// The generator is cloned to peek at the next permutation, which must continue with the same
+    /// random sequence as the original.
+    rng: StdRng,
 }
 
 impl<T> Permutator<T> for RandomTreePermutator<T>
@@ -41,7 +44,7 @@ where
     T: Clone,
 {
     fn new(mut input: Vec<T>) -> Self {
-        let mut rng = rand::thread_rng();
+        let mut rng = StdRng::from_entropy();
         // shuffle the input
         input.shuffle(&mut rng);
         let input_len = input.len();
@@ -59,6 +62,28 @@ where
             self.remaining[i].clear();
         }
     }
+
+    fn reset(&mut self) {
+        self.data.shuffle(&mut self.rng);
+        for i in 0..self.len {
+            self.state[i] = i;
+            self.remaining[i] = ((i + 1)..self.len).rev().collect();
+        }
+        self.started = false;
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        // compute the next permutation on a copy, using the same random generator state
+        let mut copy = Self {
+            data: self.data.clone(),
+            state: self.state.clone(),
+            remaining: self.remaining.clone(),
+            len: self.len,
+            started: self.started,
+            rng: self.rng.clone(),
+        };
+        copy.next()
+    }
 }
 
 impl<T> Iterator for RandomTreePermutator<T>
@@ -194,4 +219,30 @@ mod test {
         assert_eq!(permutations.len(), 18);
         assert!(permutations.iter().all(|p| p.len() == 4));
     }
+
+    #[test]
+    fn test_tree_reset() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        let mut permutator = CurrentPermutator::new(data);
+        permutator.next();
+        permutator.fail_pos(0);
+        permutator.next();
+        permutator.reset();
+        // the input is shuffled again, but all permutations are yielded exactly once
+        let permutations: Vec<Vec<Elems>> = permutator.collect();
+        assert_eq!(permutations.len(), 24);
+        assert!(permutations.iter().enumerate().all(|(i, p)| !permutations[..i].contains(p)));
+    }
+
+    #[test]
+    fn test_tree_peek() {
+        let mut permutator = CurrentPermutator::new(vec![A, B, C, D]);
+        let mut count = 0;
+        while let Some(peeked) = permutator.peek() {
+            assert_eq!(permutator.next(), Some(peeked));
+            count += 1;
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(count, 24);
+    }
 }
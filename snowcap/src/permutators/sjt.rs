@@ -47,23 +47,48 @@ where
     fn new(mut input: Vec<T>) -> Self {
         O::sort(&mut input);
         let len = input.len();
-        let mut dirs: Vec<Direction> = Vec::with_capacity(len);
-        for i in 0..len {
-            if i == 0 {
-                dirs.push(Direction::None);
-            } else {
-                dirs.push(Direction::Left);
-            }
-        }
         Self {
             data: input,
             indices: (0..len).collect(),
-            dirs,
+            dirs: initial_dirs(len),
             len,
             started: false,
             phantom: PhantomData,
         }
     }
+
+    fn reset(&mut self) {
+        self.indices = (0..self.len).collect();
+        self.dirs = initial_dirs(self.len);
+        self.started = false;
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        // compute the next permutation on a copy
+        let mut copy: Self = Self {
+            data: self.data.clone(),
+            indices: self.indices.clone(),
+            dirs: self.dirs.clone(),
+            len: self.len,
+            started: self.started,
+            phantom: PhantomData,
+        };
+        copy.next()
+    }
+}
+
+/// Returns the directions of the first permutation, where all elements but the first one point to
+/// the left.
+fn initial_dirs(len: usize) -> Vec<Direction> {
+    let mut dirs: Vec<Direction> = Vec::with_capacity(len);
+    for i in 0..len {
+        if i == 0 {
+            dirs.push(Direction::None);
+        } else {
+            dirs.push(Direction::Left);
+        }
+    }
+    dirs
 }
 
 impl<O, T> Iterator for SJTPermutator<O, T>
@@ -251,4 +276,26 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_sjt_reset() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        let all: Vec<Vec<Elems>> = CurrentPermutator::new(data.clone()).collect();
+        let mut permutator = CurrentPermutator::new(data);
+        permutator.nth(4);
+        permutator.reset();
+        assert_eq!(permutator.collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn test_sjt_peek() {
+        let mut permutator = CurrentPermutator::new(vec![A, B, C, D]);
+        let mut count = 0;
+        while let Some(peeked) = permutator.peek() {
+            assert_eq!(permutator.next(), Some(peeked));
+            count += 1;
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(count, 24);
+    }
 }
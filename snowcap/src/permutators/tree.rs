@@ -69,41 +69,150 @@ where
         }
     }
 
+    /// Skip all permutations starting with the first `pos + 1` elements of the last permutation, by
+    /// clearing all remaining choices after `pos`. The last position never has any remaining
+    /// choice, so calling this function with `pos >= len - 2` does not skip anything.
     fn fail_pos(&mut self, pos: usize) {
         for i in (pos + 1)..self.len {
             self.remaining[i].clear();
         }
     }
+
+    fn reset(&mut self) {
+        for i in 0..self.len {
+            self.state[i] = i;
+            self.remaining[i] = ((i + 1)..self.len).rev().collect();
+        }
+        self.started = false;
+    }
+
+    fn peek(&self) -> Option<Vec<T>> {
+        let state = if self.started { self.next_state()?.1 } else { self.state.clone() };
+        Some(state.into_iter().map(|idx| self.data[idx].clone()).collect())
+    }
 }
 
-impl<O, T> Iterator for TreePermutator<O, T>
+impl<O, T> TreePermutator<O, T>
 where
-    T: Clone,
+    O: ModifierOrdering<T>,
 {
-    type Item = Vec<T>;
-    fn next(&mut self) -> Option<Self::Item> {
+    /// Creates a new permutator, whose first permutation is `start` instead of the sorted one.
+    /// Afterwards, the iteration continues in lexicographic order from `start`, so all
+    /// permutations before `start` are never returned. The indices in `start` refer to the input
+    /// after it has been sorted by the ordering (see [`TreePermutator::data`]).
+    ///
+    /// # Panics
+    /// Panics if `start` is not a permutation of the indices `0..input.len()`.
+    pub fn starting_from(mut input: Vec<T>, start: &[usize]) -> Self {
+        let input_len = input.len();
+        let mut seen = vec![false; input_len];
+        assert_eq!(start.len(), input_len, "The start must contain every index exactly once!");
+        for &idx in start {
+            assert!(
+                idx < input_len && !seen[idx],
+                "The start must contain every index exactly once!"
+            );
+            seen[idx] = true;
+        }
+        // sort the input after the given ordering
+        O::sort(&mut input);
+        TreePermutator {
+            data: input,
+            state: start.to_vec(),
+            remaining: remaining_after(start),
+            len: input_len,
+            started: false,
+            ordering: PhantomData,
+        }
+    }
+}
+
+impl<O, T> TreePermutator<O, T> {
+    /// Restart the iteration at the permutation with index `n`, such that the next call to `next`
+    /// returns the same permutation as `nth(n)` on a new permutator. The permutation is computed
+    /// directly from the factorial number system representation of `n`, without iterating over
+    /// the permutations before it. All previous calls to `fail_pos` are forgotten. If `n` is
+    /// larger than or equal to the number of permutations, the permutator is exhausted.
+    ///
+    /// This can be used to split the permutations into chunks, each explored separately.
+    pub fn skip_to(&mut self, n: u128) {
+        if n >= factorial(self.len) {
+            self.remaining.iter_mut().for_each(|rem| rem.clear());
+            self.started = true;
+            return;
+        }
+        // indices not yet placed, in ascending order
+        let mut available: Vec<usize> = (0..self.len).collect();
+        let mut n = n;
+        for pos in 0..self.len {
+            let weight = factorial(self.len - pos - 1);
+            let digit = (n / weight) as usize;
+            n %= weight;
+            self.state[pos] = available.remove(digit);
+        }
+        self.remaining = remaining_after(&self.state);
+        self.started = false;
+    }
+
+    /// Returns the number of permutations which will still be returned by `next`, taking into
+    /// account all previous calls to `fail_pos`. For every position, each remaining choice leads
+    /// to a subtree containing all permutations of the elements after this position. If the number
+    /// is too large, `u128::MAX` is returned.
+    pub fn remaining_count(&self) -> u128 {
+        // the current state is still to be returned if the iteration has not yet started.
+        let current = if self.started { 0 } else { 1 };
+        self.remaining
+            .iter()
+            .enumerate()
+            .map(|(pos, rem)| (rem.len() as u128).saturating_mul(factorial(self.len - pos - 1)))
+            .fold(current, |acc, x| acc.saturating_add(x))
+    }
+
+    /// Returns the data, sorted by the ordering. The indices returned by
+    /// [`TreePermutator::next_indices`] refer to this slice.
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Advances to the next permutation (like `next`), but returns only the indices into
+    /// [`TreePermutator::data`], without cloning the elements.
+    pub fn next_indices(&mut self) -> Option<&[usize]> {
         // handle the first value
         if !self.started {
             self.started = true;
-            return Some(
-                self.state.iter().map(|idx| self.data.get(*idx).unwrap()).cloned().collect(),
-            );
+            return Some(&self.state);
+        }
+        let (change_pos, next_state) = self.next_state()?;
+
+        // the element at change_pos is now used, so remove it from the remaining choices. For all
+        // positions further down in the tree, the remaining choices are all elements after this
+        // position (in reverse order, since they are sorted in ascending order).
+        self.remaining[change_pos].pop();
+        for pos in (change_pos + 1)..self.len {
+            self.remaining[pos] = next_state[(pos + 1)..].iter().rev().cloned().collect();
         }
+        self.state = next_state;
+
+        Some(&self.state)
+    }
+
+    /// Computes the indices of the permutation which the next call to `next` would return, without
+    /// modifying the permutator. It returns the first position which changes compared to the
+    /// current permutation, and the new permutation. If there is no next permutation, `None` is
+    /// returned. This function must only be called after the iteration has started.
+    fn next_state(&self) -> Option<(usize, Vec<usize>)> {
         // go from the back of the remaining array, and get the first position where there is still
         // something remaining
-        let change_pos =
-            match self.remaining.iter().enumerate().rev().find(|(_, rem)| !rem.is_empty()) {
-                Some((pos, _)) => pos,
-                None => return None, // when nothing was found, we have tried all permutations
-            };
+        let change_pos = self.remaining.iter().rposition(|rem| !rem.is_empty())?;
 
         // build the new remaining vector for the positions further down in the tree by collecting
         // all elements from change_pos + 1
         let mut working_rem: Vec<usize> = self.state.iter().skip(change_pos).cloned().collect();
 
         // change the state of the change_pos to have the next required element
-        let new_element = self.remaining[change_pos].pop().unwrap();
-        self.state[change_pos] = new_element;
+        let new_element = *self.remaining[change_pos].last().unwrap();
+        let mut state = self.state.clone();
+        state[change_pos] = new_element;
 
         // remove the new_element from the working_rem
         working_rem.remove(working_rem.iter().position(|x| *x == new_element).unwrap());
@@ -114,12 +223,42 @@ where
 
         // Now, we have working_rem as all elements which can still be chosen by any of the next
         // positions. Thus, we build the next elements up iteratively:
-        for pos in (change_pos + 1)..self.len {
-            self.state[pos] = working_rem.pop().unwrap();
-            self.remaining[pos] = working_rem.clone();
+        for elem in state.iter_mut().skip(change_pos + 1) {
+            *elem = working_rem.pop().unwrap();
         }
 
-        Some(self.state.iter().map(|idx| self.data.get(*idx).unwrap()).cloned().collect())
+        Some((change_pos, state))
+    }
+}
+
+/// Computes the remaining choices at every position of the permutation `state`, when all
+/// permutations before `state` were already explored. At each position, the remaining choices are
+/// all elements after this position, which are larger than the current one (since the smaller ones
+/// were already explored). They are stored in reverse order.
+fn remaining_after(state: &[usize]) -> Vec<Vec<usize>> {
+    (0..state.len())
+        .map(|i| {
+            let mut rem: Vec<usize> =
+                state[(i + 1)..].iter().cloned().filter(|x| *x > state[i]).collect();
+            rem.sort_by_key(|&b| Reverse(b));
+            rem
+        })
+        .collect()
+}
+
+/// Computes `n!`, saturating at `u128::MAX`.
+fn factorial(n: usize) -> u128 {
+    (1..=n as u128).fold(1u128, |acc, x| acc.saturating_mul(x))
+}
+
+impl<O, T> Iterator for TreePermutator<O, T>
+where
+    T: Clone,
+{
+    type Item = Vec<T>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_indices()?;
+        Some(self.state.iter().map(|idx| self.data[*idx].clone()).collect())
     }
 }
 
@@ -249,4 +388,159 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn test_tree_fail_last_pos() {
+        let data: Vec<Elems> = vec![A, B, C];
+        let all: Vec<Vec<Elems>> = CurrentPermutator::new(data.clone()).collect();
+        for pos in 1..4 {
+            // failing at the last two positions (or beyond) must not skip anything
+            let mut permutator = CurrentPermutator::new(data.clone());
+            let mut permutations: Vec<Vec<Elems>> = Vec::new();
+            while let Some(p) = permutator.next() {
+                permutations.push(p);
+                permutator.fail_pos(pos);
+            }
+            assert_eq!(permutations, all);
+        }
+        // failing at the last position after every permutation but the last one still advances the
+        // most significant position as soon as all its suffixes are exhausted.
+        let mut permutator = CurrentPermutator::new(data);
+        assert_eq!(permutator.next(), Some(vec![A, B, C]));
+        permutator.fail_pos(2);
+        assert_eq!(permutator.next(), Some(vec![A, C, B]));
+        permutator.fail_pos(2);
+        assert_eq!(permutator.next(), Some(vec![B, A, C]));
+    }
+
+    #[test]
+    fn test_tree_remaining_count() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        let mut permutator = CurrentPermutator::new(data.clone());
+        for count in (0..=24).rev() {
+            assert_eq!(permutator.remaining_count(), count);
+            permutator.next();
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(permutator.remaining_count(), 0);
+
+        let mut permutator = CurrentPermutator::new(data);
+        // ABCD
+        permutator.next();
+        // skip the remaining 5 permutations starting with A
+        permutator.fail_pos(0);
+        assert_eq!(permutator.remaining_count(), 18);
+        // BACD
+        permutator.next();
+        // skip BADC
+        permutator.fail_pos(1);
+        assert_eq!(permutator.remaining_count(), 16);
+        let rest: Vec<Vec<Elems>> = permutator.collect();
+        assert_eq!(rest.len(), 16);
+    }
+
+    #[test]
+    fn test_tree_remaining_count_saturates() {
+        assert_eq!(factorial(4), 24);
+        assert_eq!(factorial(34), 295232799039604140847618609643520000000);
+        assert_eq!(factorial(35), u128::MAX);
+        let permutator = TreePermutator::<NoOrdering, usize>::new((0..40).collect());
+        assert_eq!(permutator.remaining_count(), u128::MAX);
+    }
+
+    #[test]
+    fn test_tree_next_indices() {
+        let data: Vec<Elems> = vec![A, B, C];
+        let mut permutator = CurrentPermutator::new(data.clone());
+        let mut permutations: Vec<Vec<Elems>> = Vec::new();
+        while let Some(indices) = permutator.next_indices() {
+            let indices = indices.to_vec();
+            permutations.push(indices.iter().map(|i| permutator.data()[*i]).collect());
+        }
+        assert_eq!(permutations, CurrentPermutator::new(data).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_tree_reset() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        let all: Vec<Vec<Elems>> = CurrentPermutator::new(data.clone()).collect();
+        let mut permutator = CurrentPermutator::new(data);
+        permutator.next();
+        permutator.fail_pos(0);
+        permutator.next();
+        permutator.reset();
+        assert_eq!(permutator.remaining_count(), 24);
+        assert_eq!(permutator.collect::<Vec<_>>(), all);
+    }
+
+    #[test]
+    fn test_tree_starting_from() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        let all: Vec<Vec<Elems>> = CurrentPermutator::new(data.clone()).collect();
+        for (i, first) in all.iter().enumerate() {
+            let start: Vec<usize> = first.iter().map(|x| *x as usize).collect();
+            let mut permutator = CurrentPermutator::starting_from(data.clone(), &start);
+            assert_eq!(permutator.remaining_count(), (24 - i) as u128);
+            assert_eq!(permutator.collect::<Vec<_>>(), all[i..].to_vec());
+        }
+        // fail_pos works as usual on a warm-started permutator
+        let mut permutator = CurrentPermutator::starting_from(data, &[1, 2, 0, 3]);
+        assert_eq!(permutator.next(), Some(vec![B, C, A, D]));
+        permutator.fail_pos(1);
+        assert_eq!(permutator.next(), Some(vec![B, D, A, C]));
+    }
+
+    #[test]
+    fn test_tree_skip_to() {
+        let data: Vec<Elems> = vec![A, B, C, D];
+        for n in 0..25 {
+            let mut permutator = CurrentPermutator::new(data.clone());
+            permutator.skip_to(n);
+            assert_eq!(permutator.remaining_count(), 24u128.saturating_sub(n));
+            let mut naive = CurrentPermutator::new(data.clone());
+            let expected: Vec<Vec<Elems>> =
+                naive.nth(n as usize).into_iter().chain(naive).collect();
+            assert_eq!(permutator.collect::<Vec<_>>(), expected);
+        }
+        // skipping forgets the previous state, including previous calls to fail_pos
+        let mut permutator = CurrentPermutator::new(data);
+        permutator.next();
+        permutator.fail_pos(0);
+        permutator.skip_to(3);
+        assert_eq!(permutator.next(), Some(vec![A, C, D, B]));
+        assert_eq!(permutator.next(), Some(vec![A, D, B, C]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tree_starting_from_duplicate() {
+        CurrentPermutator::starting_from(vec![A, B, C], &[0, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tree_starting_from_wrong_len() {
+        CurrentPermutator::starting_from(vec![A, B, C], &[0, 1]);
+    }
+
+    #[test]
+    fn test_tree_peek() {
+        let mut permutator = CurrentPermutator::new(vec![A, B, C]);
+        let mut permutations: Vec<Vec<Elems>> = Vec::new();
+        while let Some(peeked) = permutator.peek() {
+            let next = permutator.next().unwrap();
+            assert_eq!(peeked, next);
+            permutations.push(next);
+        }
+        assert_eq!(permutator.next(), None);
+        assert_eq!(permutations.len(), 6);
+
+        // peek after skipping some permutations
+        let mut permutator = CurrentPermutator::new(vec![A, B, C, D]);
+        permutator.next();
+        permutator.fail_pos(0);
+        assert_eq!(permutator.peek(), Some(vec![B, A, C, D]));
+        assert_eq!(permutator.peek(), Some(vec![B, A, C, D]));
+        assert_eq!(permutator.next(), Some(vec![B, A, C, D]));
+    }
 }
@@ -0,0 +1,213 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+//! # Plan Artifacts
+//!
+//! Utilities to export a synthesized ordering of configuration modifiers as an artifact for
+//! change-management audit trails. The fingerprint of a plan only depends on the modifiers and on
+//! their order, and is stable across runs and builds, which allows operators to prove which plan
+//! was generated from which input.
+//!
+//! The fingerprint is computed with the 64-bit FNV-1a hash. It detects accidental changes, but it
+//! is *not* a cryptographic signature.
+//!
+//! ## Stable Format
+//!
+//! The fingerprint is computed over the JSON serialization of each modifier (the same form as
+//! written by [`ordering_to_json`]), where every modifier is followed by a zero byte. This
+//! encoding only depends on the data model of the modifiers, and not on their `Debug`
+//! representation. Hence, the fingerprint only changes if the data model of the modifiers changes.
+//! This module requires the `serde` feature.
+
+use crate::netsim::config::ConfigModifier;
+use crate::Error;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Compute a stable fingerprint of an ordering of modifiers, as a hexadecimal string of 16
+/// characters. Two orderings have the same fingerprint if they contain the same modifiers in the
+/// same order. See the [module documentation](self) for the encoding which is hashed.
+pub fn plan_fingerprint(ordering: &[ConfigModifier]) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+    for modifier in ordering {
+        let encoded =
+            serde_json::to_vec(modifier).expect("ConfigModifier can always be serialized");
+        // separate the modifiers, such that the boundaries are part of the fingerprint
+        for byte in encoded.into_iter().chain(std::iter::once(0u8)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Artifact of a synthesized plan, bundling the ordering, the fingerprint of the input, the
+/// fingerprint of the plan, and a set of named metrics (like the cost or the number of explored
+/// states).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PlanArtifact {
+    /// Ordering of the modifiers, as returned by the synthesis
+    pub ordering: Vec<ConfigModifier>,
+    /// Fingerprint of the input modifiers (in the order in which they were given to the synthesis)
+    pub input_fingerprint: String,
+    /// Fingerprint of the ordering
+    pub plan_fingerprint: String,
+    /// Named metrics of the synthesis
+    #[serde(deserialize_with = "deserialize_metrics")]
+    pub metrics: Vec<(String, f64)>,
+}
+
+impl PlanArtifact {
+    /// Create a new artifact from the input modifiers, and the synthesized ordering. Metrics can be
+    /// added with [`PlanArtifact::with_metric`].
+    pub fn new(input: &[ConfigModifier], ordering: Vec<ConfigModifier>) -> Self {
+        Self {
+            input_fingerprint: plan_fingerprint(input),
+            plan_fingerprint: plan_fingerprint(&ordering),
+            ordering,
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Add a named metric to the artifact.
+    pub fn with_metric(mut self, name: impl Into<String>, value: f64) -> Self {
+        self.metrics.push((name.into(), value));
+        self
+    }
+
+    /// Serialize the artifact as a JSON object. The modifiers are stored as structured values (in
+    /// the same form as written by [`ordering_to_json`]), such that the artifact can be loaded
+    /// again with [`PlanArtifact::from_json`]. Metrics which are not finite are stored as `null`, and
+    /// loaded as `NaN`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("PlanArtifact can always be serialized")
+    }
+
+    /// Load an artifact, which was written by [`PlanArtifact::to_json`]. If the string is no valid
+    /// artifact, `Error::InvalidInput` is returned.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|e| Error::InvalidInput(e.to_string()))
+    }
+}
+
+/// Deserialize the metrics of a [`PlanArtifact`], where `null` (written for metrics which are not
+/// finite) is loaded as `NaN`.
+fn deserialize_metrics<'de, D>(deserializer: D) -> Result<Vec<(String, f64)>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let metrics: Vec<(String, Option<f64>)> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(metrics.into_iter().map(|(name, value)| (name, value.unwrap_or(f64::NAN))).collect())
+}
+
+/// Serialize an ordering as a JSON array of modifiers. The ordering is given as a sequence of group
+/// indices, and all modifiers of each group are written in the order of the sequence. The
+/// ordering can be loaded again with [`ordering_from_json`].
+///
+/// # Panics
+/// Panics if `sequence` contains an index which is out of bounds for `groups`.
+pub fn ordering_to_json(groups: &[Vec<ConfigModifier>], sequence: &[usize]) -> String {
+    let ordering: Vec<&ConfigModifier> = sequence.iter().flat_map(|g| groups[*g].iter()).collect();
+    serde_json::to_string(&ordering).expect("ConfigModifier can always be serialized")
+}
+
+/// Load an ordering of modifiers, which was written by [`ordering_to_json`]. If the string is no
+/// valid ordering, `Error::InvalidInput` is returned.
+pub fn ordering_from_json(json: &str) -> Result<Vec<ConfigModifier>, Error> {
+    serde_json::from_str(json).map_err(|e| Error::InvalidInput(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::netsim::config::ConfigExpr::*;
+    use crate::netsim::config::ConfigModifier::*;
+    use crate::netsim::BgpSessionType::*;
+
+    fn modifiers() -> Vec<ConfigModifier> {
+        vec![
+            Insert(BgpSession { source: 0.into(), target: 1.into(), session_type: IBgpPeer }),
+            Remove(BgpSession { source: 1.into(), target: 2.into(), session_type: IBgpPeer }),
+            Insert(IgpLinkWeight { source: 0.into(), target: 2.into(), weight: 1.0 }),
+        ]
+    }
+
+    #[test]
+    fn fingerprint_stable() {
+        assert_eq!(plan_fingerprint(&modifiers()), plan_fingerprint(&modifiers()));
+        let a = PlanArtifact::new(&modifiers(), modifiers()).with_metric("cost", 1.5);
+        let b = PlanArtifact::new(&modifiers(), modifiers()).with_metric("cost", 1.5);
+        assert_eq!(a, b);
+        assert_eq!(a.to_json(), b.to_json());
+        assert_eq!(a.plan_fingerprint.len(), 16);
+        // the fingerprint must not change across builds
+        assert_eq!(a.plan_fingerprint, "4406c707dcebc948");
+    }
+
+    #[test]
+    fn fingerprint_changes() {
+        let original = plan_fingerprint(&modifiers());
+
+        // different order
+        let mut reordered = modifiers();
+        reordered.swap(0, 1);
+        assert_ne!(original, plan_fingerprint(&reordered));
+
+        // different modifier
+        let mut changed = modifiers();
+        changed[2] = Insert(IgpLinkWeight { source: 0.into(), target: 2.into(), weight: 2.0 });
+        assert_ne!(original, plan_fingerprint(&changed));
+
+        // missing modifier
+        assert_ne!(original, plan_fingerprint(&modifiers()[..2]));
+    }
+
+    #[test]
+    fn artifact_json() {
+        let artifact = PlanArtifact::new(&modifiers(), modifiers()).with_metric("cost", 2.0);
+        let json = artifact.to_json();
+        assert!(json.contains(&format!("\"plan_fingerprint\":\"{}\"", artifact.plan_fingerprint)));
+        assert_eq!(PlanArtifact::from_json(&json).unwrap(), artifact);
+
+        // the ordering of the artifact is stored in the same form as by `ordering_to_json`
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let ordering = ordering_from_json(&value["ordering"].to_string()).unwrap();
+        assert_eq!(ordering, modifiers());
+        assert!(PlanArtifact::from_json("{\"ordering\": []}").is_err());
+
+        // metrics which are not finite are loaded as NaN
+        let artifact = artifact.with_metric("ratio", f64::INFINITY);
+        let loaded = PlanArtifact::from_json(&artifact.to_json()).unwrap();
+        assert_eq!(loaded.metrics[0], ("cost".to_string(), 2.0));
+        assert!(loaded.metrics[1].1.is_nan());
+    }
+
+    #[test]
+    fn ordering_json_roundtrip() {
+        let groups: Vec<Vec<ConfigModifier>> =
+            vec![modifiers()[..2].to_vec(), modifiers()[2..].to_vec()];
+        let json = ordering_to_json(&groups, &[1, 0]);
+        let ordering = ordering_from_json(&json).unwrap();
+        assert_eq!(
+            ordering,
+            vec![modifiers()[2].clone(), modifiers()[0].clone(), modifiers()[1].clone()]
+        );
+        assert_eq!(ordering[0].routers(), vec![0.into(), 2.into()]);
+        assert!(ordering_from_json("[{\"Insert\": 1}]").is_err());
+    }
+}
@@ -123,6 +123,9 @@
 //!
 //! - **[`NaiveRandomIBRStrategy`]**: This strategy is similar to the random strategy, but it always
 //!   schedules insert before modify before remove commands.
+//!
+//! - **[`SolverCallLimited`]**: This is not a strategy on its own, but a wrapper around any other
+//!   strategy, which limits the number of calls to the external solver (for fair benchmarks).
 
 mod permutation;
 pub use permutation::PermutationStrategy;
@@ -139,17 +142,30 @@ pub use naive_random::NaiveRandomStrategy;
 mod naive_random_ibr;
 pub use naive_random_ibr::NaiveRandomIBRStrategy;
 
+mod solver_call_limited;
+pub use solver_call_limited::SolverCallLimited;
+
 // dep_pairs_builder is very bad! Therefore, we do not re-export the name!
 //mod dep_pairs_builder;
 //pub use dep_pairs_builder::DepPairsBuilder;
 
 // the DepGroupsStrategy is in a different module. Just re-export it from here
+pub use crate::dep_groups::ltl::policy_error_to_ltl;
+pub use crate::dep_groups::ordering_cost::{total_cost, OrderingCost, TransientLoopCost};
+pub use crate::dep_groups::roles::RoleDependency;
+pub use crate::dep_groups::solver::{
+    AaltaSession, AaltafSolver, LtlSolver, MockLtlSolver, RecordedSolver, SolverResult,
+    AALTAF_PATH_ENV,
+};
 pub use crate::dep_groups::strategy::DepGroupsStrategy;
-pub use crate::dep_groups::strategy_trta::StrategyTRTA;
+pub use crate::dep_groups::strategy_trta::{
+    CandidateEvaluation, ConflictRecord, ConflictSelection, SolverErrorKind, SolverExchange,
+    StrategyTRTA, TrtaStats,
+};
 
 use crate::hard_policies::HardPolicy;
 use crate::netsim::config::{Config, ConfigModifier};
-use crate::netsim::Network;
+use crate::netsim::{ForwardingState, Network};
 use crate::{Error, Stopper};
 
 use std::time::Duration;
@@ -197,6 +213,40 @@ pub trait Strategy {
     /// Main function to find a valid reconfiguration sequence (if it exists) and return it.
     /// The function also returns the number of sequences that were tested.
     fn work(&mut self, abort: Stopper) -> Result<Vec<ConfigModifier>, Error>;
+
+    /// Find a valid reconfiguration sequence (like [`Strategy::work`]), and also return the
+    /// forwarding state after each modifier of the sequence. The forwarding states are computed by
+    /// re-applying the sequence to the network returned by [`Strategy::initial_network`]. If the
+    /// strategy does not provide the initial network, `Error::Internal` is returned.
+    fn work_with_trace(
+        &mut self,
+        abort: Stopper,
+    ) -> Result<(Vec<ConfigModifier>, Vec<ForwardingState>), Error> {
+        let ordering = self.work(abort)?;
+        let net = self.initial_network().ok_or_else(|| {
+            Error::Internal("The strategy does not provide the initial network".to_string())
+        })?;
+        let trace = forwarding_trace(net, &ordering)?;
+        Ok((ordering, trace))
+    }
+
+    /// Returns the network before any modifier was applied, if the strategy keeps it around. This
+    /// is used by [`Strategy::work_with_trace`]. By default, `None` is returned.
+    fn initial_network(&self) -> Option<&Network> {
+        None
+    }
+
+    /// Returns the number of calls to an external solver during the last call to
+    /// [`Strategy::work`]. By default, strategies do not use any solver, and 0 is returned.
+    fn num_solver_calls(&self) -> usize {
+        0
+    }
+
+    /// Limit the number of calls to an external solver during [`Strategy::work`]. If the limit is
+    /// reached, `work` returns `Error::SolverCallLimitExceeded`. `None` removes the limit. By
+    /// default, strategies do not use any solver, and the limit is ignored.
+    fn set_max_solver_calls(&mut self, _max_calls: Option<usize>) {}
+
     /// Returns the number of states explored by the strategy.
     ///
     /// *This method is only available if the `"count-states"` feature is enabled!*
@@ -204,6 +254,21 @@ pub trait Strategy {
     fn num_states(&self) -> usize;
 }
 
+/// Apply the ordering to a copy of the network, and return the forwarding state after each step.
+pub(crate) fn forwarding_trace(
+    net: &Network,
+    ordering: &[ConfigModifier],
+) -> Result<Vec<ForwardingState>, Error> {
+    let mut net = net.clone();
+    ordering
+        .iter()
+        .map(|modifier| {
+            net.apply_modifier(modifier)?;
+            Ok(net.get_forwarding_state())
+        })
+        .collect()
+}
+
 /// Trait for a strategy being able to solve groups of modifiers
 pub trait GroupStrategy: Strategy {
     /// Generate a GroupStrategy from a nested vector of ConfigModifiers.
@@ -81,7 +81,7 @@ impl Strategy for NaiveRandomIBRStrategy {
         let mut sequence_update = self
             .modifiers
             .iter()
-            .filter(|m| matches!(m, ConfigModifier::Update{..}))
+            .filter(|m| matches!(m, ConfigModifier::Update { .. }))
             .cloned()
             .collect::<Vec<_>>();
         let mut sequence_remove = self
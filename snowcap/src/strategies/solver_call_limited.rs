@@ -0,0 +1,155 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+use super::Strategy;
+use crate::hard_policies::HardPolicy;
+use crate::netsim::{config::ConfigModifier, Network};
+use crate::{Error, Stopper};
+
+use std::time::Duration;
+
+/// # Solver Call Limited Strategy
+///
+/// Wrapper around any other strategy, which limits the number of calls to the external solver (see
+/// [`Strategy::num_solver_calls`]), regardless of the time budget. This allows comparing different
+/// strategies fairly. If the limit is reached, `work` returns `Error::SolverCallLimitExceeded`.
+/// The limit is passed to the inner strategy with [`Strategy::set_max_solver_calls`], such that
+/// it can stop as soon as the limit is reached. Otherwise, the number of calls is checked after
+/// `work` has finished.
+///
+/// When created with [`Strategy::new`], the number of solver calls is unlimited. Use
+/// [`SolverCallLimited::with_limit`] or [`Strategy::set_max_solver_calls`] to set the limit.
+pub struct SolverCallLimited<S> {
+    inner: S,
+    max_calls: usize,
+}
+
+impl<S> SolverCallLimited<S>
+where
+    S: Strategy,
+{
+    /// Wrap the strategy, and limit the number of solver calls to `max_calls`.
+    pub fn with_limit(inner: S, max_calls: usize) -> Self {
+        Self { inner, max_calls }
+    }
+
+    /// Returns a reference to the inner strategy.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner strategy.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+}
+
+impl<S> Strategy for SolverCallLimited<S>
+where
+    S: Strategy,
+{
+    fn new(
+        net: Network,
+        modifiers: Vec<ConfigModifier>,
+        hard_policy: HardPolicy,
+        time_budget: Option<Duration>,
+    ) -> Result<Box<Self>, Error> {
+        let inner = S::new(net, modifiers, hard_policy, time_budget)?;
+        Ok(Box::new(Self::with_limit(*inner, usize::MAX)))
+    }
+
+    fn work(&mut self, abort: Stopper) -> Result<Vec<ConfigModifier>, Error> {
+        self.inner.set_max_solver_calls(Some(self.max_calls));
+        let result = self.inner.work(abort)?;
+        if self.inner.num_solver_calls() > self.max_calls {
+            return Err(Error::SolverCallLimitExceeded(self.max_calls));
+        }
+        Ok(result)
+    }
+
+    fn initial_network(&self) -> Option<&Network> {
+        self.inner.initial_network()
+    }
+
+    fn num_solver_calls(&self) -> usize {
+        self.inner.num_solver_calls()
+    }
+
+    fn set_max_solver_calls(&mut self, max_calls: Option<usize>) {
+        self.max_calls = max_calls.unwrap_or(usize::MAX);
+    }
+
+    #[cfg(feature = "count-states")]
+    fn num_states(&self) -> usize {
+        self.inner.num_states()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::example_networks::{ExampleNetwork, SimpleNet};
+    use crate::modifier_ordering::SimpleOrdering;
+    use crate::strategies::PushBackTreeStrategy;
+
+    #[test]
+    fn no_solver() {
+        let net = SimpleNet::net(0);
+        let final_config = SimpleNet::final_config(&net, 0);
+        let modifiers = net.current_config().get_diff(&final_config).modifiers;
+        let hard_policy =
+            HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+
+        // strategies without a solver are not affected by the limit
+        let mut strategy = SolverCallLimited::<PushBackTreeStrategy<SimpleOrdering>>::new(
+            net,
+            modifiers,
+            hard_policy,
+            None,
+        )
+        .unwrap();
+        strategy.set_max_solver_calls(Some(0));
+        assert!(strategy.work(Stopper::new()).is_ok());
+        assert_eq!(strategy.num_solver_calls(), 0);
+    }
+
+    #[cfg(feature = "ltl-solver")]
+    #[test]
+    fn solver_call_limit() {
+        use crate::strategies::{RecordedSolver, StrategyTRTA};
+        use crate::test::simple_net_problem;
+
+        let (net, modifiers, hard_policy) = simple_net_problem();
+        let mut inner = <StrategyTRTA>::new(net, modifiers, hard_policy, None).unwrap();
+        inner.set_try_given_order(false);
+        inner.set_shuffle(false);
+        inner.set_solver(Box::new(RecordedSolver::new(vec![Some(vec![1, 0])])));
+
+        // the removal fails first, and the solver would be asked.
+        let mut strategy = SolverCallLimited::with_limit(*inner, 0);
+        match strategy.work(Stopper::new()) {
+            Err(Error::SolverCallLimitExceeded(0)) => {}
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        assert_eq!(strategy.num_solver_calls(), 0);
+
+        // with a single call, the solver resolves the conflict
+        strategy.set_max_solver_calls(Some(1));
+        assert!(strategy.work(Stopper::new()).is_ok());
+        assert_eq!(strategy.num_solver_calls(), 1);
+    }
+}
@@ -18,15 +18,16 @@
 //! # Snowcap
 //! Wrapper function to synthesize configuration updates
 
-use crate::hard_policies::HardPolicy;
+use crate::hard_policies::{HardPolicy, PolicyError};
 use crate::netsim::config::{Config, ConfigModifier};
-use crate::netsim::Network;
+use crate::netsim::{Network, NetworkError};
 use crate::optimizers::{Optimizer, OptimizerTRTA};
 use crate::soft_policies::SoftPolicy;
 use crate::strategies::{Strategy, StrategyTRTA};
 use crate::{Error, Stopper};
 
 use log::*;
+use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
 
@@ -86,7 +87,7 @@ pub fn synthesize(
     info!("Solving the problem...");
 
     // generate PushBackTreeStrategy
-    let mut strategy = StrategyTRTA::new(net, modifiers, hard_policy, time_limit)?;
+    let mut strategy = <StrategyTRTA>::new(net, modifiers, hard_policy, time_limit)?;
 
     // try to solve the problem
     match strategy.work(Stopper::new()) {
@@ -173,7 +174,7 @@ pub fn synthesize_parallel(
             let p = hard_policy.clone();
             let a = abort.clone();
             thread::spawn(move || {
-                let mut strategy = StrategyTRTA::new(n, m, p, Some(time_limit))?;
+                let mut strategy = <StrategyTRTA>::new(n, m, p, Some(time_limit))?;
                 let result = strategy.work(a.clone());
                 if result.is_ok() {
                     a.send_stop();
@@ -211,6 +212,111 @@ pub fn synthesize_parallel(
     }
 }
 
+/// # Race multiple seeded strategies
+///
+/// This function spawns `num_workers` [`StrategyTRTA`](crate::strategies::StrategyTRTA) threads,
+/// which solve the same problem, each seeded with its own index (see
+/// [`StrategyTRTA::new_seeded`](crate::strategies::StrategyTRTA::new_seeded)). All workers share a
+/// single [`Stopper`]. As soon as the first solution is found, the remaining workers are aborted,
+/// and the solution is returned. If no worker finds a solution, the error of one of them is
+/// returned, where errors other than `Error::Abort` take precedence. At least one worker is
+/// spawned.
+///
+/// In contrast to [`synthesize_parallel`], the network must already be in the initial state, and
+/// the runs are reproducible for a given number of workers (up to which one finishes first).
+///
+/// ## Usage
+///
+/// ```
+/// use snowcap::hard_policies::*;
+/// use snowcap::run_portfolio;
+/// use snowcap::Error;
+/// use std::time::Duration;
+/// # use snowcap::example_networks::*;
+///
+/// fn main() -> Result<(), Error> {
+///     // prepare the network and the modifiers
+///     // let net = ...
+///     // let modifiers = ...
+/// # let net = SimpleNet::net(0);
+/// # let modifiers = net.current_config().get_diff(&SimpleNet::final_config(&net, 0)).modifiers;
+///
+///     // prepare the policies
+///     // let hard_policy = ...
+/// # let hard_policy = HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+///
+///     // race four seeds
+///     let sequence = run_portfolio(net, modifiers, hard_policy, Some(Duration::from_secs(60)), 4)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub fn run_portfolio(
+    net: Network,
+    modifiers: Vec<ConfigModifier>,
+    hard_policy: HardPolicy,
+    time_budget: Option<Duration>,
+    num_workers: usize,
+) -> Result<Vec<ConfigModifier>, Error> {
+    let abort = Stopper::new();
+    let (tx, rx) = mpsc::channel();
+
+    let num_workers = num_workers.max(1);
+    info!("Spawning {} workers", num_workers);
+
+    let handles = (0..num_workers as u64)
+        .map(|seed| {
+            let n = net.clone();
+            let m = modifiers.clone();
+            let p = hard_policy.clone();
+            let a = abort.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let result = <StrategyTRTA>::new_seeded(n, m, p, time_budget, seed)
+                    .and_then(|mut strategy| strategy.work(a));
+                // the receiver is gone once a solution was found
+                let _ = tx.send((seed, result));
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+
+    let mut correct_result = None;
+    let mut some_error: Option<Error> = None;
+
+    // wait for the first solution, or until all workers are done
+    for (seed, result) in rx.iter() {
+        match result {
+            Ok(sequence) => {
+                info!("Worker {} found a valid solution!", seed);
+                abort.send_stop();
+                correct_result = Some(sequence);
+                break;
+            }
+            Err(e) => {
+                warn!("Worker {} had a problem solving the problem: {}", seed, e);
+                if matches!(some_error, None | Some(Error::Abort)) {
+                    some_error = Some(e);
+                }
+            }
+        }
+    }
+
+    // wait until the aborted workers are done
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    match (correct_result, some_error) {
+        (Some(sequence), _) => Ok(sequence),
+        (None, Some(e)) => {
+            error!("Could not find any result: {}", e);
+            Err(e)
+        }
+        _ => unreachable!(),
+    }
+}
+
 /// # Synthesize Configuration Updates while optimizing soft policies
 ///
 /// This is the main function to interact with the system. It uses the
@@ -285,3 +391,118 @@ pub fn optimize<SP: SoftPolicy + Clone>(
         }
     }
 }
+
+/// Reason why an ordering is rejected by [`verify_ordering`].
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum VerifyError {
+    /// The hard policies are already violated in the initial state, before applying any modifier.
+    #[error("The hard policies are violated in the initial state: {0:?}")]
+    InitialState(Vec<PolicyError>),
+    /// The modifier at the given position cannot be applied, or the hard policies cannot be
+    /// checked afterwards (e.g., because the network does not converge). The position is `None` if
+    /// the hard policies cannot be checked on the initial state.
+    #[error("Network error at modifier {0:?}: {1}")]
+    Network(Option<usize>, NetworkError),
+    /// The hard policies are violated after applying the modifier at the given position. The
+    /// errors of the watch are attached.
+    #[error("The hard policies are violated after modifier {0}: {1:?}")]
+    Violation(usize, Vec<PolicyError>),
+}
+
+/// # Verify an Ordering
+///
+/// Check an ordering, which was produced elsewhere (or written by hand), against the hard policies
+/// without running any synthesis. The hard policy is first checked on the initial state. Then, each
+/// modifier is applied in turn on a copy of the network, and the hard policy is checked after
+/// every step (including the final state).
+///
+/// If the initial state already violates the hard policies, [`VerifyError::InitialState`] is
+/// returned. If the ordering violates the hard policies, the position of the first modifier, after
+/// which the hard policy is violated, is returned, together with the errors of the watch. If a
+/// modifier cannot be applied (e.g., because the network does not converge), its position is
+/// returned together with the error of the network.
+///
+/// ```
+/// use snowcap::hard_policies::*;
+/// use snowcap::verify_ordering;
+/// # use snowcap::example_networks::*;
+///
+/// # let net = SimpleNet::net(0);
+/// # let modifiers = net.current_config().get_diff(&SimpleNet::final_config(&net, 0)).modifiers;
+/// let hard_policy = HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+///
+/// match verify_ordering(&net, &modifiers, &hard_policy) {
+///     Ok(()) => println!("The ordering is valid"),
+///     Err(e) => println!("The ordering is not valid: {}", e),
+/// }
+/// ```
+pub fn verify_ordering(
+    net: &Network,
+    ordering: &[ConfigModifier],
+    hard_policy: &HardPolicy,
+) -> Result<(), VerifyError> {
+    let mut net = net.clone();
+    let mut hard_policy = hard_policy.clone();
+    hard_policy.reset();
+    hard_policy.set_num_mods_if_none(ordering.len());
+    let watch_errors = |hard_policy: &HardPolicy| -> Vec<PolicyError> {
+        hard_policy.get_watch_errors().1.into_iter().flatten().collect()
+    };
+
+    // check the initial state
+    step_policy(&mut net, &mut hard_policy).map_err(|e| VerifyError::Network(None, e))?;
+    if !hard_policy.check_overwrite_finish(ordering.is_empty()) {
+        return Err(VerifyError::InitialState(watch_errors(&hard_policy)));
+    }
+
+    for (i, modifier) in ordering.iter().enumerate() {
+        if let Err(e) = net.apply_modifier(modifier) {
+            debug!("Cannot apply modifier {}: {}", i, e);
+            return Err(VerifyError::Network(Some(i), e));
+        }
+        step_policy(&mut net, &mut hard_policy).map_err(|e| VerifyError::Network(Some(i), e))?;
+        if !hard_policy.check_overwrite_finish(i + 1 == ordering.len()) {
+            return Err(VerifyError::Violation(i, watch_errors(&hard_policy)));
+        }
+    }
+    Ok(())
+}
+
+/// Perform a step of the hard policy on the current state of the network. Black holes and
+/// forwarding loops are reported after the state was recorded, and are caught by the check of the
+/// hard policy. Hence, only errors which prevent recording the state are returned.
+fn step_policy(net: &mut Network, hard_policy: &mut HardPolicy) -> Result<(), NetworkError> {
+    let num_steps = hard_policy.num_steps();
+    let mut fw_state = net.get_forwarding_state();
+    match hard_policy.step(net, &mut fw_state) {
+        Err(e) if hard_policy.num_steps() == num_steps => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// # Apply an ordering
+///
+/// Apply each modifier of the ordering in turn on the network, and return the resulting network,
+/// e.g., to inspect the final configuration or the forwarding state. The hard policies are not
+/// checked (see [`verify_ordering`]). If a modifier cannot be applied, its position is returned
+/// together with the error.
+///
+/// ```
+/// use snowcap::apply_ordering;
+/// # use snowcap::example_networks::*;
+///
+/// # let net = SimpleNet::net(0);
+/// # let final_config = SimpleNet::final_config(&net, 0);
+/// # let ordering = net.current_config().get_diff(&final_config).modifiers;
+/// let net = apply_ordering(net, &ordering).unwrap();
+/// assert_eq!(net.current_config(), &final_config);
+/// ```
+pub fn apply_ordering(
+    mut net: Network,
+    ordering: &[ConfigModifier],
+) -> Result<Network, (usize, NetworkError)> {
+    for (i, modifier) in ordering.iter().enumerate() {
+        net.apply_modifier(modifier).map_err(|e| (i, e))?;
+    }
+    Ok(net)
+}
@@ -32,3 +32,34 @@ mod test_solve_network;
 // NOTE These tests are deactivated, since this feature is temporarily disabled.
 //#[cfg(test)]
 //mod test_transient_behavior;
+#[cfg(test)]
+mod test_strategy_trta;
+
+#[cfg(test)]
+use crate::example_networks::{ExampleNetwork, SimpleNet};
+#[cfg(test)]
+use crate::hard_policies::HardPolicy;
+#[cfg(test)]
+use crate::netsim::{
+    config::{ConfigExpr::BgpSession, ConfigModifier},
+    BgpSessionType::{EBgp, IBgpPeer},
+    Network,
+};
+
+/// Reconfiguration problem on [`SimpleNet`] (variant 1), shared by the tests of the strategies:
+/// removing the session `r1 -> r4` before adding the session `r4 -> e4` causes a black hole at
+/// `r4`, which violates the reachability of all routers.
+#[cfg(test)]
+pub(crate) fn simple_net_problem() -> (Network, Vec<ConfigModifier>, HardPolicy) {
+    let net = SimpleNet::net(1);
+    let r1 = net.get_router_id("r1").unwrap();
+    let r4 = net.get_router_id("r4").unwrap();
+    let e4 = net.get_router_id("e4").unwrap();
+    let modifiers = vec![
+        ConfigModifier::Remove(BgpSession { source: r1, target: r4, session_type: IBgpPeer }),
+        ConfigModifier::Insert(BgpSession { source: r4, target: e4, session_type: EBgp }),
+    ];
+    let hard_policy =
+        HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+    (net, modifiers, hard_policy)
+}
@@ -318,3 +318,25 @@ fn test_config_undo_wrong_patch() {
     c.apply_patch(&patch).unwrap_err();
     assert_eq!(c, c_before);
 }
+
+#[test]
+fn test_modifier_routers() {
+    let (r0, r1, r2): (RouterId, RouterId, RouterId) = (0.into(), 1.into(), 2.into());
+    assert_eq!(
+        Insert(BgpSession { source: r1, target: r0, session_type: IBgpPeer }).routers(),
+        vec![r0, r1]
+    );
+    assert_eq!(
+        Remove(StaticRoute { router: r1, prefix: Prefix(0), target: r2 }).routers(),
+        vec![r1, r2]
+    );
+    // both the old and the new next hop are touched
+    assert_eq!(
+        Update {
+            from: StaticRoute { router: r1, prefix: Prefix(0), target: r2 },
+            to: StaticRoute { router: r1, prefix: Prefix(0), target: r0 },
+        }
+        .routers(),
+        vec![r0, r1, r2]
+    );
+}
@@ -15,7 +15,8 @@
 // with this program; if not, write to the Free Software Foundation, Inc.,
 // 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
 
-use crate::netsim::config::{Config, ConfigExpr::*};
+use crate::example_networks::{repetitions::Repetition50, ChainGadget, ExampleNetwork, SimpleNet};
+use crate::netsim::config::{Config, ConfigExpr::*, ConfigModifier::*};
 use crate::netsim::route_map::*;
 use crate::netsim::{AsId, BgpSessionType::*, Network, Prefix};
 
@@ -208,3 +209,67 @@ fn test_forwarding_state_carousel_gadget() {
         }
     }
 }
+
+#[test]
+fn test_forwarding_state_delta() {
+    // chain gadget with 54 routers
+    let mut net = ChainGadget::<Repetition50>::net(0);
+    let patch = net.current_config().get_diff(&ChainGadget::<Repetition50>::final_config(&net, 0));
+
+    let mut routers = net.get_routers();
+    routers.sort();
+
+    let mut state = net.get_forwarding_state();
+    for modifier in patch.modifiers {
+        // fill the cache, such that the delta needs to invalidate it
+        for router in routers.iter() {
+            for prefix in net.get_known_prefixes() {
+                let _ = state.get_route(*router, *prefix);
+            }
+        }
+
+        let snapshot = net.snapshot();
+        net.apply_modifier(&modifier).unwrap();
+        state = net.get_forwarding_state_delta(state, snapshot);
+        let mut expected = net.get_forwarding_state();
+
+        assert!(state == expected);
+        for router in routers.iter() {
+            for prefix in net.get_known_prefixes() {
+                assert_eq!(state.get_route(*router, *prefix), expected.get_route(*router, *prefix));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_forwarding_state_delta_igp_and_external() {
+    let mut net = SimpleNet::net(0);
+    let r1 = net.get_router_id("r1").unwrap();
+    let r2 = net.get_router_id("r2").unwrap();
+    let e1 = net.get_router_id("e1").unwrap();
+    let weight = |weight| IgpLinkWeight { source: r1, target: r2, weight };
+
+    // a changed link weight may change the next hop of every router
+    let state = net.get_forwarding_state();
+    let snapshot = net.snapshot();
+    net.apply_modifier(&Update { from: weight(1.0), to: weight(5.0) }).unwrap();
+    let state = net.get_forwarding_state_delta(state, snapshot);
+    assert!(state == net.get_forwarding_state());
+
+    // only the external router changes its next hop, all others learn it by BGP messages
+    let snapshot = net.snapshot();
+    net.retract_external_route(e1, Prefix(0)).unwrap();
+    let mut state = net.get_forwarding_state_delta(state, snapshot);
+    let mut expected = net.get_forwarding_state();
+    assert!(state == expected);
+    for router in net.get_routers() {
+        assert_eq!(state.get_route(router, Prefix(0)), expected.get_route(router, Prefix(0)));
+    }
+
+    // a snapshot which is no longer part of the history falls back to collecting all next hops
+    let snapshot = net.snapshot();
+    net.undo_action().unwrap();
+    let state = net.get_forwarding_state_delta(state, snapshot);
+    assert!(state == net.get_forwarding_state());
+}
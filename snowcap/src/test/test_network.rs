@@ -22,9 +22,12 @@ use crate::netsim::network::Network;
 use crate::netsim::route_map::{
     RouteMap, RouteMapDirection::*, RouteMapMatch as Match, RouteMapSet as Set, RouteMapState::*,
 };
-use crate::netsim::{AsId, BgpSessionType::*, LinkWeight, NetworkError, Prefix, RouterId};
+use crate::netsim::{
+    AsId, BgpSessionType::*, LinkWeight, NetworkError, Prefix, RouterId, TopologyNode,
+};
 use lazy_static::lazy_static;
 use petgraph::algo::FloatMeasure;
+use petgraph::graph::Graph;
 
 lazy_static! {
     static ref R1: RouterId = 0.into();
@@ -257,6 +260,60 @@ fn test_bgp_connectivity() {
     assert_eq!(net_save_1.undo_action(), Ok(false));
 }
 
+#[test]
+fn test_from_petgraph() {
+    let mut graph = Graph::new_undirected();
+    for name in ["R1", "R2", "R3", "R4"] {
+        graph.add_node(TopologyNode::Internal(name.to_string()));
+    }
+    assert_eq!(*E1, graph.add_node(TopologyNode::External("E1".to_string(), AsId(65101))));
+    assert_eq!(*E4, graph.add_node(TopologyNode::External("E4".to_string(), AsId(65104))));
+    for (a, b) in [(*R1, *E1), (*R1, *R2), (*R1, *R3), (*R2, *R3), (*R2, *R4), (*R3, *R4)] {
+        graph.add_edge(a, b, ());
+    }
+    graph.add_edge(*R4, *E4, ());
+
+    let expected = get_test_net_bgp();
+    let net = Network::from_petgraph(&graph, expected.current_config()).unwrap();
+    assert!(net == expected);
+    assert_eq!(net.get_router_name(*E4), Ok("E4"));
+
+    // the configuration must only reference routers of the graph
+    graph.remove_node(*E4);
+    assert!(Network::from_petgraph(&graph, expected.current_config()).is_err());
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let mut net = get_test_net_bgp();
+    let p = Prefix(0);
+
+    let initial = net.snapshot();
+    let net_save_1 = net.clone();
+    net.advertise_external_route(*E1, p, vec![AsId(65101), AsId(65201)], None, None).unwrap();
+    let after_e1 = net.snapshot();
+    let net_save_2 = net.clone();
+    net.advertise_external_route(*E4, p, vec![AsId(65104), AsId(65201)], None, None).unwrap();
+    net.apply_modifier(&Remove(BgpSession { source: *R3, target: *R4, session_type: IBgpPeer }))
+        .unwrap();
+
+    // restoring the current state does not change anything
+    let current = net.clone();
+    net.restore(net.snapshot()).unwrap();
+    assert!(net == current);
+
+    // restore multiple actions at once
+    net.restore(after_e1).unwrap();
+    assert!(net == net_save_2);
+    net.restore(initial).unwrap();
+    assert!(net == net_save_1);
+
+    // the snapshot is no longer part of the history
+    assert!(matches!(net.restore(after_e1), Err(NetworkError::HistoryError(_))));
+    let mut cloned = net_save_2.clone();
+    assert!(matches!(cloned.restore(after_e1), Err(NetworkError::HistoryError(_))));
+}
+
 #[test]
 fn test_static_route() {
     let mut net = get_test_net_bgp().clone();
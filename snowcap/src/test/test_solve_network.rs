@@ -21,10 +21,10 @@ use crate::example_networks::*;
 
 use crate::hard_policies::*;
 use crate::modifier_ordering::*;
-use crate::netsim::printer;
+use crate::netsim::{printer, Prefix};
 use crate::permutators::*;
 use crate::strategies::*;
-use crate::{Error, Stopper};
+use crate::{apply_ordering, verify_ordering, Error, Stopper, VerifyError};
 
 use std::time::Duration;
 
@@ -380,3 +380,60 @@ fn impossible_constraint() {
     eprintln!("StrategyTRTA");
     test_net_bad_policy::<StrategyTRTA>();
 }
+
+#[test]
+fn verify_external_ordering() {
+    let net = CarouselGadget::net(0);
+    let cf = CarouselGadget::final_config(&net, 0);
+    let modifiers = net.current_config().get_diff(&cf).modifiers;
+    let hard_policy =
+        HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+
+    // the carousel gadget has no valid ordering
+    match verify_ordering(&net, &modifiers, &hard_policy) {
+        Err(VerifyError::Violation(pos, errors)) => {
+            assert!(pos < modifiers.len());
+            assert!(!errors.is_empty());
+        }
+        e => panic!("Unexpected result: {:?}", e),
+    }
+
+    // an invalid initial state is reported before applying any modifier (no router knows a route
+    // towards the unknown prefix)
+    let unreachable = HardPolicy::reachability(net.get_routers().iter(), [Prefix(100)].iter());
+    assert!(matches!(
+        verify_ordering(&net, &modifiers, &unreachable),
+        Err(VerifyError::InitialState(errors)) if !errors.is_empty()
+    ));
+
+    // a synthesized ordering is valid
+    let net = SimpleNet::net(0);
+    let cf = SimpleNet::final_config(&net, 0);
+    let hard_policy =
+        HardPolicy::reachability(net.get_routers().iter(), net.get_known_prefixes().iter());
+    let sequence = PushBackTreeStrategy::<SimpleOrdering>::synthesize(
+        net.clone(),
+        cf,
+        hard_policy.clone(),
+        Some(Duration::from_secs(60)),
+        Stopper::new(),
+    )
+    .unwrap();
+    assert_eq!(verify_ordering(&net, &sequence, &hard_policy), Ok(()));
+}
+
+#[test]
+fn apply_external_ordering() {
+    let net = SimpleNet::net(0);
+    let cf = SimpleNet::final_config(&net, 0);
+    let modifiers = net.current_config().get_diff(&cf).modifiers;
+
+    // the final network has the final configuration
+    let final_net = apply_ordering(net.clone(), &modifiers).unwrap();
+    assert_eq!(final_net.current_config(), &cf);
+
+    // applying a modifier twice fails at the second position
+    let ordering = vec![modifiers[0].clone(), modifiers[0].clone()];
+    let (pos, _) = apply_ordering(net, &ordering).unwrap_err();
+    assert_eq!(pos, 1);
+}
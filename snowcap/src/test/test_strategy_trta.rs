@@ -0,0 +1,101 @@
+// Snowcap: Synthesizing Network-Wide Configuration Updates
+// Copyright (C) 2021  Tibor Schneider
+//
+// This program is free software; you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation; either version 2 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along
+// with this program; if not, write to the Free Software Foundation, Inc.,
+// 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+
+#![cfg(test)]
+#![cfg(feature = "ltl-solver")]
+// Tests of the conflict-learning loop of `StrategyTRTA`, with the solver replaced by a
+// `MockLtlSolver`. Removing the session `r1 -> r4` before adding the session `r4 -> e4` causes
+// a black hole at `r4`, from which the constraint `G(e0 -> (N(G(! e1))))` is learned.
+
+use crate::netsim::config::ConfigModifier;
+use crate::strategies::*;
+use crate::test::simple_net_problem;
+use crate::{Error, Stopper};
+
+const CLAUSE: &str = "G(e0 -> (N(G(! e1))))";
+
+fn setup(solver: MockLtlSolver) -> (StrategyTRTA, Vec<ConfigModifier>) {
+    let (net, modifiers, hard_policy) = simple_net_problem();
+    let mut strategy = *<StrategyTRTA>::new(net, modifiers.clone(), hard_policy, None).unwrap();
+    strategy.set_shuffle(false);
+    strategy.set_try_given_order(false);
+    strategy.set_record_transcript(None);
+    strategy.set_solver(Box::new(solver));
+    (strategy, modifiers)
+}
+
+#[test]
+fn learned_clause_is_solved() {
+    let (mut strategy, modifiers) = setup(MockLtlSolver::new(vec![(CLAUSE, Some(vec![1, 0]))]));
+    let ordering = strategy.work(Stopper::new()).unwrap();
+    assert_eq!(ordering, vec![modifiers[1].clone(), modifiers[0].clone()]);
+    let stats = strategy.stats();
+    assert_eq!(stats.solver_calls, 1);
+    assert_eq!(stats.conflicts.len(), 1);
+    assert_eq!(stats.conflicts[0].doing, 0);
+    assert_eq!(stats.conflicts[0].matched_groups, vec![vec![1]]);
+    assert_eq!(stats.conflicts[0].clause, CLAUSE);
+}
+
+#[test]
+fn invalid_model_causes_conflict() {
+    // the solver suggests the failing order, which runs into the same conflict again
+    let (mut strategy, modifiers) = setup(MockLtlSolver::new(vec![(CLAUSE, Some(vec![0, 1]))]));
+    let ordering = strategy.work(Stopper::new()).unwrap();
+    assert_eq!(ordering, vec![modifiers[1].clone(), modifiers[0].clone()]);
+    let stats = strategy.stats();
+    assert_eq!(stats.solver_calls, 1);
+    assert_eq!(stats.conflicts.len(), 2);
+    assert_eq!(stats.conflicts[0], stats.conflicts[1]);
+}
+
+#[test]
+fn unsat_has_no_safe_ordering() {
+    let (mut strategy, _) = setup(MockLtlSolver::new(vec![(CLAUSE, None)]));
+    assert!(matches!(strategy.work(Stopper::new()), Err(Error::ProbablyNoSafeOrdering)));
+    assert_eq!(strategy.stats().solver_calls, 1);
+    assert_eq!(strategy.transcript()[0].result, None);
+    // the conflicts explain why no ordering exists
+    let conflicts = strategy.last_conflicts();
+    assert_eq!(conflicts, strategy.stats().conflicts.as_slice());
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].prefix, Vec::<usize>::new());
+    assert_eq!(conflicts[0].doing, 0);
+    assert_eq!(conflicts[0].clause, CLAUSE);
+}
+
+#[test]
+fn unexpected_query() {
+    let (mut strategy, _) = setup(MockLtlSolver::new(vec![("x7", Some(vec![1, 0]))]));
+    assert!(matches!(strategy.work(Stopper::new()), Err(Error::Internal(_))));
+    let (mut strategy, _) = setup(MockLtlSolver::new(Vec::<(String, _)>::new()));
+    assert!(matches!(strategy.work(Stopper::new()), Err(Error::Internal(_))));
+}
+
+#[test]
+fn replay_transcript() {
+    let (mut strategy, _) = setup(MockLtlSolver::new(vec![("", Some(vec![1, 0]))]));
+    let ordering = strategy.work(Stopper::new()).unwrap();
+    let transcript = strategy.transcript().to_vec();
+    assert_eq!(transcript.len(), 1);
+    assert!(transcript[0].query.starts_with(&format!("({}) & ", CLAUSE)));
+
+    // replaying the recorded transcript reproduces the same run
+    let (mut replay, _) = setup(MockLtlSolver::from_transcript(&transcript));
+    assert_eq!(replay.work(Stopper::new()).unwrap(), ordering);
+    assert_eq!(replay.transcript(), transcript.as_slice());
+}
@@ -89,13 +89,66 @@ impl ZooTopology {
         info!("Parsing the file and reading the graph");
         let graph = gml_parser::parse_gml_graph(gml_filename.as_ref())?;
         info!("Successfully read the GML file.");
+        Ok(Self::from_graph(graph, seed))
+    }
+
+    /// Return a new, (predictably) random ZooTopology instead of reading it from a GML file. The
+    /// topology contains `num_internal` internal routers, connected by a random spanning tree and
+    /// `num_internal / 2` additional random links. Each of the `num_external` external routers
+    /// (each in its own AS) is connected to a random internal router. All link weights are set to
+    /// 1, as for topologies read from GML files.
+    pub fn random(num_internal: usize, num_external: usize, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut graph: Graph<NodeData, LinkWeight, Undirected, u32> = Graph::new_undirected();
+        let internal: Vec<NodeIdx> = (0..num_internal)
+            .map(|i| {
+                graph.add_node(NodeData {
+                    name: format!("r{}", i),
+                    external: false,
+                    as_id: AsId(65001),
+                    net_idx: None,
+                })
+            })
+            .collect();
+        // random spanning tree, such that the network is connected
+        for i in 1..num_internal {
+            let j = rng.gen_range(0, i);
+            graph.add_edge(internal[i], internal[j], 1.0);
+        }
+        // additional random links
+        if num_internal > 1 {
+            for _ in 0..num_internal / 2 {
+                let a = internal[rng.gen_range(0, num_internal)];
+                let b = internal[rng.gen_range(0, num_internal)];
+                if a != b && !graph.contains_edge(a, b) {
+                    graph.add_edge(a, b, 1.0);
+                }
+            }
+        }
+        // external routers
+        if num_internal > 0 {
+            for i in 0..num_external {
+                let node = graph.add_node(NodeData {
+                    name: format!("e{}", i),
+                    external: true,
+                    as_id: AsId(65101 + i as u32),
+                    net_idx: None,
+                });
+                graph.add_edge(node, internal[rng.gen_range(0, num_internal)], 1.0);
+            }
+        }
+        Self::from_graph(graph, seed)
+    }
+
+    /// Create a new instance from the physical graph.
+    fn from_graph(graph: Graph<NodeData, LinkWeight, Undirected, u32>, seed: u64) -> Self {
         // clone the nodes of the physical graph and make sure that the nodes indices are always the same
         let mut ibgp_graph: Graph<(), (), Directed, u32> = Graph::new();
         for _ in graph.node_indices() {
             ibgp_graph.add_node(());
         }
 
-        Ok(Self {
+        Self {
             rng: StdRng::seed_from_u64(seed),
             graph,
             ibgp_graph,
@@ -103,7 +156,7 @@ impl ZooTopology {
             disconnected: HashSet::new(),
             maintenance: HashSet::new(),
             create_ibgp_peers: true,
-        })
+        }
     }
 
     /// This funciton applies a scenario to a topology (including some common configuration). This
@@ -1482,6 +1535,20 @@ mod test {
         }
     }
 
+    #[test]
+    fn random() {
+        let t = ZooTopology::random(10, 3, 42);
+        assert_eq!(t.graph.node_count(), 13);
+        assert_eq!(t.graph.node_indices().filter(|n| t.graph[*n].external).count(), 3);
+        assert_eq!(petgraph::algo::connected_components(&t.graph), 1);
+        // the same seed results in the same topology
+        let t2 = ZooTopology::random(10, 3, 42);
+        assert_eq!(
+            t.graph.edge_references().map(|e| (e.source(), e.target())).collect::<Vec<_>>(),
+            t2.graph.edge_references().map(|e| (e.source(), e.target())).collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn ibgp_spanning_forest() {
         let gml_filename: String = format!("{}/test_files/switch.gml", env!("CARGO_MANIFEST_DIR"));